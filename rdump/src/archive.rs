@@ -0,0 +1,215 @@
+use anyhow::{anyhow, Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::evaluator::Hunk;
+use crate::roots::unique_relative_path;
+
+/// The name of the generated file listing the query and the archived files.
+pub const INDEX_FILE: &str = "INDEX.md";
+
+/// The kinds of archive `--archive` can write, chosen by the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveKind {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if name.ends_with(".zip") {
+            Ok(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(ArchiveKind::TarGz)
+        } else {
+            Err(anyhow!(
+                "Unsupported archive '{}'. Use a .zip, .tar.gz or .tgz file name.",
+                path.display()
+            ))
+        }
+    }
+}
+
+/// Writes every matching file into a zip or tar.gz archive at `archive_path`, keeping
+/// its path relative to the root it was found under, together with an `INDEX.md`
+/// listing `query` and the matched hunks.
+///
+/// Files are stored byte for byte, so binary files survive unchanged. Symlinks are
+/// stored as links rather than followed, so an archive never contains files from
+/// outside the search root.
+pub fn write_archive(
+    archive_path: &Path,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    roots: &[PathBuf],
+    query: &str,
+) -> Result<()> {
+    let kind = ArchiveKind::from_path(archive_path)?;
+    let entries: Vec<(&PathBuf, PathBuf)> = matching_files
+        .iter()
+        .map(|(path, _)| (path, archive_name(path, roots)))
+        .collect();
+    // Don't shadow a real `INDEX.md` that is part of the results.
+    let with_index = !entries.iter().any(|(_, name)| name == Path::new(INDEX_FILE));
+    let index = build_index(query, matching_files, &entries);
+
+    let file = File::create(archive_path)
+        .with_context(|| format!("Failed to create archive {}", archive_path.display()))?;
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = ZipWriter::new(file);
+            let options = SimpleFileOptions::default();
+            if with_index {
+                zip.start_file(INDEX_FILE, options)?;
+                zip.write_all(index.as_bytes())?;
+            }
+            for (path, name) in &entries {
+                let name = zip_name(name);
+                let metadata = fs::symlink_metadata(path)
+                    .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
+                if metadata.file_type().is_symlink() {
+                    let target = fs::read_link(path)?;
+                    zip.add_symlink(name, target.to_string_lossy(), options)?;
+                    continue;
+                }
+                #[cfg(unix)]
+                let options = {
+                    use std::os::unix::fs::PermissionsExt;
+                    options.unix_permissions(metadata.permissions().mode())
+                };
+                zip.start_file(name, options)?;
+                let bytes = fs::read(path)
+                    .with_context(|| format!("Failed to read file {}", path.display()))?;
+                zip.write_all(&bytes)?;
+            }
+            zip.finish()?;
+        }
+        ArchiveKind::TarGz => {
+            let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+            tar.follow_symlinks(false);
+            if with_index {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(index.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+                tar.append_data(&mut header, INDEX_FILE, index.as_bytes())?;
+            }
+            for (path, name) in &entries {
+                tar.append_path_with_name(path, name)
+                    .with_context(|| format!("Failed to archive {}", path.display()))?;
+            }
+            tar.into_inner()?.finish()?.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The path a file is stored under: relative to its root (see
+/// [`unique_relative_path`]), with any `/`, `.` or `..` components dropped so every
+/// entry stays inside the archive.
+fn archive_name(path: &Path, roots: &[PathBuf]) -> PathBuf {
+    unique_relative_path(roots, path)
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+/// Zip entry names always use `/`, whatever the platform.
+fn zip_name(name: &Path) -> String {
+    name.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn build_index(
+    query: &str,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    entries: &[(&PathBuf, PathBuf)],
+) -> String {
+    let mut index = String::from("# rdump archive\n\n");
+    let _ = writeln!(index, "Query: `{query}`\n");
+    let _ = writeln!(index, "{} matching files:\n", matching_files.len());
+    for ((_, hunks), (_, name)) in matching_files.iter().zip(entries) {
+        let name = zip_name(name);
+        if hunks.is_empty() {
+            let _ = writeln!(index, "- `{name}`");
+            continue;
+        }
+        let lines: Vec<String> = hunks
+            .iter()
+            .map(|h| format!("{}-{}", h.start_point.row + 1, h.end_point.row + 1))
+            .collect();
+        let _ = writeln!(index, "- `{name}` (lines {})", lines.join(", "));
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_kind_from_path() {
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("out/bundle.zip")).unwrap(),
+            ArchiveKind::Zip
+        );
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("bundle.TAR.GZ")).unwrap(),
+            ArchiveKind::TarGz
+        );
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("bundle.tgz")).unwrap(),
+            ArchiveKind::TarGz
+        );
+        assert!(ArchiveKind::from_path(Path::new("bundle.rar")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_are_stored_as_links() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().join("project");
+        fs::create_dir_all(&root)?;
+        fs::write(root.join("db.rs"), "pub fn connect() {}\n")?;
+        std::os::unix::fs::symlink("db.rs", root.join("link.rs"))?;
+
+        let archive_path = dir.path().join("bundle.tar.gz");
+        let files = [(root.join("link.rs"), vec![])];
+        write_archive(&archive_path, &files, &[root], "name:link.rs")?;
+
+        let decoder = flate2::read::GzDecoder::new(File::open(&archive_path)?);
+        let mut archive = tar::Archive::new(decoder);
+        let mut entries = archive.entries()?;
+        let index = entries.next().unwrap()?;
+        assert_eq!(index.path()?.to_str(), Some(INDEX_FILE));
+        let link = entries.next().unwrap()?;
+        assert_eq!(link.path()?.to_str(), Some("link.rs"));
+        assert!(link.header().entry_type().is_symlink());
+        assert_eq!(link.link_name()?.unwrap().to_str(), Some("db.rs"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_name_stays_inside_archive() {
+        let root = &[PathBuf::from("./project")];
+        assert_eq!(
+            archive_name(Path::new("./project/src/main.rs"), root),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(
+            archive_name(Path::new("/etc/../tmp/a.rs"), root),
+            PathBuf::from("etc/tmp/a.rs")
+        );
+    }
+}
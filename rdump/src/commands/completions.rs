@@ -0,0 +1,78 @@
+use crate::config::{self, Config};
+use crate::predicates::code_aware::profiles::list_language_profiles;
+use crate::types::all_types;
+use crate::{Cli, CompletionsArgs};
+use anyhow::Result;
+use clap::builder::PossibleValuesParser;
+use clap::{Command, CommandFactory};
+use clap_complete::generate;
+use std::io;
+
+pub fn run_completions(args: CompletionsArgs) -> Result<()> {
+    // A broken config shouldn't stop completions from working; it just means no
+    // preset or custom type names to offer.
+    let config = config::load_config().unwrap_or_default();
+    let mut command = completion_command(&config);
+    generate(args.shell, &mut command, "rdump", &mut io::stdout());
+    Ok(())
+}
+
+/// The CLI as the completion scripts see it: `--preset`, `--type` and
+/// `lang describe` complete to the presets, file types and languages known now.
+fn completion_command(config: &Config) -> Command {
+    let mut presets: Vec<String> = config.presets.keys().cloned().collect();
+    presets.sort();
+    let types: Vec<String> = all_types(config).into_keys().collect();
+    let languages: Vec<String> = list_language_profiles()
+        .iter()
+        .map(|profile| profile.name.to_lowercase())
+        .collect();
+
+    Cli::command()
+        .mut_subcommand("search", |search| {
+            search
+                .mut_arg("preset", |arg| {
+                    arg.value_parser(PossibleValuesParser::new(presets))
+                })
+                .mut_arg("file_type", |arg| {
+                    arg.value_parser(PossibleValuesParser::new(types))
+                })
+        })
+        .mut_subcommand("lang", |lang| {
+            lang.mut_subcommand("describe", |describe| {
+                describe.mut_arg("language", |arg| {
+                    arg.value_parser(PossibleValuesParser::new(languages))
+                })
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PresetEntry;
+    use clap_complete::Shell;
+
+    fn script(shell: Shell, config: &Config) -> String {
+        let mut buffer = Vec::new();
+        generate(shell, &mut completion_command(config), "rdump", &mut buffer);
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_completions_offer_presets_and_languages() {
+        let mut config = Config::default();
+        config.presets.insert(
+            "rust-funcs".into(),
+            PresetEntry::Query("ext:rs & func:.".into()),
+        );
+
+        let bash = script(Shell::Bash, &config);
+        assert!(bash.contains("rust-funcs"), "{bash}");
+        assert!(bash.contains("python"), "{bash}");
+        assert!(bash.contains("json-extended"), "{bash}");
+
+        let fish = script(Shell::Fish, &config);
+        assert!(fish.contains("rust-funcs"), "{fish}");
+    }
+}
@@ -0,0 +1,248 @@
+use crate::config::{self, Config, CONFIG_VERSION};
+use crate::ConfigAction;
+use anyhow::{anyhow, Context, Result};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The main entry point for the `config` command.
+pub fn run_config(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Init { force } => {
+            let path = env::current_dir()?.join(".rdump.toml");
+            init_file(&path, force)?;
+            println!("Created {}", path.display());
+        }
+        ConfigAction::Migrate { dry_run } => {
+            let paths = [
+                config::global_config_path(),
+                config::find_local_config(&env::current_dir()?),
+            ];
+            let mut found = false;
+            for path in paths.into_iter().flatten().filter(|path| path.exists()) {
+                found = true;
+                migrate_file(&path, dry_run, &mut io::stdout().lock())?;
+            }
+            if !found {
+                println!("No config files found.");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What `config init` writes: the current version and a commented example of
+/// every section.
+const STARTER_CONFIG: &str = r#"version = 1
+
+# Extra comment markers (besides TODO, FIXME, etc.) recognized by `tag:.`.
+# tags = ["PERF", "SECURITY"]
+
+# Named queries, used with `rdump search -p NAME`.
+[presets]
+# rust-src = "ext:rs & !path:tests/"
+# backend = { extends = "rust-src", query = "path:server/" }
+
+# Custom `--type` file types.
+[types]
+# docs = ["md", "rst"]
+
+# Default `search` flags for this project. A flag given on the command line wins.
+[defaults]
+# format = "markdown"
+# line-numbers = true
+# context = 3
+# before-context = 3
+# after-context = 3
+# merge-adjacent-hunks = 1
+# color = "auto"
+# theme = "InspiredGitHub"
+# max-depth = 5
+# hidden = true
+# no-ignore = true
+# unsorted = true
+# workspace = true
+# pager = "less -R"
+"#;
+
+/// Writes `STARTER_CONFIG` to `path`, refusing to replace a file unless `force`.
+fn init_file(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(anyhow!(
+            "{} already exists; use --force to overwrite it",
+            path.display()
+        ));
+    }
+    fs::write(path, STARTER_CONFIG)
+        .with_context(|| format!("Failed to write config to {}", path.display()))
+}
+
+/// Upgrades one config file, printing a diff of the changes first.
+fn migrate_file(path: &Path, dry_run: bool, out: &mut impl Write) -> Result<()> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config at {}", path.display()))?;
+    let Some(migrated) = migrate(&text, path)? else {
+        writeln!(
+            out,
+            "{}: already at version {CONFIG_VERSION}",
+            path.display()
+        )?;
+        return Ok(());
+    };
+
+    writeln!(out, "--- {}", path.display())?;
+    writeln!(out, "+++ {} (version {CONFIG_VERSION})", path.display())?;
+    write_diff(out, &text, &migrated)?;
+    if dry_run {
+        writeln!(out, "Dry run: {} was not changed.", path.display())?;
+    } else {
+        fs::write(path, &migrated)
+            .with_context(|| format!("Failed to write config to {}", path.display()))?;
+        writeln!(
+            out,
+            "Migrated {} to version {CONFIG_VERSION}.",
+            path.display()
+        )?;
+    }
+    Ok(())
+}
+
+/// One step of `MIGRATIONS`: rewrites a file's text from one version to the next.
+type Migration = fn(&str) -> String;
+
+/// `MIGRATIONS[n]` upgrades version `n` to `n + 1`. Files without a version are
+/// version 0. Steps edit the text rather than re-serializing the config, so
+/// comments and formatting survive.
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [add_version_marker];
+
+/// 0 -> 1: marks the file as version 1; nothing else changed.
+fn add_version_marker(text: &str) -> String {
+    format!("version = 1\n{text}")
+}
+
+/// The text of the config file at `path` upgraded to `CONFIG_VERSION`, or `None`
+/// if it's already there. Running it on its own output changes nothing.
+fn migrate(text: &str, path: &Path) -> Result<Option<String>> {
+    let version = config::parse_config(text, path)?.version.unwrap_or(0);
+    if version == CONFIG_VERSION {
+        return Ok(None);
+    }
+    let mut migrated = text.to_string();
+    for step in &MIGRATIONS[version as usize..] {
+        migrated = step(&migrated);
+    }
+    // Catch a step that forgets to update the version.
+    let config: Config = toml::from_str(&migrated)
+        .map_err(|e| anyhow!("Migrating {} produced invalid TOML: {e}", path.display()))?;
+    if config.version != Some(CONFIG_VERSION) {
+        return Err(anyhow!(
+            "Migrating {} didn't reach version {CONFIG_VERSION}",
+            path.display()
+        ));
+    }
+    Ok(Some(migrated))
+}
+
+/// Lines of context printed around each change.
+const DIFF_CONTEXT: usize = 2;
+
+/// Prints a line diff of `old` and `new`: removed lines with `-`, added ones with
+/// `+`, and up to `DIFF_CONTEXT` unchanged lines around each change.
+fn write_diff(out: &mut impl Write, old: &str, new: &str) -> Result<()> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j]: the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..lines.len()).filter(|&k| lines[k].0 != ' ').collect();
+    let mut last_printed = None;
+    for (k, (sign, line)) in lines.iter().enumerate() {
+        let near_change = changed.iter().any(|&c| c.abs_diff(k) <= DIFF_CONTEXT);
+        if !near_change {
+            continue;
+        }
+        if last_printed.is_some_and(|last| last + 1 < k) {
+            writeln!(out, "...")?;
+        }
+        writeln!(out, "{sign}{line}")?;
+        last_printed = Some(k);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrated(text: &str) -> Option<String> {
+        migrate(text, Path::new("config.toml")).unwrap()
+    }
+
+    #[test]
+    fn test_migrate_adds_version_marker() {
+        let old = "# my presets\n[presets]\nrust = \"ext:rs\"\n";
+        let new = migrated(old).unwrap();
+        assert_eq!(new, format!("version = 1\n{old}"));
+        // The comment survives, and a second run has nothing to do.
+        assert!(new.contains("# my presets"));
+        assert_eq!(migrated(&new), None);
+    }
+
+    #[test]
+    fn test_current_version_needs_no_migration() {
+        assert_eq!(
+            migrated("version = 1\n[presets]\nrust = \"ext:rs\"\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_newer_version_is_refused() {
+        let err = migrate("version = 99\n", Path::new("config.toml")).unwrap_err();
+        assert!(err.to_string().contains("config version 99"), "{err}");
+    }
+
+    #[test]
+    fn test_starter_config_is_valid() {
+        let config = config::parse_config(STARTER_CONFIG, Path::new(".rdump.toml")).unwrap();
+        assert_eq!(config.version, Some(CONFIG_VERSION));
+        assert!(config.presets.is_empty());
+        assert_eq!(config.defaults, Default::default());
+        assert!(STARTER_CONFIG.contains("# format = \"markdown\""));
+    }
+
+    #[test]
+    fn test_write_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\n";
+        let mut out = Vec::new();
+        write_diff(&mut out, old, new).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), " b\n c\n-d\n+D\n e\n f\n");
+    }
+}
@@ -0,0 +1,278 @@
+use crate::commands::search::{splice_presets, validate_ast_predicates};
+use crate::config::{self, Config};
+use crate::evaluator::{
+    node_label, EvaluationTier, Evaluator, FileContext, MatchResult, TraceStep,
+};
+use crate::parser::{self, AstNode, LogicalOperator, PredicateKey};
+use crate::predicates::{self, PredicateRegistry};
+use crate::ExplainArgs;
+use anyhow::{anyhow, Result};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub fn run_explain(args: ExplainArgs) -> Result<()> {
+    let config = config::load_config()?;
+    let query = splice_presets(args.query, &args.preset, &config)?
+        .ok_or_else(|| anyhow!("No query provided. Please provide a query or use a preset."))?;
+    let ast = parser::parse_query(&query)?;
+    let registry = full_registry(&config);
+    validate_ast_predicates(&ast, &registry)?;
+
+    let mut out = io::stdout().lock();
+    write_plan(&mut out, &query, &args.preset, &config, &ast)?;
+    if let Some(path) = &args.file {
+        let evaluator = Evaluator::new(ast, registry);
+        let mut context = FileContext::new(path.clone(), PathBuf::from("."));
+        let (result, trace) = evaluator.evaluate_traced(&mut context)?;
+        writeln!(out)?;
+        write_trace(&mut out, path, &trace, &result)?;
+    }
+    Ok(())
+}
+
+/// The registry `search` evaluates files with, less the `duplicate:` set, which
+/// needs every candidate file.
+fn full_registry(config: &Config) -> PredicateRegistry {
+    let mut registry = predicates::create_predicate_registry();
+    if !config.tags.is_empty() {
+        registry.insert(
+            PredicateKey::Tag,
+            predicates::tag::TagEvaluator::with_extra_tags(&config.tags),
+        );
+    }
+    registry
+}
+
+/// Prints the query with its presets spliced in, its syntax tree, and the order
+/// `search` evaluates it in.
+fn write_plan(
+    out: &mut impl Write,
+    query: &str,
+    presets: &[String],
+    config: &Config,
+    ast: &AstNode,
+) -> Result<()> {
+    writeln!(out, "Query: {query}")?;
+    for name in presets {
+        if let Some(preset) = config.presets.get(name) {
+            writeln!(out, "  preset {name}: {}", preset.query())?;
+        }
+    }
+
+    writeln!(out, "\nSyntax tree:")?;
+    write_tree(out, ast, "", "")?;
+
+    let mut prefilter = Vec::new();
+    collect_prefilter(ast, &mut prefilter);
+    let mut all = Vec::new();
+    collect_predicates(ast, &mut all);
+    let cost = Evaluator::new(ast.clone(), PredicateRegistry::new()).estimate_cost();
+
+    writeln!(out, "\nEvaluation:")?;
+    if prefilter.is_empty() {
+        writeln!(
+            out,
+            "  1. Metadata pre-filter: none, so every candidate file is evaluated in full."
+        )?;
+    } else {
+        writeln!(out, "  1. Metadata pre-filter: {}", prefilter.join(", "))?;
+        writeln!(out, "     Files it rules out are never read.")?;
+    }
+    writeln!(
+        out,
+        "  2. Full evaluation, left to right: {}",
+        all.join(", ")
+    )?;
+    let reads = if cost.requires_tree_sitter {
+        "Reads each file's content and parses it with tree-sitter."
+    } else if cost.requires_content_read {
+        "Reads each file's content."
+    } else {
+        "Needs only metadata, so no file is read."
+    };
+    writeln!(out, "     {reads}")?;
+    Ok(())
+}
+
+/// Prints `node` and its children as an indented tree, each line starting with
+/// `lead`, and each child's with `indent`.
+fn write_tree(out: &mut impl Write, node: &AstNode, lead: &str, indent: &str) -> Result<()> {
+    let label = node_label(node);
+    let description = match node {
+        AstNode::Predicate(key, _) => format!("[{}]", tier_name(EvaluationTier::of(key))),
+        AstNode::LogicalOp(LogicalOperator::And, _, _) => {
+            "and: skips the right side unless the left side matches".to_string()
+        }
+        AstNode::LogicalOp(LogicalOperator::Or, _, _) => {
+            "or: skips the right side if the left side matches the whole file".to_string()
+        }
+        AstNode::Not(_) => "not".to_string(),
+    };
+    writeln!(out, "{lead}{label}  {description}")?;
+
+    let children: Vec<&AstNode> = match node {
+        AstNode::Predicate(..) => vec![],
+        AstNode::LogicalOp(_, left, right) => vec![left, right],
+        AstNode::Not(inner) => vec![inner],
+    };
+    for (i, child) in children.iter().enumerate() {
+        let (branch, next) = if i + 1 == children.len() {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        write_tree(
+            out,
+            child,
+            &format!("{indent}{branch}"),
+            &format!("{indent}{next}"),
+        )?;
+    }
+    Ok(())
+}
+
+fn tier_name(tier: EvaluationTier) -> &'static str {
+    match tier {
+        EvaluationTier::Metadata => "metadata",
+        EvaluationTier::Content => "content",
+        EvaluationTier::Syntax => "semantic",
+    }
+}
+
+/// Every predicate in `node`, left to right.
+fn collect_predicates(node: &AstNode, out: &mut Vec<String>) {
+    match node {
+        AstNode::Predicate(..) => out.push(node_label(node)),
+        AstNode::LogicalOp(_, left, right) => {
+            collect_predicates(left, out);
+            collect_predicates(right, out);
+        }
+        AstNode::Not(inner) => collect_predicates(inner, out),
+    }
+}
+
+/// The predicates the metadata pre-filter checks: the metadata ones, except under
+/// a `!` whose operand also needs the content, which the pre-filter lets through.
+fn collect_prefilter(node: &AstNode, out: &mut Vec<String>) {
+    match node {
+        AstNode::Predicate(key, _) => {
+            if EvaluationTier::of(key) == EvaluationTier::Metadata {
+                out.push(node_label(node));
+            }
+        }
+        AstNode::LogicalOp(_, left, right) => {
+            collect_prefilter(left, out);
+            collect_prefilter(right, out);
+        }
+        AstNode::Not(inner) => {
+            if metadata_only(inner) {
+                collect_prefilter(inner, out);
+            }
+        }
+    }
+}
+
+fn metadata_only(node: &AstNode) -> bool {
+    match node {
+        AstNode::Predicate(key, _) => EvaluationTier::of(key) == EvaluationTier::Metadata,
+        AstNode::LogicalOp(_, left, right) => metadata_only(left) && metadata_only(right),
+        AstNode::Not(inner) => metadata_only(inner),
+    }
+}
+
+/// Prints each node evaluated against `path`, indented by depth, with its result.
+fn write_trace(
+    out: &mut impl Write,
+    path: &Path,
+    trace: &[TraceStep],
+    result: &MatchResult,
+) -> Result<()> {
+    writeln!(out, "Evaluating {}:", path.display())?;
+    for step in trace {
+        let skipped = if step.short_circuited {
+            "; right side skipped"
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "  {}{} → {}{skipped}",
+            "  ".repeat(step.depth),
+            step.label,
+            describe_result(&step.result)
+        )?;
+    }
+    writeln!(out, "Result: {}", describe_result(result))?;
+    Ok(())
+}
+
+/// E.g. `2 hunks at lines 3, 10-12`.
+fn describe_result(result: &MatchResult) -> String {
+    match result {
+        MatchResult::Boolean(true) => "matches the whole file".to_string(),
+        MatchResult::Boolean(false) => "no match".to_string(),
+        MatchResult::Hunks(hunks) if hunks.is_empty() => "no match".to_string(),
+        MatchResult::Hunks(hunks) => {
+            let lines: Vec<String> = hunks
+                .iter()
+                .map(|hunk| {
+                    let (start, end) = (hunk.start_point.row + 1, hunk.end_point.row + 1);
+                    if start == end {
+                        start.to_string()
+                    } else {
+                        format!("{start}-{end}")
+                    }
+                })
+                .collect();
+            match hunks.len() {
+                1 => format!("1 hunk at line {}", lines[0]),
+                n => format!("{n} hunks at lines {}", lines.join(", ")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(query: &str) -> String {
+        let ast = parser::parse_query(query).unwrap();
+        let mut out = Vec::new();
+        write_plan(&mut out, query, &[], &Config::default(), &ast).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_write_plan() {
+        let plan = plan("ext:rs & (func:main | !contains:TODO)");
+        assert!(plan.contains(
+            "\
+&  and: skips the right side unless the left side matches
+├── ext:rs  [metadata]
+└── |  or: skips the right side if the left side matches the whole file
+    ├── func:main  [semantic]
+    └── !  not
+        └── contains:TODO  [content]
+"
+        ));
+        assert!(plan.contains("1. Metadata pre-filter: ext:rs\n"), "{plan}");
+        assert!(
+            plan.contains("left to right: ext:rs, func:main, contains:TODO\n"),
+            "{plan}"
+        );
+        assert!(plan.contains("parses it with tree-sitter"), "{plan}");
+    }
+
+    #[test]
+    fn test_prefilter_skips_negated_content() {
+        let negated = plan("!(ext:md & contains:draft)");
+        assert!(negated.contains("Metadata pre-filter: none"), "{negated}");
+        let metadata = plan("!ext:md & size:>1kb");
+        assert!(
+            metadata.contains("Metadata pre-filter: ext:md, size:>1kb"),
+            "{metadata}"
+        );
+        assert!(metadata.contains("no file is read"), "{metadata}");
+    }
+}
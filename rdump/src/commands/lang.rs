@@ -26,7 +26,7 @@ pub fn run_lang(action: LangAction) -> Result<()> {
                 profile.extensions.join(", ")
             );
 
-            let metadata_preds = ["ext", "name", "path", "size", "modified"];
+            let metadata_preds = ["ext", "name", "path", "size", "modified", "since", "before"];
             let content_preds = ["contains", "matches"];
 
             println!("\nMETADATA");
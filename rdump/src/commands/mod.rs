@@ -1,5 +1,10 @@
 // This makes the functions inside search.rs and preset.rs available
 // to other parts of the program that use the `commands` module.
+pub mod completions;
+pub mod config;
+pub mod explain;
 pub mod lang;
 pub mod preset;
 pub mod search;
+pub mod stats;
+pub mod types;
@@ -1,7 +1,11 @@
-use crate::config::{self, Config};
-use crate::PresetAction;
-use anyhow::{anyhow, Result};
+use crate::config::{self, Config, PresetEntry};
+use crate::parser;
+use crate::{PresetAction, PresetFileFormat};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs; // We'll need to make PresetAction public
+use std::path::Path;
 
 /// The main entry point for the `preset` command.
 pub fn run_preset(action: PresetAction) -> Result<()> {
@@ -30,7 +34,7 @@ pub fn run_preset(action: PresetAction) -> Result<()> {
             };
 
             println!("Adding/updating preset '{name}'...");
-            config.presets.insert(name, query);
+            config.presets.insert(name, query.into());
             config::save_config(&config)?;
         }
         PresetAction::Remove { name } => {
@@ -52,6 +56,142 @@ pub fn run_preset(action: PresetAction) -> Result<()> {
                 return Err(anyhow!("Preset '{}' not found in global config.", name));
             }
         }
+        PresetAction::Export { names, format } => {
+            let config = config::load_config()?;
+            print!("{}", export_presets(&config, &names, format)?);
+        }
+        PresetAction::Import { file, overwrite } => {
+            let imported = read_preset_file(&file)?;
+            let path = config::global_config_path()
+                .ok_or_else(|| anyhow!("Could not determine global config path"))?;
+            let mut config = if path.exists() {
+                config::parse_config(&fs::read_to_string(&path)?, &path)?
+            } else {
+                Config::default()
+            };
+
+            let count = imported.presets.len();
+            merge_presets(&mut config, imported, overwrite)?;
+            println!("Importing {count} preset(s) from {}...", file.display());
+            config::save_config(&config)?;
+        }
+    }
+    Ok(())
+}
+
+/// What `preset export` writes and `preset import` reads: the `[presets]` table
+/// of a config file, so an export can also be pasted into one.
+#[derive(Deserialize, Serialize, Debug, Default)]
+struct PresetFile {
+    presets: BTreeMap<String, PresetEntry>,
+}
+
+/// Serializes the presets named in `names`, or every preset, with any `extends`
+/// already resolved so the file stands on its own.
+fn export_presets(config: &Config, names: &[String], format: PresetFileFormat) -> Result<String> {
+    let mut file = PresetFile::default();
+    if names.is_empty() {
+        file.presets
+            .extend(config.presets.iter().map(|(name, preset)| (name.clone(), preset.clone())));
+    }
+    for name in names {
+        let preset = config
+            .presets
+            .get(name)
+            .ok_or_else(|| anyhow!("Preset '{}' not found", name))?;
+        file.presets.insert(name.clone(), preset.clone());
+    }
+    Ok(match format {
+        PresetFileFormat::Toml => toml::to_string_pretty(&file)?,
+        PresetFileFormat::Json => serde_json::to_string_pretty(&file)? + "\n",
+    })
+}
+
+/// Reads an exported preset file, checking that every query parses.
+fn read_preset_file(path: &Path) -> Result<PresetFile> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read preset file {}", path.display()))?;
+    let file: PresetFile = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse preset file {}", path.display()))?
+    } else {
+        toml::from_str(&text)
+            .with_context(|| format!("Failed to parse preset file {}", path.display()))?
+    };
+    for (name, preset) in &file.presets {
+        parser::parse_query(preset.query())
+            .with_context(|| format!("Preset '{name}' in {} is invalid", path.display()))?;
+    }
+    Ok(file)
+}
+
+/// Adds `imported` to `config`. A preset that already exists with a different
+/// query is a conflict, an error unless `overwrite`; nothing is merged then.
+fn merge_presets(config: &mut Config, imported: PresetFile, overwrite: bool) -> Result<()> {
+    let conflicts: Vec<&str> = imported
+        .presets
+        .iter()
+        .filter(|(name, preset)| config.presets.get(*name).is_some_and(|old| old != *preset))
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if !conflicts.is_empty() && !overwrite {
+        return Err(anyhow!(
+            "Preset(s) {} already exist with a different query. Pass --overwrite to replace them.",
+            conflicts.join(", ")
+        ));
     }
+    config.presets.extend(imported.presets);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with(presets: &[(&str, &str)]) -> Config {
+        let mut config = Config::default();
+        for (name, query) in presets {
+            config
+                .presets
+                .insert(name.to_string(), query.to_string().into());
+        }
+        config
+    }
+
+    #[test]
+    fn test_export_presets() {
+        let config = config_with(&[("rust", "ext:rs"), ("docs", "ext:md")]);
+        let toml = export_presets(&config, &[], PresetFileFormat::Toml).unwrap();
+        assert_eq!(toml, "[presets]\ndocs = \"ext:md\"\nrust = \"ext:rs\"\n");
+
+        let json = export_presets(&config, &["rust".into()], PresetFileFormat::Json).unwrap();
+        let parsed: PresetFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.presets.len(), 1);
+        assert_eq!(parsed.presets["rust"].query(), "ext:rs");
+
+        let err = export_presets(&config, &["nope".into()], PresetFileFormat::Toml).unwrap_err();
+        assert!(err.to_string().contains("'nope' not found"));
+    }
+
+    #[test]
+    fn test_merge_presets_conflicts() {
+        let imported = || PresetFile {
+            presets: config_with(&[("rust", "ext:rs & !path:target"), ("docs", "ext:md")])
+                .presets
+                .into_iter()
+                .collect(),
+        };
+
+        let mut config = config_with(&[("rust", "ext:rs")]);
+        let err = merge_presets(&mut config, imported(), false).unwrap_err();
+        assert!(err.to_string().contains("rust"), "{err}");
+        assert!(!config.presets.contains_key("docs"), "nothing merged on a conflict");
+
+        merge_presets(&mut config, imported(), true).unwrap();
+        assert_eq!(config.presets["rust"].query(), "ext:rs & !path:target");
+        assert_eq!(config.presets["docs"].query(), "ext:md");
+
+        // Importing the same presets again is no conflict.
+        merge_presets(&mut config, imported(), false).unwrap();
+    }
+}
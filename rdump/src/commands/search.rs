@@ -1,23 +1,121 @@
-use crate::{config, ColorChoice, SearchArgs};
+use crate::{config, ColorChoice, SearchArgs, SortKey};
 use anyhow::anyhow;
-use anyhow::Result;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
 use ignore::WalkBuilder;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use rayon::prelude::*;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, IsTerminal, Write};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, IsTerminal, Read, Write};
+use std::num::NonZeroUsize;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Instant;
 use tempfile::NamedTempFile;
-use tree_sitter::Range;
 
-use crate::evaluator::{Evaluator, FileContext, MatchResult};
+use crate::archive;
+use crate::diagnostics;
+use crate::evaluator::{Evaluator, FileContext, Hunk, MatchResult};
 use crate::formatter;
+use crate::links::Permalinks;
+use crate::metrics::{Stage, METRICS};
 use crate::parser::{self, AstNode, PredicateKey};
-use crate::predicates::{self, PredicateEvaluator};
+use crate::predicates::{self, PredicateRegistry};
+use crate::progress::Progress;
+use crate::roots;
+use crate::template::Template;
+use crate::tokens::estimate_tokens;
+use crate::types;
+
+/// The main entry point for the `search` command. Returns the status to exit with,
+/// which only depends on the matches with `--exit-code` or `--silent`.
+pub fn run_search(args: SearchArgs) -> Result<ExitCode> {
+    let started = Instant::now();
+    METRICS.reset();
+    let stats = args.stats;
+    // A dry run evaluates nothing, so it has no matches to report on.
+    let exit_code = (args.exit_code || args.silent)
+        && !(args.dry_run || args.dry_run_count || args.dry_run_report);
+    let searched = with_jobs(args.jobs, || search_and_print(args));
+    if stats {
+        eprint!("{}", METRICS.summary(started.elapsed()));
+    }
+    if !exit_code {
+        return searched.map(|_| ExitCode::SUCCESS);
+    }
+    Ok(match searched {
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            ExitCode::from(2)
+        }
+        Ok(0) => ExitCode::from(1),
+        Ok(_) => ExitCode::SUCCESS,
+    })
+}
+
+/// Runs `f` on a pool of `jobs` threads for `--jobs`, so that every parallel pass in
+/// it, from sorting and evaluating the files to formatting them, runs on that many
+/// threads. Without `jobs`, `f` runs on rayon's global pool.
+pub(crate) fn with_jobs<T: Send>(
+    jobs: Option<NonZeroUsize>,
+    f: impl FnOnce() -> Result<T> + Send,
+) -> Result<T> {
+    let Some(jobs) = jobs else {
+        return f();
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.get())
+        .build()
+        .with_context(|| format!("Failed to start {jobs} threads for --jobs"))?;
+    pool.install(f)
+}
+
+/// Runs the search and prints its output. Returns the number of matching files
+/// written, after `--offset`, `--limit` and `--max-tokens`.
+fn search_and_print(mut args: SearchArgs) -> Result<usize> {
+    let config = config::load_config()?;
+    config.defaults.merge_into(&mut args);
+    diagnostics::set_quiet(args.quiet || args.silent);
+    if args.workspace && args.root == [PathBuf::from(".")] {
+        let root = detect_workspace_root(&env::current_dir()?).unwrap_or_else(|| ".".into());
+        if args.verbose {
+            eprintln!("Using workspace root {}", root.display());
+        }
+        args.root = vec![root];
+    }
+    if args.absolute_paths {
+        // Files are found under their root, so absolute roots give absolute paths.
+        args.root = args
+            .root
+            .iter()
+            .map(|root| {
+                dunce::canonicalize(root)
+                    .with_context(|| format!("Failed to resolve root {}", root.display()))
+            })
+            .collect::<Result<_>>()?;
+    }
+    // --quiet implies --no-progress, unless --progress is given too.
+    args.no_progress |= args.quiet && !args.progress;
+
+    if args.dry_run || args.dry_run_count {
+        print_candidates(&args, io::stdout())?;
+        return Ok(0);
+    }
+    if args.dry_run_report {
+        print_dry_run_report(&args, &config, io::stdout())?;
+        return Ok(0);
+    }
+    if args.silent {
+        return find_any_match(args, &config);
+    }
 
-/// The main entry point for the `search` command.
-pub fn run_search(mut args: SearchArgs) -> Result<()> {
     // --- Handle Shorthand Flags ---
     if args.no_headers {
         args.format = crate::Format::Cat;
@@ -25,13 +123,42 @@ pub fn run_search(mut args: SearchArgs) -> Result<()> {
     if args.find {
         args.format = crate::Format::Find;
     }
-
-    // --- Perform the actual search ---
-    let matching_files = perform_search(&args)?;
+    if args.print0 {
+        args.format = crate::Format::Paths0;
+    }
+    if args.count {
+        args.format = crate::Format::Count;
+    }
+    if args.template.is_some() {
+        args.format = crate::Format::Template;
+    }
+    // Inverted matches are files with nothing in them to show, so only the formats
+    // that list files make sense for them.
+    if args.invert_match && !INVERT_MATCH_FORMATS.contains(&args.format) {
+        let names: Vec<_> = INVERT_MATCH_FORMATS.iter().map(format_name).collect();
+        return Err(anyhow!(
+            "--format={} shows matches within files, but --invert-match only finds whole \
+             files. Use one of: {}.",
+            format_name(&args.format),
+            names.join(", ")
+        ));
+    }
+    // Whole-file matches are shown in full, which these formats have nothing to show for.
+    let lists_hunks = matches!(
+        args.format,
+        crate::Format::Symbols | crate::Format::Quickfix
+    );
+    if args.whole_file && lists_hunks {
+        return Err(anyhow!(
+            "--format={} lists matching hunks, but --whole-file only finds whole files. \
+             Try --format=paths instead.",
+            format_name(&args.format)
+        ));
+    }
 
     // --- Determine if color should be used ---
-    let use_color = if args.output.is_some() {
-        // If outputting to a file, never use color unless explicitly forced.
+    let use_color = if args.output.is_some() || args.output_dir.is_some() || args.copy {
+        // If outputting to a file or the clipboard, never use color unless explicitly forced.
         args.color == ColorChoice::Always
     } else {
         // Otherwise, decide based on the color choice and TTY status.
@@ -51,50 +178,922 @@ pub fn run_search(mut args: SearchArgs) -> Result<()> {
         }
     }
 
+    // Boxes sized to the terminal make no sense anywhere else.
+    let to_terminal = io::stdout().is_terminal()
+        && args.output.is_none()
+        && args.output_dir.is_none()
+        && args.archive.is_none()
+        && !args.copy;
+    if args.format == crate::Format::Rich && !to_terminal {
+        args.format = crate::Format::Markdown;
+    }
+
+    let query = build_query(&args, &config)?;
+    let options = output_options(&args, use_color, query)?;
+    // Catch a misspelled theme before any output is written.
+    formatter::find_theme(&options.theme)?;
+
     // --- 5. Format and print results ---
-    let mut writer: Box<dyn Write> = if let Some(output_path) = &args.output {
-        Box::new(File::create(output_path)?)
+    if let Some(output_dir) = &args.output_dir {
+        let matching_files = within_token_budget(search_matches(&args, &config)?, &args, &options)?;
+        write_output_dir(output_dir, &matching_files, &args, options)?;
+        return Ok(matching_files.len());
+    }
+    if let Some(archive_path) = &args.archive {
+        archive::ArchiveKind::from_path(archive_path)?;
+        let matching_files = within_token_budget(search_matches(&args, &config)?, &args, &options)?;
+        archive::write_archive(archive_path, &matching_files, &args.root, &options.query)?;
+        return Ok(matching_files.len());
+    }
+
+    if args.interactive {
+        return browse_matches(&args, &config, options);
+    }
+
+    if args.editor.is_some() || args.dry_run_editor {
+        let editor = resolve_editor(&args)?;
+        let matching_files = within_token_budget(search_matches(&args, &config)?, &args, &options)?;
+        let mut printer = formatter::StreamingPrinter::new(io::stdout(), options);
+        for file in &matching_files {
+            printer.print(file.clone())?;
+        }
+        printer.finish()?;
+        open_in_editor(&editor, &matching_files, args.dry_run_editor)?;
+        return Ok(matching_files.len());
+    }
+
+    let mut written = 0;
+    if args.copy {
+        let mut buffer = Vec::new();
+        write_matches(&args, &config, &mut buffer, options, &mut written)?;
+        copy_to_clipboard(buffer)?;
+        return Ok(written);
+    }
+
+    if let Some(output_path) = &args.output {
+        let file = File::create(output_path)?;
+        write_matches(&args, &config, file, options, &mut written)?;
+        return Ok(written);
+    }
+    let pager = pager_command(&args, io::stdout().is_terminal(), env::var("PAGER").ok());
+    match pager {
+        Some(pager) => {
+            // The pager owns the screen, so keep the progress line off it.
+            args.no_progress |= !args.progress;
+            write_to_pager(&args, &config, &pager, options, &mut written)?;
+        }
+        None => write_matches(&args, &config, io::stdout(), options, &mut written)?,
+    }
+    Ok(written)
+}
+
+/// The formats `--invert-match` allows: those that list files rather than what
+/// matched inside them.
+const INVERT_MATCH_FORMATS: [crate::Format; 9] = [
+    crate::Format::Paths,
+    crate::Format::Paths0,
+    crate::Format::Find,
+    crate::Format::FindLong,
+    crate::Format::Json,
+    crate::Format::JsonExtended,
+    crate::Format::Ndjson,
+    crate::Format::Count,
+    crate::Format::Tree,
+];
+
+/// The name `format` is given on the command line, e.g. `json-extended`.
+fn format_name(format: &crate::Format) -> String {
+    let value = format.to_possible_value().expect("no format is hidden");
+    value.get_name().to_string()
+}
+
+/// Searches until the first matching file, for `--silent`. Dropping the receiver
+/// after it stops the remaining evaluation. An error in another file doesn't
+/// matter once a match is found.
+fn find_any_match(mut args: SearchArgs, config: &config::Config) -> Result<usize> {
+    args.unsorted = true;
+    args.offset = 0;
+    args.limit = Some(1);
+    args.sample = None;
+    args.sort = SortKey::Path;
+    args.reverse = false;
+    args.no_progress = true;
+    args.progress = false;
+    let mut found = false;
+    let searched = for_each_match(&args, config, |_| {
+        found = true;
+        Ok(())
+    });
+    if found {
+        return Ok(1);
+    }
+    searched.map(|()| 0)
+}
+
+/// Prints the files a search would evaluate, or just their number with
+/// `--dry-run-count`, without reading them.
+fn print_candidates(args: &SearchArgs, mut writer: impl Write) -> Result<()> {
+    let mut candidates = get_candidate_files_in_roots(args)?;
+    if let Some(max_size) = args.max_filesize {
+        candidates = skip_large_files(candidates, max_size);
+    }
+    if args.dry_run_count {
+        writeln!(writer, "{}", candidates.len())?;
+        return Ok(());
+    }
+    for path in candidates {
+        writeln!(writer, "{}", path.display())?;
+    }
+    Ok(())
+}
+
+/// Prints what a search would do for `--dry-run-report`: the files walked, those
+/// left after the metadata pre-filter, the bytes evaluating them would read, and
+/// the largest of them. Nothing is read but metadata.
+fn print_dry_run_report(
+    args: &SearchArgs,
+    config: &config::Config,
+    mut writer: impl Write,
+) -> Result<()> {
+    let mut candidates = get_candidate_files_in_roots(args)?;
+    let walked = candidates.len();
+    if let Some(max_size) = args.max_filesize {
+        candidates = skip_large_files(candidates, max_size);
+    }
+    // Without a query, every file walked would be searched.
+    let has_query = args.query.is_some()
+        || !args.preset.is_empty()
+        || !args.include.is_empty()
+        || !args.file_type.is_empty();
+    let mut reads_content = true;
+    if has_query {
+        let ast = parser::parse_query(&build_query(args, config)?)?;
+        validate_ast_predicates(&ast, &predicates::create_predicate_registry())?;
+        candidates = pre_filter(args, &ast, candidates, &Progress::new(false, 0))?;
+        let cost = Evaluator::new(ast, PredicateRegistry::new()).estimate_cost();
+        reads_content = cost.requires_content_read;
+    }
+
+    let mut largest: Vec<(u64, PathBuf)> = candidates
+        .into_iter()
+        .map(|path| (fs::metadata(&path).map_or(0, |m| m.len()), path))
+        .collect();
+    let total: u64 = largest.iter().map(|(size, _)| size).sum();
+    writeln!(writer, "Files walked:       {walked}")?;
+    writeln!(writer, "Files to evaluate:  {}", largest.len())?;
+    if reads_content {
+        writeln!(writer, "Bytes to read:      {}", formatter::format_size(total))?;
     } else {
-        Box::new(io::stdout())
+        writeln!(writer, "Bytes to read:      0 (the query needs only metadata)")?;
+    }
+    if !largest.is_empty() {
+        writeln!(writer, "\nLargest files:")?;
+        // Biggest first; ties stay in path order.
+        largest.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+        for (size, path) in largest.into_iter().take(10) {
+            writeln!(writer, "  {:>8}  {}", formatter::format_size(size), path.display())?;
+        }
+    }
+    Ok(())
+}
+
+/// The editor command for `--editor`: the one given, or `$VISUAL`, or `$EDITOR`.
+pub(crate) fn resolve_editor(args: &SearchArgs) -> Result<String> {
+    if let Some(Some(editor)) = &args.editor {
+        return Ok(editor.clone());
+    }
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .ok_or_else(|| {
+            anyhow!("No editor to open. Pass --editor=<CMD>, or set $VISUAL or $EDITOR.")
+        })
+}
+
+/// Opens the matching files in `editor`, or prints the commands with `dry_run`.
+pub(crate) fn open_in_editor(
+    editor: &str,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    dry_run: bool,
+) -> Result<()> {
+    for command in editor_commands(editor, matching_files)? {
+        if dry_run {
+            println!("{}", shell_words(&command));
+            continue;
+        }
+        let status = Command::new(&command[0])
+            .args(&command[1..])
+            .status()
+            .with_context(|| format!("Failed to start editor '{editor}'"))?;
+        if !status.success() {
+            return Err(anyhow!("Editor '{editor}' exited with {status}"));
+        }
+    }
+    Ok(())
+}
+
+/// The commands that open each file at the first line of its first hunk, in the
+/// syntax `editor` understands. Terminal editors get one file at a time; VS Code
+/// takes every file in one command.
+fn editor_commands(
+    editor: &str,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+) -> Result<Vec<Vec<String>>> {
+    if matching_files.is_empty() {
+        return Ok(vec![]);
+    }
+    let base: Vec<String> = editor.split_whitespace().map(String::from).collect();
+    let program = base
+        .first()
+        .ok_or_else(|| anyhow!("The editor command is empty"))?;
+    let name = Path::new(program)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let targets = matching_files.iter().map(|(path, hunks)| {
+        let line = hunks
+            .iter()
+            .map(|h| h.start_point.row + 1)
+            .min()
+            .unwrap_or(1);
+        (path.display().to_string(), line)
+    });
+
+    let commands = match name.as_str() {
+        "code" | "code-insiders" | "codium" => {
+            let mut command = base.clone();
+            command.push("--goto".to_string());
+            command.extend(targets.map(|(path, line)| format!("{path}:{line}")));
+            vec![command]
+        }
+        "vi" | "vim" | "nvim" | "gvim" | "emacs" | "emacsclient" | "nano" => targets
+            .map(|(path, line)| {
+                let mut command = base.clone();
+                command.extend([format!("+{line}"), path]);
+                command
+            })
+            .collect(),
+        // Editors without a known line syntax just get the file.
+        _ => targets
+            .map(|(path, _)| {
+                let mut command = base.clone();
+                command.push(path);
+                command
+            })
+            .collect(),
     };
+    Ok(commands)
+}
+
+/// Joins `command` for display, quoting any argument a shell would split.
+fn shell_words(command: &[String]) -> String {
+    command
+        .iter()
+        .map(|arg| {
+            let special = |c: char| c.is_whitespace() || "'\"$\\".contains(c);
+            if arg.is_empty() || arg.contains(special) {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The pager to page the output through, if any. Paging only happens on a terminal,
+/// when asked for with `--pager` or `pager` in the config, and not with `--no-pager`.
+fn pager_command(
+    args: &SearchArgs,
+    stdout_is_terminal: bool,
+    env_pager: Option<String>,
+) -> Option<String> {
+    if args.no_pager || !stdout_is_terminal {
+        return None;
+    }
+    match &args.pager {
+        None => None,
+        Some(Some(command)) => Some(command.clone()),
+        Some(None) => Some(
+            env_pager
+                .filter(|pager| !pager.trim().is_empty())
+                .unwrap_or_else(|| "less -R".to_string()),
+        ),
+    }
+}
 
-    formatter::print_output(
-        &mut writer,
-        &matching_files,
-        &args.format,
-        args.line_numbers,
-        args.no_headers,
+/// Searches and prints every match into the stdin of `pager`, e.g. `less -R`, then
+/// waits for the user to close it. Closing the pager early is not an error.
+fn write_to_pager(
+    args: &SearchArgs,
+    config: &config::Config,
+    pager: &str,
+    options: formatter::OutputOptions,
+    written: &mut usize,
+) -> Result<()> {
+    let mut words = pager.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| anyhow!("The pager command is empty"))?;
+    let mut command = Command::new(program);
+    command.args(words).stdin(Stdio::piped());
+    // Like git: quit if the output fits on one screen, and keep colors.
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to start pager '{pager}'"))?;
+    let stdin = child.stdin.take().expect("pager stdin is piped");
+
+    let result = write_matches(args, config, stdin, options, written);
+    child.wait()?;
+    match result {
+        Err(e) if is_broken_pipe(&e) => Ok(()),
+        result => result,
+    }
+}
+
+/// Whether `error` comes from writing to a pipe whose reader has gone away.
+fn is_broken_pipe(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+    })
+}
+
+/// Searches and prints every match to `writer`, counting the files printed in
+/// `written`. The count is kept even if writing fails part way.
+fn write_matches(
+    args: &SearchArgs,
+    config: &config::Config,
+    writer: impl Write,
+    options: formatter::OutputOptions,
+    written: &mut usize,
+) -> Result<()> {
+    let mut budget = args.max_tokens.map(TokenBudget::new);
+    // Print each file as soon as it's found rather than after the whole search.
+    let mut printer = formatter::StreamingPrinter::new(writer, options.clone());
+    let mut print = |file: (PathBuf, Vec<Hunk>), metadata: Option<fs::Metadata>| {
+        if let Some(budget) = &mut budget {
+            if !budget.admit(&file, &options)? {
+                return Ok(());
+            }
+        }
+        let started = Instant::now();
+        let printed = printer.print_with_metadata(file, metadata);
+        METRICS.add_time(Stage::Format, started.elapsed());
+        *written += 1;
+        printed
+    };
+    if needs_all_matches(args) {
+        for (file, metadata) in collect_matches(args, config)? {
+            print(file, metadata)?;
+        }
+    } else {
+        for_each_match(args, config, |file| print(file, None))?;
+    }
+    if let Some(budget) = budget {
+        budget.report();
+    }
+    printer.finish()
+}
+
+/// Tracks `--max-tokens`: files are admitted in the order they're offered until the
+/// budget is spent, skipping any file that doesn't fit.
+struct TokenBudget {
+    max_tokens: usize,
+    used: usize,
+    skipped: usize,
+}
+
+impl TokenBudget {
+    fn new(max_tokens: usize) -> Self {
+        TokenBudget {
+            max_tokens,
+            used: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Returns whether `file`'s output fits in what's left of the budget, and spends
+    /// it if so.
+    fn admit(
+        &mut self,
+        file: &(PathBuf, Vec<Hunk>),
+        options: &formatter::OutputOptions,
+    ) -> Result<bool> {
+        let tokens = formatter::estimate_output_tokens(file, options)?;
+        if self.used + tokens > self.max_tokens {
+            self.skipped += 1;
+            return Ok(false);
+        }
+        self.used += tokens;
+        Ok(true)
+    }
+
+    /// Notes on stderr how many files were left out, if any.
+    fn report(&self) {
+        if self.skipped > 0 && !diagnostics::is_quiet() {
+            eprintln!(
+                "Skipped {} file(s) to stay within --max-tokens {} (~{} tokens used).",
+                self.skipped, self.max_tokens, self.used
+            );
+        }
+    }
+}
+
+/// Applies `--max-tokens`, if given, to an already collected list of matches.
+fn within_token_budget(
+    files: Vec<(PathBuf, Vec<Hunk>)>,
+    args: &SearchArgs,
+    options: &formatter::OutputOptions,
+) -> Result<Vec<(PathBuf, Vec<Hunk>)>> {
+    let Some(max_tokens) = args.max_tokens else {
+        return Ok(files);
+    };
+    let mut budget = TokenBudget::new(max_tokens);
+    let mut kept = Vec::new();
+    for file in files {
+        if budget.admit(&file, options)? {
+            kept.push(file);
+        }
+    }
+    budget.report();
+    Ok(kept)
+}
+
+/// Puts the formatted output on the system clipboard and reports how much was copied.
+fn copy_to_clipboard(output: Vec<u8>) -> Result<()> {
+    let text = String::from_utf8_lossy(&output);
+    let summary = copy_summary(output.len(), estimate_tokens(&text));
+    set_clipboard_text(&text)?;
+    if !diagnostics::is_quiet() {
+        eprintln!("{summary}");
+    }
+    Ok(())
+}
+
+fn open_clipboard() -> Result<arboard::Clipboard> {
+    arboard::Clipboard::new().map_err(|e| {
+        anyhow!("Could not access the system clipboard: {e}. Is a display available?")
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_clipboard_text(text: &str) -> Result<()> {
+    open_clipboard()?
+        .set_text(text)
+        .map_err(|e| anyhow!("Could not copy to the system clipboard: {e}"))
+}
+
+/// Set for the copy of rdump that `set_clipboard_text` leaves holding the clipboard.
+#[cfg(target_os = "linux")]
+pub(crate) const CLIPBOARD_HOLDER_VAR: &str = "RDUMP_CLIPBOARD_HOLDER";
+
+/// On X11 and Wayland, copied text is served by the process that copied it and is
+/// gone once it exits. So a copy of rdump is started in the background to hold the
+/// text, until something else is copied.
+#[cfg(target_os = "linux")]
+pub(crate) fn set_clipboard_text(text: &str) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    // Fail here, where the error can be seen, when there is no display.
+    drop(open_clipboard()?);
+    let mut holder = Command::new(env::current_exe()?)
+        .env(CLIPBOARD_HOLDER_VAR, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // Out of the terminal's process group, so Ctrl-C doesn't reach it.
+        .process_group(0)
+        .spawn()
+        .context("Failed to start the process that holds the clipboard")?;
+    let mut stdin = holder.stdin.take().expect("holder stdin is piped");
+    stdin.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+/// Runs in the process started by `set_clipboard_text`: puts its stdin on the
+/// clipboard and serves it until something else is copied.
+#[cfg(target_os = "linux")]
+pub(crate) fn hold_clipboard() -> Result<()> {
+    use arboard::SetExtLinux;
+
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text)?;
+    open_clipboard()?
+        .set()
+        .wait()
+        .text(text)
+        .map_err(|e| anyhow!("Could not copy to the system clipboard: {e}"))
+}
+
+/// Opens the `--interactive` browser on the matches, and returns how many there
+/// were. `e` in the browser uses the same editor as `--editor`.
+fn browse_matches(
+    args: &SearchArgs,
+    config: &config::Config,
+    options: formatter::OutputOptions,
+) -> Result<usize> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(anyhow!("--interactive needs a terminal"));
+    }
+    let matching_files = within_token_budget(search_matches(args, config)?, args, &options)?;
+    if matching_files.is_empty() {
+        diagnostics::warn("no files matched, so there is nothing to browse");
+        return Ok(0);
+    }
+    let count = matching_files.len();
+    crate::tui::browse(matching_files, options, resolve_editor(args).ok())?;
+    Ok(count)
+}
+
+/// A one-line summary of a clipboard copy.
+fn copy_summary(bytes: usize, tokens: usize) -> String {
+    format!("Copied {bytes} bytes (~{tokens} tokens) to the clipboard.")
+}
+
+/// Collects the output options from `args`. `query` is the full query being run.
+/// Fails on a malformed `--template`, before any searching is done.
+fn output_options(
+    args: &SearchArgs,
+    use_color: bool,
+    query: String,
+) -> Result<formatter::OutputOptions> {
+    let template = args.template.as_deref().map(Template::parse).transpose()?;
+    if args.format == crate::Format::Template && template.is_none() {
+        return Err(anyhow!("--format=template needs a --template string"));
+    }
+    let permalinks = if args.format == crate::Format::Links {
+        // Links point into a single repository, the one the first root is in.
+        let root = args.root.first().map_or(Path::new("."), PathBuf::as_path);
+        Some(Permalinks::detect(root, args.link_base.as_deref())?)
+    } else {
+        None
+    };
+    Ok(formatter::OutputOptions {
+        format: args.format.clone(),
+        with_line_numbers: args.line_numbers,
+        no_headers: args.no_headers || args.quiet,
         use_color,
-        args.context.unwrap_or(0),
-    )?;
+        before_context: args.before_context.or(args.context).unwrap_or(0),
+        after_context: args.after_context.or(args.context).unwrap_or(0),
+        merge_gap: args.merge_adjacent_hunks,
+        theme: args
+            .theme
+            .clone()
+            .unwrap_or_else(|| formatter::DEFAULT_THEME.to_string()),
+        query,
+        root: args.root.clone(),
+        template,
+        json_full_content: args.json_full_content,
+        toc: args.toc,
+        permalinks,
+        max_hunks_per_file: args.max_hunks_per_file.map(|max| max.get()),
+        rich_width: if args.format == crate::Format::Rich {
+            rich_width(args)
+        } else {
+            formatter::DEFAULT_RICH_WIDTH
+        },
+    })
+}
 
+/// The terminal's width, capped by `--rich-max-width`.
+fn rich_width(args: &SearchArgs) -> usize {
+    let columns = terminal_columns().unwrap_or(formatter::DEFAULT_RICH_WIDTH);
+    args.rich_max_width.map_or(columns, |max| columns.min(max))
+}
+
+fn terminal_columns() -> Option<usize> {
+    let (columns, _rows) = ratatui::crossterm::terminal::size().ok()?;
+    Some(columns.into())
+}
+
+/// Writes each matching file's formatted output to its own file under `output_dir`,
+/// mirroring the file's path relative to the search root (e.g. `src/main.rs.md`).
+fn write_output_dir(
+    output_dir: &Path,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    args: &SearchArgs,
+    options: formatter::OutputOptions,
+) -> Result<()> {
+    let extension = match args.format {
+        crate::Format::Markdown => "md",
+        crate::Format::Json | crate::Format::JsonExtended => "json",
+        crate::Format::Sarif => "sarif",
+        crate::Format::Csv => "csv",
+        crate::Format::Xml => "xml",
+        crate::Format::Yaml => "yaml",
+        crate::Format::Html => "html",
+        _ => "txt",
+    };
+
+    let mut used_paths = HashSet::new();
+    for file in matching_files {
+        let relative = roots::unique_relative_path(&args.root, &file.0);
+        // Keep absolute or `..` paths inside the output directory.
+        let relative: PathBuf = relative
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect();
+        let base = output_dir.join(relative);
+
+        // Two source files can map to the same output path; add a counter to keep both.
+        let mut target = PathBuf::from(format!("{}.{extension}", base.display()));
+        let mut counter = 1;
+        while !used_paths.insert(target.clone()) {
+            target = PathBuf::from(format!("{}-{counter}.{extension}", base.display()));
+            counter += 1;
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        let writer = File::create(&target)
+            .with_context(|| format!("Failed to create output file {}", target.display()))?;
+        let mut printer = formatter::StreamingPrinter::new(writer, options.clone());
+        printer.print(file.clone())?;
+        printer.finish()?;
+    }
     Ok(())
 }
 
 /// Performs the search logic and returns the matching files and their hunks.
 /// This function is separated from `run_search` to be testable.
-pub fn perform_search(args: &SearchArgs) -> Result<Vec<(PathBuf, Vec<Range>)>> {
-    // --- Load Config and Build Query ---
-    let config = config::load_config()?;
-    let mut final_query: Option<String> = args.query.clone();
-
-    // If presets are specified, prepend them to the query.
-    if !args.preset.is_empty() {
-        let mut preset_queries = Vec::new();
-        for preset_name in &args.preset {
-            let preset_query = config
-                .presets
-                .get(preset_name)
-                .ok_or_else(|| anyhow!("Preset '{}' not found", preset_name))?;
-            preset_queries.push(format!("({})", preset_query));
-        }
-        let all_presets = preset_queries.join(" & ");
-
-        if let Some(q) = final_query {
-            final_query = Some(format!("({}) & ({})", all_presets, q));
+pub fn perform_search(args: &SearchArgs) -> Result<Vec<(PathBuf, Vec<Hunk>)>> {
+    search_matches(args, &config::load_config()?)
+}
+
+/// Like `perform_search`, with the config already loaded.
+fn search_matches(args: &SearchArgs, config: &config::Config) -> Result<Vec<(PathBuf, Vec<Hunk>)>> {
+    Ok(collect_matches(args, config)?
+        .into_iter()
+        .map(|(file, _)| file)
+        .collect())
+}
+
+/// A matching file, with its metadata if it was read to sort the files.
+type SortedMatch = ((PathBuf, Vec<Hunk>), Option<fs::Metadata>);
+
+/// Searches, then samples, sorts and pages through the matches as `args` asks.
+fn collect_matches(args: &SearchArgs, config: &config::Config) -> Result<Vec<SortedMatch>> {
+    let mut matching_files = Vec::new();
+    for_each_match(args, config, |file| {
+        matching_files.push(file);
+        Ok(())
+    })?;
+    if !needs_all_matches(args) {
+        // `for_each_match` has already applied --offset and --limit.
+        return Ok(matching_files.into_iter().map(|file| (file, None)).collect());
+    }
+
+    if let Some(sample_size) = args.sample {
+        if args.unsorted {
+            // Files arrive in a different order on each run; --seed needs the same one.
+            matching_files.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        matching_files = sample_files(matching_files, sample_size, args.seed);
+    }
+    let sorted = sort_files(matching_files, args.sort, args.reverse)?;
+    Ok(sorted
+        .into_iter()
+        .skip(args.offset)
+        .take(args.limit.unwrap_or(usize::MAX))
+        .collect())
+}
+
+/// Whether every match has to be known before the first can be output: a sample can
+/// only be drawn, and files sorted by anything but path, once the search is done.
+fn needs_all_matches(args: &SearchArgs) -> bool {
+    args.sample.is_some() || args.sort != SortKey::Path || args.reverse
+}
+
+/// Sorts files by `key`, keeping path order between files that compare equal. Sorting
+/// by size or modification time reads each file's metadata once and returns it, so
+/// the find formats don't read it again.
+fn sort_files(
+    mut files: Vec<(PathBuf, Vec<Hunk>)>,
+    key: SortKey,
+    reverse: bool,
+) -> Result<Vec<SortedMatch>> {
+    if key == SortKey::Path && !reverse {
+        return Ok(files.into_iter().map(|file| (file, None)).collect());
+    }
+    // Sort by path first, even with --unsorted, so ties come out in a stable order.
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut sorted: Vec<SortedMatch> = match key {
+        SortKey::Size | SortKey::Modified => files
+            .into_par_iter()
+            .map(|file| {
+                // Not followed, as `ls -l` does, since the find formats reuse it.
+                let metadata = fs::symlink_metadata(&file.0)
+                    .with_context(|| format!("Failed to read metadata for {}", file.0.display()))?;
+                Ok((file, Some(metadata)))
+            })
+            .collect::<Result<_>>()?,
+        _ => files.into_iter().map(|file| (file, None)).collect(),
+    };
+    match key {
+        SortKey::Path => {}
+        SortKey::Size => sorted.sort_by_key(|(_, metadata)| metadata.as_ref().map(|m| m.len())),
+        SortKey::Modified => {
+            sorted.sort_by_key(|(_, metadata)| metadata.as_ref().and_then(|m| m.modified().ok()))
+        }
+        SortKey::Matches => sorted.sort_by_key(|((_, hunks), _)| hunks.len()),
+        SortKey::Ext => {
+            sorted.sort_by(|((a, _), _), ((b, _), _)| a.extension().cmp(&b.extension()))
+        }
+    }
+    if reverse {
+        sorted.reverse();
+    }
+    Ok(sorted)
+}
+
+/// Picks `sample_size` files at random, without replacement, keeping their original order.
+/// Asking for at least as many files as there are returns all of them.
+fn sample_files(
+    files: Vec<(PathBuf, Vec<Hunk>)>,
+    sample_size: usize,
+    seed: Option<u64>,
+) -> Vec<(PathBuf, Vec<Hunk>)> {
+    if sample_size >= files.len() {
+        return files;
+    }
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let indices: Vec<usize> = (0..files.len()).collect();
+    let chosen: HashSet<usize> = indices
+        .choose_multiple(&mut rng, sample_size)
+        .copied()
+        .collect();
+    files
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| chosen.contains(i))
+        .map(|(_, file)| file)
+        .collect()
+}
+
+/// Runs the search on a background thread and calls `on_match` with each matching file
+/// as soon as it can be released. Files are released in path order: a file that finishes
+/// early waits only for the files sorted before it. With `--unsorted`, files are released
+/// the moment they are found. Unless every match is needed first, --offset and --limit
+/// apply to the released files, and the search stops once the limit is reached.
+fn for_each_match(
+    args: &SearchArgs,
+    config: &config::Config,
+    mut on_match: impl FnMut((PathBuf, Vec<Hunk>)) -> Result<()>,
+) -> Result<()> {
+    // Shown by default only for searches big enough to take a while; --progress
+    // shows it for any search.
+    let progress = if args.progress {
+        Progress::new(true, 0)
+    } else {
+        Progress::new(!args.no_progress && io::stderr().is_terminal(), PROGRESS_MIN_FILES)
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        scope.spawn(|| progress.report());
+        let search = scope.spawn(|| stream_search(args, config, &progress, sender));
+
+        let (offset, limit) = if needs_all_matches(args) {
+            (0, None)
+        } else {
+            (args.offset, args.limit)
+        };
+        // Returning early drops the receiver, which stops the search.
+        let released = release_matches(receiver, args.unsorted, offset, limit, &mut |file| {
+            progress.suspend(|| on_match(file))
+        });
+
+        let searched = search.join();
+        progress.finish();
+        searched.map_err(|_| anyhow!("Search thread panicked"))??;
+        released
+    })
+}
+
+/// The fewest files a search needs for the progress line to show without --progress.
+const PROGRESS_MIN_FILES: usize = 500;
+
+/// Passes the matches arriving on `receiver` to `on_match`, in path order unless `unsorted`.
+/// The first `offset` matches are skipped, and it returns once `limit` have been passed.
+fn release_matches(
+    receiver: mpsc::Receiver<IndexedResult>,
+    unsorted: bool,
+    offset: usize,
+    limit: Option<usize>,
+    on_match: &mut impl FnMut((PathBuf, Vec<Hunk>)) -> Result<()>,
+) -> Result<()> {
+    if limit == Some(0) {
+        return Ok(());
+    }
+    let mut skipped = 0;
+    let mut remaining = limit.unwrap_or(usize::MAX);
+    // Releases a match, and returns whether more are wanted.
+    let mut release = |file| -> Result<bool> {
+        if skipped < offset {
+            skipped += 1;
         } else {
-            final_query = Some(all_presets);
+            on_match(file)?;
+            remaining -= 1;
+        }
+        Ok(remaining > 0)
+    };
+
+    // Results that finished ahead of an earlier file, keyed by position.
+    let mut pending = BTreeMap::new();
+    let mut next_index = 0;
+    for (index, result) in receiver {
+        if unsorted {
+            if let Some(file) = result {
+                if !release(file)? {
+                    return Ok(());
+                }
+            }
+            continue;
         }
+        pending.insert(index, result);
+        while let Some(result) = pending.remove(&next_index) {
+            next_index += 1;
+            if let Some(file) = result {
+                if !release(file)? {
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A file's position in path order, and its match (if any).
+type IndexedResult = (usize, Option<(PathBuf, Vec<Hunk>)>);
+
+/// Prepends the named presets to `query`, or makes them the query if there is none.
+pub(crate) fn splice_presets(
+    query: Option<String>,
+    presets: &[String],
+    config: &config::Config,
+) -> Result<Option<String>> {
+    if presets.is_empty() {
+        return Ok(query);
+    }
+    let mut preset_queries = Vec::new();
+    for preset_name in presets {
+        let preset_query = config
+            .presets
+            .get(preset_name)
+            .ok_or_else(|| anyhow!("Preset '{}' not found", preset_name))?;
+        preset_queries.push(format!("({})", preset_query.query()));
+    }
+    let all_presets = preset_queries.join(" & ");
+    Ok(Some(match query {
+        Some(q) => format!("({}) & ({})", all_presets, q),
+        None => all_presets,
+    }))
+}
+
+/// Combines the query with any `--preset` queries from the config, `--include` globs
+/// and `--exclude` queries or globs.
+fn build_query(args: &SearchArgs, config: &config::Config) -> Result<String> {
+    let mut final_query = splice_presets(args.query.clone(), &args.preset, config)?;
+
+    // Only the query itself is inverted; --include, --type and --exclude still say
+    // which files to look at.
+    if args.invert_match {
+        let q = final_query
+            .ok_or_else(|| anyhow!("--invert-match needs a query or preset to invert."))?;
+        final_query = Some(format!("!({q})"));
+    }
+
+    // Each --include glob narrows the query further; on their own, they are the query.
+    if !args.include.is_empty() {
+        let includes = args
+            .include
+            .iter()
+            .map(|glob| name_predicate(glob))
+            .collect::<Vec<_>>()
+            .join(" & ");
+        final_query = Some(match final_query {
+            Some(q) => format!("({q}) & {includes}"),
+            None => includes,
+        });
+    }
+
+    // --type works the same way, but a file of any of the types will do.
+    if !args.file_type.is_empty() {
+        let types = types::type_query(&args.file_type, config)?;
+        final_query = Some(match final_query {
+            Some(q) => format!("({q}) & {types}"),
+            None => types,
+        });
     }
 
     // Ensure we have a query to run.
@@ -105,14 +1104,54 @@ pub fn perform_search(args: &SearchArgs) -> Result<Vec<(PathBuf, Vec<Range>)>> {
     if query_to_parse.trim().is_empty() {
         return Err(anyhow!("Empty query."));
     }
+    if args.exclude.is_empty() {
+        return Ok(query_to_parse);
+    }
+
+    // Parse each part on its own first, so an error says which one is wrong.
+    parser::parse_query(&query_to_parse)
+        .with_context(|| format!("Failed to parse the query '{query_to_parse}'"))?;
+    let mut combined = format!("({query_to_parse})");
+    for exclude in &args.exclude {
+        // Every predicate has a `key:`, so anything without one is a file name glob.
+        if !exclude.contains(':') {
+            combined.push_str(&format!(" & !{}", name_predicate(exclude)));
+            continue;
+        }
+        parser::parse_query(exclude)
+            .with_context(|| format!("Failed to parse the --exclude query '{exclude}'"))?;
+        combined.push_str(&format!(" & !({exclude})"));
+    }
+    Ok(combined)
+}
+
+/// A `name:` predicate matching `glob`, quoted so any character in it is literal RQL.
+fn name_predicate(glob: &str) -> String {
+    let escaped = glob.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("name:\"{escaped}\"")
+}
+
+/// Performs the search, sending every pre-filtered file's result to `sender` as soon
+/// as it has been evaluated, tagged with the file's position in path order. Files that
+/// don't match are sent as `None` so the receiver can tell when earlier files are done.
+fn stream_search(
+    args: &SearchArgs,
+    config: &config::Config,
+    progress: &Progress,
+    sender: mpsc::Sender<IndexedResult>,
+) -> Result<()> {
+    // --- Build Query ---
+    let query_to_parse = build_query(args, config)?;
 
     // --- 1. Find initial candidates ---
-    let candidate_files = get_candidate_files(
-        &args.root,
-        args.no_ignore,
-        args.hidden,
-        args.max_depth,
-    )?;
+    let walk_started = Instant::now();
+    let mut candidate_files = get_candidate_files_in_roots(args)?;
+    METRICS.add_time(Stage::Walk, walk_started.elapsed());
+    METRICS.files_walked(candidate_files.len());
+    if let Some(max_size) = args.max_filesize {
+        candidate_files = skip_large_files(candidate_files, max_size);
+    }
+    progress.set_total(candidate_files.len());
 
     // --- 2. Parse query ---
     let ast = parser::parse_query(&query_to_parse)?;
@@ -123,67 +1162,155 @@ pub fn perform_search(args: &SearchArgs) -> Result<Vec<(PathBuf, Vec<Range>)>> {
     validate_ast_predicates(&ast, &predicates::create_predicate_registry())?;
 
     // --- 3. Pre-filtering Pass (Metadata) ---
-    // This pass uses an evaluator with only fast metadata predicates.
-    // It quickly reduces the number of files needing full evaluation.
-    let metadata_registry = predicates::create_metadata_predicate_registry();
-    let pre_filter_evaluator = Evaluator::new(ast.clone(), metadata_registry);
+    let evaluate_started = Instant::now();
+    let pre_filtered_files = pre_filter(args, &ast, candidate_files, progress)?;
+
+    // --- 4. Main Evaluation Pass (Content + Semantic) ---
+    // This pass uses the full evaluator on the smaller, pre-filtered set of files.
+    let mut full_registry = predicates::create_predicate_registry();
+    if !config.tags.is_empty() {
+        full_registry.insert(
+            PredicateKey::Tag,
+            predicates::tag::TagEvaluator::with_extra_tags(&config.tags),
+        );
+    }
+
+    // `duplicate:` needs to see every candidate at once, so hash the pre-filtered
+    // set up front and hand the result to its evaluator.
+    if ast_uses_predicate(&ast, &PredicateKey::Duplicate) {
+        let duplicates = predicates::duplicate::find_duplicates(&pre_filtered_files);
+        full_registry.insert(
+            PredicateKey::Duplicate,
+            predicates::duplicate::DuplicateEvaluator::new(duplicates),
+        );
+    }
+    let evaluator = Evaluator::new(ast, full_registry);
 
     let first_error = Mutex::new(None);
-    let pre_filtered_files: Vec<PathBuf> = candidate_files
-        .into_iter() // This pass is not parallel, it's fast enough.
-        .filter(|path| {
-            if first_error.lock().unwrap().is_some() {
-                return false;
+    let lossy_files = AtomicUsize::new(0);
+    let binary_files = AtomicUsize::new(0);
+    // Unsorted matches are released as they're found, so once enough have been found
+    // the rest can be skipped without waiting for the receiver to stop listening.
+    let wanted = match args.limit {
+        Some(limit) if args.unsorted && !needs_all_matches(args) => Some(args.offset + limit),
+        _ => None,
+    };
+    let matched = AtomicUsize::new(0);
+    let _ = pre_filtered_files
+        .par_iter()
+        .enumerate()
+        .map(|(index, path)| {
+            if first_error.lock().unwrap().is_some()
+                || wanted.is_some_and(|wanted| matched.load(Ordering::Relaxed) >= wanted)
+            {
+                return (index, None);
             }
-            let mut context = FileContext::new(path.clone(), args.root.clone());
-            match pre_filter_evaluator.evaluate(&mut context) {
-                Ok(result) => result.is_match(),
+            let root = roots::root_of(&args.root, path).to_path_buf();
+            let mut context = FileContext::new(path.clone(), root)
+                .with_strict_encoding(args.strict_encoding)
+                .with_hunk_granularity(args.hunk_granularity);
+            METRICS.file_evaluated();
+            progress.file_started(path);
+            let result = match evaluator.evaluate(&mut context) {
+                Ok(MatchResult::Boolean(true)) => Some((path.clone(), Vec::new())),
+                Ok(MatchResult::Boolean(false)) => None,
+                Ok(MatchResult::Hunks(hunks)) => {
+                    if hunks.is_empty() {
+                        None
+                    } else if args.whole_file {
+                        // No hunks makes it a whole-file match.
+                        Some((path.clone(), Vec::new()))
+                    } else {
+                        Some((path.clone(), hunks))
+                    }
+                }
                 Err(e) => {
                     let mut error_guard = first_error.lock().unwrap();
                     if error_guard.is_none() {
                         *error_guard =
-                            Some(anyhow!("Error during pre-filter on {}: {}", path.display(), e));
+                            Some(anyhow!("Error evaluating file {}: {}", path.display(), e));
                     }
-                    false
+                    None
                 }
+            };
+            if context.encoding_lossy() {
+                lossy_files.fetch_add(1, Ordering::Relaxed);
+            }
+            // Binary content can't be searched or shown, even if the query matched,
+            // e.g. with a `!contains:`.
+            let result = if context.is_binary() {
+                binary_files.fetch_add(1, Ordering::Relaxed);
+                None
+            } else {
+                result
+            };
+            progress.file_scanned(result.is_some());
+            if let Some((_, hunks)) = &result {
+                matched.fetch_add(1, Ordering::Relaxed);
+                METRICS.file_matched(hunks.len());
             }
+            (index, result)
         })
-        .collect();
+        // Sending only fails once the receiver has stopped listening, so stop searching.
+        .try_for_each_with(sender, |sender, result| sender.send(result));
+    METRICS.add_time(Stage::Evaluate, evaluate_started.elapsed());
 
     if let Some(e) = first_error.into_inner().unwrap() {
         return Err(e);
     }
+    let binary_files = binary_files.into_inner();
+    if binary_files > 0 {
+        diagnostics::warn(format!(
+            "skipped {binary_files} binary file(s) whose content the query needed"
+        ));
+    }
+    let lossy_files = lossy_files.into_inner();
+    if lossy_files > 0 {
+        diagnostics::warn(format!(
+            "{lossy_files} file(s) weren't valid UTF-8 and were searched with the invalid \
+             bytes replaced. Pass --strict-encoding to treat them as errors."
+        ));
+    }
+
+    Ok(())
+}
 
-    // --- 4. Main Evaluation Pass (Content + Semantic) ---
-    // This pass uses the full evaluator on the smaller, pre-filtered set of files.
-    let full_registry = predicates::create_predicate_registry();
-    let evaluator = Evaluator::new(ast, full_registry);
+
+/// Leaves out the candidates the query's metadata predicates (`ext:`, `in:`,
+/// `size:`...) rule out, without reading any file.
+fn pre_filter(
+    args: &SearchArgs,
+    ast: &AstNode,
+    candidate_files: Vec<PathBuf>,
+    progress: &Progress,
+) -> Result<Vec<PathBuf>> {
+    // This pass uses an evaluator with only fast metadata predicates.
+    // It quickly reduces the number of files needing full evaluation.
+    let metadata_registry = predicates::create_metadata_predicate_registry();
+    let pre_filter_evaluator = Evaluator::new(ast.clone(), metadata_registry);
 
     let first_error = Mutex::new(None);
-    let mut matching_files: Vec<(PathBuf, Vec<Range>)> = pre_filtered_files
-        .par_iter()
-        .filter_map(|path| {
+    let mut pre_filtered_files: Vec<PathBuf> = candidate_files
+        .into_iter() // This pass is not parallel, it's fast enough.
+        .filter(|path| {
             if first_error.lock().unwrap().is_some() {
-                return None;
+                return false;
             }
-            let mut context = FileContext::new(path.clone(), args.root.clone());
-            match evaluator.evaluate(&mut context) {
-                Ok(MatchResult::Boolean(true)) => Some((path.clone(), Vec::new())),
-                Ok(MatchResult::Boolean(false)) => None,
-                Ok(MatchResult::Hunks(hunks)) => {
-                    if hunks.is_empty() {
-                        None
-                    } else {
-                        Some((path.clone(), hunks))
-                    }
+            let root = roots::root_of(&args.root, path).to_path_buf();
+            let mut context = FileContext::new(path.clone(), root);
+            match pre_filter_evaluator.evaluate(&mut context) {
+                Ok(result) if result.is_match() => true,
+                Ok(_) => {
+                    progress.file_scanned(false);
+                    false
                 }
                 Err(e) => {
                     let mut error_guard = first_error.lock().unwrap();
                     if error_guard.is_none() {
                         *error_guard =
-                            Some(anyhow!("Error evaluating file {}: {}", path.display(), e));
+                            Some(anyhow!("Error during pre-filter on {}: {}", path.display(), e));
                     }
-                    None
+                    false
                 }
             }
         })
@@ -192,26 +1319,171 @@ pub fn perform_search(args: &SearchArgs) -> Result<Vec<(PathBuf, Vec<Range>)>> {
     if let Some(e) = first_error.into_inner().unwrap() {
         return Err(e);
     }
+    pre_filtered_files.sort();
+    Ok(pre_filtered_files)
+}
 
-    matching_files.sort_by(|a, b| a.0.cmp(&b.0));
+/// Files and directories that mark the top of a project.
+const WORKSPACE_MARKERS: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "go.mod",
+    ".git",
+];
 
-    Ok(matching_files)
+/// Finds the nearest directory at or above `cwd` containing a workspace marker, and
+/// returns it relative to `cwd`, e.g. `../..`, so paths in the output stay relative.
+fn detect_workspace_root(cwd: &Path) -> Option<PathBuf> {
+    let levels_up = cwd
+        .ancestors()
+        .position(|dir| WORKSPACE_MARKERS.iter().any(|marker| dir.join(marker).exists()))?;
+    if levels_up == 0 {
+        return Some(PathBuf::from("."));
+    }
+    Some((0..levels_up).map(|_| "..").collect())
+}
+
+/// Leaves out files over `max_size` bytes, so nothing reads them, and says how many.
+fn skip_large_files(files: Vec<PathBuf>, max_size: u64) -> Vec<PathBuf> {
+    let (kept, skipped): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|path| fs::metadata(path).map_or(true, |m| m.len() <= max_size));
+    if !skipped.is_empty() {
+        diagnostics::warn(format!(
+            "skipped {} file(s) larger than --max-filesize",
+            skipped.len()
+        ));
+    }
+    kept
+}
+
+/// Walks every `--root` and returns the files found, in path order. A root inside
+/// another is skipped, so a file under two overlapping roots is only listed once.
+/// `--files-from` replaces the walk with a list of files.
+fn get_candidate_files_in_roots(args: &SearchArgs) -> Result<Vec<PathBuf>> {
+    let mut files = if let Some(list) = &args.files_from {
+        read_file_list(list, b'\n', &args.root)?
+    } else if let Some(list) = &args.files_from0 {
+        read_file_list(list, b'\0', &args.root)?
+    } else {
+        let mut files = Vec::new();
+        let options = WalkOptions::from_args(args);
+        for root in roots::distinct_roots(&args.root) {
+            files.extend(get_candidate_files(root, &options)?);
+        }
+        files
+    };
+    files.sort();
+    files.dedup();
+    if args.follow {
+        // Links can make one file reachable by several paths; keep the first.
+        let mut seen = HashSet::new();
+        files.retain(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())));
+    }
+    Ok(files)
+}
+
+/// Reads the files listed in `list`, or on stdin if it's `-`, one per `separator`.
+/// Relative paths are resolved against the first root. Paths that aren't files are
+/// skipped with a warning.
+fn read_file_list(list: &Path, separator: u8, roots: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let bytes = if list == Path::new("-") {
+        let mut bytes = Vec::new();
+        io::stdin()
+            .read_to_end(&mut bytes)
+            .context("Failed to read the file list from stdin")?;
+        bytes
+    } else {
+        fs::read(list)
+            .with_context(|| format!("Failed to read the file list {}", list.display()))?
+    };
+    let root = roots.first().map_or(Path::new("."), PathBuf::as_path);
+    let mut files = Vec::new();
+    for entry in bytes.split(|&b| b == separator) {
+        let entry = entry.strip_suffix(b"\r").unwrap_or(entry);
+        if entry.is_empty() {
+            continue;
+        }
+        let path = root.join(path_from_bytes(entry));
+        if path.is_file() {
+            files.push(path);
+        } else {
+            diagnostics::warn(format!("skipping {}: not a file", path.display()));
+        }
+    }
+    Ok(files)
+}
+
+/// The path spelled by `bytes`. Any bytes make a path on Unix; elsewhere paths are
+/// Unicode, so bytes that aren't UTF-8 are replaced.
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+    }
+    #[cfg(not(unix))]
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
 }
 
+/// Build output, dependencies and VCS metadata, skipped unless --no-ignore or
+/// --no-default-ignore is given. Gitignore syntax, so no leading whitespace.
+const DEFAULT_IGNORES: &[&str] = &[
+    "node_modules/",
+    "target/",
+    "dist/",
+    "build/",
+    ".git/",
+    ".svn/",
+    ".hg/",
+    "*.pyc",
+    "__pycache__/",
+];
+
+/// How `get_candidate_files` walks a directory: which ignore files apply, and which
+/// files and directories it enters.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct WalkOptions {
+    pub no_ignore: bool,
+    pub no_default_ignore: bool,
+    pub no_rdumpignore: bool,
+    /// Extra gitignore-style files from `--ignore-file`.
+    pub ignore_files: Vec<PathBuf>,
+    pub hidden: bool,
+    pub max_depth: Option<usize>,
+    /// Files shallower than this are left out; a root's own files are at depth 1.
+    pub min_depth: Option<usize>,
+    pub follow: bool,
+}
+
+impl WalkOptions {
+    fn from_args(args: &SearchArgs) -> Self {
+        WalkOptions {
+            no_ignore: args.no_ignore,
+            no_default_ignore: args.no_default_ignore,
+            no_rdumpignore: args.no_rdumpignore,
+            ignore_files: args.ignore_file.clone(),
+            hidden: args.hidden,
+            max_depth: args.max_depth,
+            min_depth: args.min_depth,
+            follow: args.follow,
+        }
+    }
+}
 
 /// Walks the directory, respecting .gitignore, and applies our own smart defaults.
-fn get_candidate_files(
-    root: &PathBuf,
-    no_ignore: bool,
-    hidden: bool,
-    max_depth: Option<usize>,
-) -> Result<Vec<PathBuf>> {
+pub(crate) fn get_candidate_files(root: &PathBuf, options: &WalkOptions) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
     let mut walker_builder = WalkBuilder::new(root);
 
-    walker_builder.hidden(!hidden).max_depth(max_depth);
+    // Following links detects loops, which are reported as errors below.
+    walker_builder
+        .hidden(!options.hidden)
+        .max_depth(options.max_depth)
+        .follow_links(options.follow);
 
-    if no_ignore {
+    if options.no_ignore {
         // If --no-ignore is passed, disable everything.
         walker_builder
             .ignore(false)
@@ -219,45 +1491,58 @@ fn get_candidate_files(
             .git_global(false)
             .git_exclude(false);
     } else {
-        // Layer 1: Our "sane defaults". These have the lowest precedence.
-        let default_ignores = "
-           # Default rdump ignores
-           node_modules/
-           target/
-           dist/
-           build/
-           .git/
-           .svn/
-           .hg/
-           *.pyc
-           __pycache__/
-       ";
-        let mut temp_ignore = NamedTempFile::new()?;
-        write!(temp_ignore, "{default_ignores}")?;
-        walker_builder.add_ignore(temp_ignore.path());
+        // Layer 1: Our "sane defaults". These have the lowest precedence, and
+        // --no-default-ignore leaves them out.
+        if !options.no_default_ignore {
+            let mut temp_ignore = NamedTempFile::new()?;
+            for pattern in DEFAULT_IGNORES {
+                writeln!(temp_ignore, "{pattern}")?;
+            }
+            walker_builder.add_ignore(temp_ignore.path());
+        }
 
         // Layer 2: A user's custom global ignore file.
         if let Some(global_ignore_path) = dirs::config_dir().map(|p| p.join("rdump/ignore")) {
             if global_ignore_path.exists() {
                 if let Some(err) = walker_builder.add_ignore(global_ignore_path) {
-                    eprintln!("Warning: could not add global ignore file: {err}");
+                    diagnostics::warn(format!("could not add global ignore file: {err}"));
                 }
             }
         }
 
         // Layer 3: A user's custom project-local .rdumpignore file.
-        walker_builder.add_custom_ignore_filename(".rdumpignore");
+        if !options.no_rdumpignore {
+            walker_builder.add_custom_ignore_filename(".rdumpignore");
+        }
 
         // Layer 4: Standard .gitignore files are enabled by default.
         // walker_builder.git_global(true);
         // walker_builder.git_ignore(true);
     }
 
+    // Ignore files named with --ignore-file, e.g. a .dockerignore. They're asked for
+    // by name, so --no-ignore doesn't turn them off.
+    for ignore_file in &options.ignore_files {
+        if !ignore_file.is_file() {
+            return Err(anyhow!(
+                "ignore file '{}' does not exist.",
+                ignore_file.display()
+            ));
+        }
+        if let Some(err) = walker_builder.add_ignore(ignore_file) {
+            return Err(anyhow!(
+                "could not read ignore file '{}': {err}",
+                ignore_file.display()
+            ));
+        }
+    }
+
     for result in walker_builder.build() {
         // Handle potential errors from the directory walk itself
         match result {
             Ok(entry) => {
-                if entry.file_type().is_some_and(|ft| ft.is_file()) {
+                let deep_enough = options.min_depth.is_none_or(|min| entry.depth() >= min);
+                if deep_enough && entry.file_type().is_some_and(|ft| ft.is_file()) {
                     files.push(entry.into_path());
                 }
             }
@@ -271,7 +1556,7 @@ fn get_candidate_files(
                     }
                 }
                 // For other errors (e.g. permission denied on a sub-dir), just print a warning.
-                eprintln!("Warning: could not access entry: {}", e);
+                diagnostics::warn(format!("could not access entry: {}", e));
             }
         }
     }
@@ -279,9 +1564,9 @@ fn get_candidate_files(
 }
 
 /// Recursively traverses the AST to ensure all used predicates are valid.
-fn validate_ast_predicates(
+pub(crate) fn validate_ast_predicates(
     node: &AstNode,
-    registry: &HashMap<PredicateKey, Box<dyn PredicateEvaluator + Send + Sync>>,
+    registry: &PredicateRegistry,
 ) -> Result<()> {
     match node {
         AstNode::Predicate(key, _) => {
@@ -305,6 +1590,17 @@ fn validate_ast_predicates(
     Ok(())
 }
 
+/// Returns true if the given predicate key appears anywhere in the AST.
+fn ast_uses_predicate(node: &AstNode, key: &PredicateKey) -> bool {
+    match node {
+        AstNode::Predicate(k, _) => k == key,
+        AstNode::LogicalOp(_, left, right) => {
+            ast_uses_predicate(left, key) || ast_uses_predicate(right, key)
+        }
+        AstNode::Not(child) => ast_uses_predicate(child, key),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,7 +1615,13 @@ mod tests {
         hidden: bool,
         max_depth: Option<usize>,
     ) -> Vec<String> {
-        let mut paths = get_candidate_files(root, no_ignore, hidden, max_depth).unwrap();
+        let options = WalkOptions {
+            no_ignore,
+            hidden,
+            max_depth,
+            ..Default::default()
+        };
+        let mut paths = get_candidate_files(root, &options).unwrap();
         paths.sort();
         paths
             .into_iter()
@@ -378,19 +1680,76 @@ mod tests {
         let output_file = dir.path().join("dump.txt");
         let args = SearchArgs {
             query: Some("ext:rs".to_string()),
-            root: root.clone(),
+            root: vec![root.clone()],
+            workspace: false,
+            absolute_paths: false,
+            files_from: None,
+            files_from0: None,
             output: Some(output_file.clone()),
             color: ColorChoice::Auto, // This is the default
             // Other fields can be default
             preset: vec![],
+            include: vec![],
+            exclude: vec![],
+            invert_match: false,
+            whole_file: false,
+            file_type: vec![],
             line_numbers: false,
             no_headers: false,
+            quiet: false,
+            verbose: false,
             format: crate::Format::Hunks,
             no_ignore: false,
+            no_default_ignore: false,
+            no_rdumpignore: false,
+            ignore_file: vec![],
             hidden: false,
+            follow: false,
+            dry_run: false,
+            dry_run_count: false,
+            dry_run_report: false,
             max_depth: None,
+            max_hunks_per_file: None,
+            explicit_flags: Default::default(),
+            hunk_granularity: None,
+            rich_max_width: None,
+            min_depth: None,
             context: Some(0),
+            before_context: None,
+            after_context: None,
+            output_dir: None,
+            archive: None,
+            copy: false,
+            interactive: false,
+            template: None,
+            json_full_content: false,
+            toc: false,
+            link_base: None,
+            unsorted: false,
+            sort: SortKey::Path,
+            reverse: false,
+            limit: None,
+            offset: 0,
+            sample: None,
+            seed: None,
+            max_tokens: None,
+            max_filesize: None,
+            strict_encoding: false,
+            jobs: None,
+            theme: None,
+            merge_adjacent_hunks: 0,
             find: false,
+            print0: false,
+            count: false,
+            stats: false,
+            exit_code: false,
+            silent: false,
+            progress: false,
+            no_progress: false,
+            pager: None,
+            no_pager: false,
+            editor: None,
+            dry_run_editor: false,
         };
 
         // Run the search part of the command
@@ -414,7 +1773,7 @@ mod tests {
         let output_file = dir.path().join("dump.txt");
         let args = SearchArgs {
             query: Some("ext:rs".to_string()),
-            root: root.clone(),
+            root: vec![root.clone()],
             output: Some(output_file.clone()),
             color: ColorChoice::Never,
             ..Default::default()
@@ -436,7 +1795,7 @@ mod tests {
         let output_file = dir.path().join("dump.txt");
         let args = SearchArgs {
             query: Some("ext:rs".to_string()),
-            root: root.clone(),
+            root: vec![root.clone()],
             output: Some(output_file.clone()),
             color: ColorChoice::Always,
             format: crate::Format::Cat, // Use a format that supports color
@@ -451,4 +1810,179 @@ mod tests {
             "Output file should contain ANSI color codes when color=always"
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_write_output_dir_adds_counter_on_conflict() {
+        let root = tempdir().unwrap();
+        let file_path = root.path().join("main.rs");
+        fs::write(&file_path, "fn main() {}").unwrap();
+        let out = tempdir().unwrap();
+
+        let args = SearchArgs {
+            root: vec![root.path().to_path_buf()],
+            format: crate::Format::Cat,
+            ..Default::default()
+        };
+        let matching_files = vec![(file_path.clone(), vec![]), (file_path, vec![])];
+        write_output_dir(
+            out.path(),
+            &matching_files,
+            &args,
+            output_options(&args, false, String::new()).unwrap(),
+        )
+        .unwrap();
+
+        assert!(out.path().join("main.rs.txt").exists());
+        assert!(out.path().join("main.rs-1.txt").exists());
+    }
+
+    #[test]
+    fn test_copy_summary() {
+        assert_eq!(copy_summary(10, 3), "Copied 10 bytes (~3 tokens) to the clipboard.");
+    }
+
+    #[test]
+    fn test_editor_commands() {
+        let hunk = |row| {
+            Hunk::from(tree_sitter::Range {
+                start_byte: 0,
+                end_byte: 0,
+                start_point: tree_sitter::Point { row, column: 0 },
+                end_point: tree_sitter::Point { row, column: 0 },
+            })
+        };
+        let files = vec![
+            (PathBuf::from("./src/main.rs"), vec![hunk(9), hunk(4)]),
+            (PathBuf::from("./my notes.md"), vec![]),
+        ];
+
+        let commands = editor_commands("nvim", &files).unwrap();
+        let shown: Vec<String> = commands.iter().map(|c| shell_words(c)).collect();
+        assert_eq!(shown, ["nvim +5 ./src/main.rs", "nvim +1 './my notes.md'"]);
+
+        let commands = editor_commands("code --reuse-window", &files).unwrap();
+        assert_eq!(
+            commands,
+            [["code", "--reuse-window", "--goto", "./src/main.rs:5", "./my notes.md:1"]]
+        );
+
+        let commands = editor_commands("/usr/bin/emacs -nw", &files[..1]).unwrap();
+        assert_eq!(commands, [["/usr/bin/emacs", "-nw", "+5", "./src/main.rs"]]);
+
+        let commands = editor_commands("subl", &files[..1]).unwrap();
+        assert_eq!(commands, [["subl", "./src/main.rs"]]);
+
+        assert!(editor_commands("code", &[]).unwrap().is_empty());
+        assert!(editor_commands(" ", &files).is_err());
+    }
+
+    #[test]
+    fn test_pager_command() {
+        let pager_env = || Some("more".to_string());
+        let mut args = SearchArgs::default();
+        assert_eq!(pager_command(&args, true, pager_env()), None);
+
+        args.pager = Some(None);
+        assert_eq!(pager_command(&args, true, pager_env()).as_deref(), Some("more"));
+        assert_eq!(pager_command(&args, true, None).as_deref(), Some("less -R"));
+        // Only a terminal is paged.
+        assert_eq!(pager_command(&args, false, pager_env()), None);
+
+        args.pager = Some(Some("bat --paging=always".to_string()));
+        assert_eq!(
+            pager_command(&args, true, pager_env()).as_deref(),
+            Some("bat --paging=always")
+        );
+
+        args.no_pager = true;
+        assert_eq!(pager_command(&args, true, pager_env()), None);
+    }
+
+    #[test]
+    fn test_detect_workspace_root() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("project");
+        let nested = project.join("src/commands");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(project.join("Cargo.toml"), "[package]\n").unwrap();
+
+        assert_eq!(detect_workspace_root(&nested), Some(PathBuf::from("../..")));
+        assert_eq!(detect_workspace_root(&project), Some(PathBuf::from(".")));
+    }
+
+    #[test]
+    fn test_name_predicate_round_trips() {
+        for glob in ["*.rs", "my file.rs", r#"we"ird\*.rs"#, "a|b&c"] {
+            assert_eq!(
+                parser::parse_query(&name_predicate(glob)).unwrap(),
+                AstNode::Predicate(PredicateKey::Name, glob.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_files() {
+        let files: Vec<(PathBuf, Vec<Hunk>)> = (0..20)
+            .map(|i| (PathBuf::from(format!("file_{i:02}.rs")), vec![]))
+            .collect();
+
+        let sample = sample_files(files.clone(), 5, Some(42));
+        assert_eq!(sample.len(), 5);
+        // The sample keeps the files' original order.
+        assert!(sample.windows(2).all(|w| w[0].0 < w[1].0));
+        // The same seed gives the same sample.
+        assert_eq!(sample, sample_files(files.clone(), 5, Some(42)));
+
+        assert_eq!(sample_files(files.clone(), 20, None).len(), 20);
+        assert_eq!(sample_files(files, 100, None).len(), 20);
+    }
+
+    #[test]
+    fn test_release_matches_with_offset_and_limit() {
+        let (sender, receiver) = mpsc::channel();
+        // Out of order, with a non-matching file at position 1.
+        for index in [2, 0, 1, 4, 3, 5] {
+            let file = (index != 1).then(|| (PathBuf::from(format!("{index}.rs")), vec![]));
+            sender.send((index, file)).unwrap();
+        }
+        drop(sender);
+
+        let mut released = Vec::new();
+        release_matches(receiver, false, 1, Some(2), &mut |(path, _)| {
+            released.push(path.display().to_string());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(released, ["2.rs", "3.rs"]);
+    }
+
+    #[test]
+    fn test_sort_files_by_matches_and_ext() {
+        let hunk = Hunk::from(tree_sitter::Range {
+            start_byte: 0,
+            end_byte: 0,
+            start_point: tree_sitter::Point { row: 0, column: 0 },
+            end_point: tree_sitter::Point { row: 0, column: 0 },
+        });
+        let files = vec![
+            (PathBuf::from("a.rs"), vec![hunk; 2]),
+            (PathBuf::from("b.py"), vec![hunk; 3]),
+            (PathBuf::from("c.rs"), vec![]),
+        ];
+        let names = |key, reverse| -> Vec<String> {
+            sort_files(files.clone(), key, reverse)
+                .unwrap()
+                .into_iter()
+                .map(|((path, _), metadata)| {
+                    assert!(metadata.is_none());
+                    path.display().to_string()
+                })
+                .collect()
+        };
+        assert_eq!(names(SortKey::Path, false), ["a.rs", "b.py", "c.rs"]);
+        assert_eq!(names(SortKey::Matches, false), ["c.rs", "a.rs", "b.py"]);
+        assert_eq!(names(SortKey::Matches, true), ["b.py", "a.rs", "c.rs"]);
+        // Files with the same extension stay in path order.
+        assert_eq!(names(SortKey::Ext, false), ["b.py", "a.rs", "c.rs"]);
+    }
+}
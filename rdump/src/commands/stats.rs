@@ -0,0 +1,123 @@
+use crate::commands::search::{get_candidate_files, with_jobs, WalkOptions};
+use crate::evaluator::FileContext;
+use crate::parser::PredicateKey;
+use crate::predicates::code_aware::find_captures;
+use crate::predicates::code_aware::profiles::list_language_profiles;
+use crate::{StatsArgs, StatsFormat};
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// What `rdump stats` counts for each language.
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+struct LanguageStats {
+    language: String,
+    files: usize,
+    definitions: usize,
+    functions: usize,
+    imports: usize,
+}
+
+pub fn run_stats(args: StatsArgs) -> Result<()> {
+    let options = WalkOptions {
+        no_ignore: args.no_ignore,
+        hidden: args.hidden,
+        ..Default::default()
+    };
+    let files = get_candidate_files(&args.root, &options)?;
+    let stats = with_jobs(args.jobs, || collect_stats(&files))?;
+    match args.format {
+        StatsFormat::Table => {
+            println!(
+                "{:<12} {:>7} {:>12} {:>10} {:>8}",
+                "LANGUAGE", "FILES", "DEFINITIONS", "FUNCTIONS", "IMPORTS"
+            );
+            println!("{}", "─".repeat(53));
+            for row in &stats {
+                println!(
+                    "{:<12} {:>7} {:>12} {:>10} {:>8}",
+                    row.language, row.files, row.definitions, row.functions, row.imports
+                );
+            }
+        }
+        StatsFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+    }
+    Ok(())
+}
+
+/// Counts every `def:`, `func:` and `import:` match in `files`, per language, in
+/// language name order. Files in languages rdump can't parse are left out.
+fn collect_stats(files: &[impl AsRef<Path> + Sync]) -> Result<Vec<LanguageStats>> {
+    let profiles = list_language_profiles();
+    let per_file = files
+        .par_iter()
+        .filter_map(|path| {
+            let path = path.as_ref();
+            let extension = path.extension()?.to_str()?;
+            let profile = profiles
+                .iter()
+                .find(|p| p.extensions.contains(&extension))?;
+            Some(count_file(path, profile.name))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut by_language: BTreeMap<String, LanguageStats> = BTreeMap::new();
+    for file in per_file {
+        let row = by_language
+            .entry(file.language.clone())
+            .or_insert_with(|| LanguageStats {
+                language: file.language.clone(),
+                ..Default::default()
+            });
+        row.files += 1;
+        row.definitions += file.definitions;
+        row.functions += file.functions;
+        row.imports += file.imports;
+    }
+    Ok(by_language.into_values().collect())
+}
+
+fn count_file(path: &Path, language: &str) -> Result<LanguageStats> {
+    let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let mut context = FileContext::new(path.to_path_buf(), root);
+    let mut count = |key: PredicateKey| -> Result<usize> {
+        Ok(find_captures(&mut context, &key)?.map_or(0, |captures| captures.len()))
+    };
+    Ok(LanguageStats {
+        language: language.to_string(),
+        files: 1,
+        definitions: count(PredicateKey::Def)?,
+        functions: count(PredicateKey::Func)?,
+        imports: count(PredicateKey::Import)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_collect_stats() {
+        let dir = tempdir().unwrap();
+        let rust = dir.path().join("main.rs");
+        fs::write(
+            &rust,
+            "use std::fs;\nstruct A;\nfn main() {}\nfn helper() {}\n",
+        )
+        .unwrap();
+        let notes = dir.path().join("notes.txt");
+        fs::write(&notes, "fn not_code() {}\n").unwrap();
+
+        let stats = collect_stats(&[rust, notes]).unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].language, "Rust");
+        assert_eq!(stats[0].files, 1);
+        assert_eq!(stats[0].functions, 2);
+        assert_eq!(stats[0].imports, 1);
+        assert!(stats[0].definitions >= 1);
+    }
+}
@@ -0,0 +1,18 @@
+use crate::config;
+use crate::types::all_types;
+use crate::TypeAction;
+use anyhow::Result;
+
+pub fn run_type(action: TypeAction) -> Result<()> {
+    match action {
+        TypeAction::List => {
+            let config = config::load_config()?;
+            println!("{:<12} EXTENSIONS", "NAME");
+            println!("──────────────────────────");
+            for (name, extensions) in all_types(&config) {
+                println!("{:<12} {}", name, extensions.join(", "));
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,16 +1,166 @@
 // rdump/src/config.rs - FINAL CORRECTED VERSION
 
-use anyhow::{Context, Result};
+use crate::{diagnostics, ColorChoice, Format, SearchArgs};
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::env;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// The config format this rdump reads and writes. `rdump config migrate` upgrades
+/// older files.
+pub const CONFIG_VERSION: u32 = 1;
+
 #[derive(Deserialize, Serialize, Debug, Default)]
 pub struct Config {
+    /// The format the file was written in, e.g. `version = 1`. Files written before
+    /// versioning have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
     #[serde(default)]
-    pub presets: HashMap<String, String>,
+    pub presets: HashMap<String, PresetEntry>,
+    /// Extra comment markers (besides TODO, FIXME, etc.) recognized by `tag:.`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Custom `--type` file types, e.g. `docs = ["md", "rst"]`, which replace a
+    /// built-in type of the same name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub types: BTreeMap<String, Vec<String>>,
+    /// Default `search` flags for a project, used when a flag isn't given.
+    #[serde(default, skip_serializing_if = "Defaults::is_empty")]
+    pub defaults: Defaults,
+}
+
+/// The `[defaults]` section: values for `search` flags that apply whenever the
+/// flag is left at its built-in default on the command line.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Defaults {
+    pub format: Option<Format>,
+    pub line_numbers: Option<bool>,
+    pub context: Option<usize>,
+    pub before_context: Option<usize>,
+    pub after_context: Option<usize>,
+    pub merge_adjacent_hunks: Option<usize>,
+    pub color: Option<ColorChoice>,
+    pub theme: Option<String>,
+    pub max_depth: Option<usize>,
+    pub hidden: Option<bool>,
+    pub no_ignore: Option<bool>,
+    pub unsorted: Option<bool>,
+    pub pager: Option<String>,
+    pub workspace: Option<bool>,
+}
+
+impl Defaults {
+    fn is_empty(&self) -> bool {
+        *self == Defaults::default()
+    }
+
+    /// Returns these defaults with any field set in `other` taking its value.
+    fn overlay(self, other: Defaults) -> Defaults {
+        Defaults {
+            format: other.format.or(self.format),
+            line_numbers: other.line_numbers.or(self.line_numbers),
+            context: other.context.or(self.context),
+            before_context: other.before_context.or(self.before_context),
+            after_context: other.after_context.or(self.after_context),
+            merge_adjacent_hunks: other.merge_adjacent_hunks.or(self.merge_adjacent_hunks),
+            color: other.color.or(self.color),
+            theme: other.theme.or(self.theme),
+            max_depth: other.max_depth.or(self.max_depth),
+            hidden: other.hidden.or(self.hidden),
+            no_ignore: other.no_ignore.or(self.no_ignore),
+            unsorted: other.unsorted.or(self.unsorted),
+            pager: other.pager.or(self.pager),
+            workspace: other.workspace.or(self.workspace),
+        }
+    }
+
+    /// Fills in every flag of `args` that wasn't given on the command line, going by
+    /// `args.explicit_flags`, so even `--format=hunks` or `--hidden=false` wins.
+    pub fn merge_into(&self, args: &mut SearchArgs) {
+        let given = args.explicit_flags.clone();
+        fill(&mut args.format, "format", &self.format, &given);
+        fill(&mut args.color, "color", &self.color, &given);
+        fill(
+            &mut args.merge_adjacent_hunks,
+            "merge_adjacent_hunks",
+            &self.merge_adjacent_hunks,
+            &given,
+        );
+        fill(&mut args.line_numbers, "line_numbers", &self.line_numbers, &given);
+        fill(&mut args.hidden, "hidden", &self.hidden, &given);
+        fill(&mut args.no_ignore, "no_ignore", &self.no_ignore, &given);
+        fill(&mut args.unsorted, "unsorted", &self.unsorted, &given);
+        fill(&mut args.workspace, "workspace", &self.workspace, &given);
+        // Flags without a default value are `None` when they aren't given.
+        args.context = args.context.or(self.context);
+        args.before_context = args.before_context.or(self.before_context);
+        args.after_context = args.after_context.or(self.after_context);
+        args.max_depth = args.max_depth.or(self.max_depth);
+        if args.theme.is_none() {
+            args.theme.clone_from(&self.theme);
+        }
+        if args.pager.is_none() {
+            args.pager = self.pager.clone().map(Some);
+        }
+    }
+}
+
+/// Sets `flag` to `value`, if there is one and the flag with id `id` isn't in `given`.
+fn fill<T: Clone>(flag: &mut T, id: &str, value: &Option<T>, given: &BTreeSet<String>) {
+    if let Some(value) = value {
+        if !given.contains(id) {
+            *flag = value.clone();
+        }
+    }
+}
+
+/// A saved preset: either a plain query string, or a table whose query narrows
+/// another preset, e.g. `backend = { extends = "rust", query = "path:server/" }`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum PresetEntry {
+    Query(String),
+    Table {
+        query: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        extends: Option<String>,
+    },
+}
+
+impl PresetEntry {
+    /// The preset's own query, without anything it extends.
+    pub fn query(&self) -> &str {
+        match self {
+            PresetEntry::Query(query) | PresetEntry::Table { query, .. } => query,
+        }
+    }
+
+    fn extends(&self) -> Option<&str> {
+        match self {
+            PresetEntry::Query(_) => None,
+            PresetEntry::Table { extends, .. } => extends.as_deref(),
+        }
+    }
+}
+
+impl From<String> for PresetEntry {
+    fn from(query: String) -> Self {
+        PresetEntry::Query(query)
+    }
+}
+
+impl fmt::Display for PresetEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.extends() {
+            Some(parent) => write!(f, "{} (extends {parent})", self.query()),
+            None => f.write_str(self.query()),
+        }
+    }
 }
 
 /// Returns the path to the global configuration file.
@@ -26,7 +176,7 @@ pub fn global_config_path() -> Option<PathBuf> {
 }
 
 /// Searches for a local `.rdump.toml` in the given directory and its parents.
-fn find_local_config(start_dir: &Path) -> Option<PathBuf> {
+pub(crate) fn find_local_config(start_dir: &Path) -> Option<PathBuf> {
     for ancestor in start_dir.ancestors() {
         let config_path = ancestor.join(".rdump.toml");
         if config_path.exists() {
@@ -37,8 +187,13 @@ fn find_local_config(start_dir: &Path) -> Option<PathBuf> {
 }
 
 /// Finds and loads the configuration, merging global and local files.
+/// Presets come back fully resolved: a preset that `extends` another holds the
+/// combined query `(parent) & (child)`.
 pub fn load_config() -> Result<Config> {
     let mut final_config = Config::default();
+    // Global presets, then local ones, so a local preset can extend the global
+    // preset it overrides.
+    let mut preset_layers = Vec::new();
 
     // 1. Load the global config file, if it exists.
     if let Some(global_config_path) = global_config_path() {
@@ -46,8 +201,11 @@ pub fn load_config() -> Result<Config> {
             let global_config_str = fs::read_to_string(&global_config_path).with_context(|| {
                 format!("Failed to read global config at {global_config_path:?}")
             })?;
-            let global_config: Config = toml::from_str(&global_config_str)?;
-            final_config.presets.extend(global_config.presets);
+            let global_config = parse_config(&global_config_str, &global_config_path)?;
+            preset_layers.push(global_config.presets);
+            final_config.tags.extend(global_config.tags);
+            final_config.types.extend(global_config.types);
+            final_config.defaults = final_config.defaults.overlay(global_config.defaults);
         }
     }
 
@@ -57,14 +215,84 @@ pub fn load_config() -> Result<Config> {
         if local_config_path.exists() {
             let local_config_str = fs::read_to_string(&local_config_path)
                 .with_context(|| format!("Failed to read local config at {local_config_path:?}"))?;
-            let local_config: Config = toml::from_str(&local_config_str)?;
-            final_config.presets.extend(local_config.presets);
+            let local_config = parse_config(&local_config_str, &local_config_path)?;
+            preset_layers.push(local_config.presets);
+            final_config.tags.extend(local_config.tags);
+            final_config.types.extend(local_config.types);
+            final_config.defaults = final_config.defaults.overlay(local_config.defaults);
         }
     }
 
+    final_config.presets = resolve_presets(&preset_layers)?;
     Ok(final_config)
 }
 
+/// Parses the config file at `path`, refusing one written by a newer rdump.
+pub(crate) fn parse_config(text: &str, path: &Path) -> Result<Config> {
+    let config: Config = toml::from_str(text)?;
+    if let Some(version) = config.version.filter(|&v| v > CONFIG_VERSION) {
+        return Err(anyhow!(
+            "{} is config version {version}, but this rdump only reads up to version \
+             {CONFIG_VERSION}. Upgrade rdump to use it.",
+            path.display()
+        ));
+    }
+    Ok(config)
+}
+
+/// Flattens `extends` chains across config layers (lowest priority first), so every
+/// returned preset is a plain query. Unknown parents and cycles are errors.
+fn resolve_presets(
+    layers: &[HashMap<String, PresetEntry>],
+) -> Result<HashMap<String, PresetEntry>> {
+    let mut resolved = HashMap::new();
+    for name in layers.iter().flat_map(|layer| layer.keys()) {
+        if !resolved.contains_key(name) {
+            let query = resolve_preset(name, layers, layers.len(), &mut Vec::new())?;
+            resolved.insert(name.clone(), PresetEntry::Query(query));
+        }
+    }
+    Ok(resolved)
+}
+
+/// Resolves preset `name` from the highest of the first `below` layers that defines it.
+/// `chain` holds the presets (and their layers) currently being resolved.
+fn resolve_preset(
+    name: &str,
+    layers: &[HashMap<String, PresetEntry>],
+    below: usize,
+    chain: &mut Vec<(String, usize)>,
+) -> Result<String> {
+    let (layer, entry) = layers[..below]
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, presets)| presets.get(name).map(|entry| (i, entry)))
+        .ok_or_else(|| match chain.last() {
+            Some((child, _)) => anyhow!("Preset '{}' extends unknown preset '{}'", child, name),
+            None => anyhow!("Preset '{}' not found", name),
+        })?;
+
+    if chain.iter().any(|(n, l)| n == name && *l == layer) {
+        let cycle: Vec<&str> = chain
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .chain(std::iter::once(name))
+            .collect();
+        return Err(anyhow!("Preset inheritance cycle: {}", cycle.join(" -> ")));
+    }
+
+    let Some(parent) = entry.extends() else {
+        return Ok(entry.query().to_string());
+    };
+    // A preset extending its own name builds on the one it overrides.
+    let parent_below = if parent == name { layer } else { layers.len() };
+    chain.push((name.to_string(), layer));
+    let parent_query = resolve_preset(parent, layers, parent_below, chain)?;
+    chain.pop();
+    Ok(format!("({}) & ({})", parent_query, entry.query()))
+}
+
 /// Saves the given config to the global configuration file.
 pub fn save_config(config: &Config) -> Result<()> {
     let path = global_config_path()
@@ -75,11 +303,16 @@ pub fn save_config(config: &Config) -> Result<()> {
             .with_context(|| format!("Failed to create config directory at {parent:?}"))?;
     }
 
-    let toml_string = toml::to_string_pretty(config)?;
+    // Whatever it was read as, the file is now written in the current format.
+    let mut table = toml::Table::try_from(config)?;
+    table.insert("version".into(), i64::from(CONFIG_VERSION).into());
+    let toml_string = toml::to_string_pretty(&table)?;
     fs::write(&path, toml_string)
         .with_context(|| format!("Failed to write global config to {path:?}"))?;
 
-    println!("Successfully saved config to {path:?}");
+    if !diagnostics::is_quiet() {
+        println!("Successfully saved config to {path:?}");
+    }
     Ok(())
 }
 
@@ -142,6 +375,8 @@ mod tests {
         writeln!(
             local_file,
             r#"
+            tags = ["PERF"]
+
             [presets]
             docs = "ext:md | ext:txt"
             scripts = "ext:sh"
@@ -156,10 +391,132 @@ mod tests {
         env::set_current_dir(&original_dir).unwrap();
 
         assert_eq!(config.presets.len(), 3);
-        assert_eq!(config.presets.get("rust").unwrap(), "ext:rs");
-        assert_eq!(config.presets.get("scripts").unwrap(), "ext:sh");
-        assert_eq!(config.presets.get("docs").unwrap(), "ext:md | ext:txt");
+        assert_eq!(config.presets.get("rust").unwrap().query(), "ext:rs");
+        assert_eq!(config.presets.get("scripts").unwrap().query(), "ext:sh");
+        assert_eq!(config.presets.get("docs").unwrap().query(), "ext:md | ext:txt");
+        assert_eq!(config.tags, vec!["PERF".to_string()]);
 
         env::remove_var("RDUMP_TEST_CONFIG_DIR");
     }
+
+    fn layer(toml_str: &str) -> HashMap<String, PresetEntry> {
+        toml::from_str::<Config>(toml_str).unwrap().presets
+    }
+
+    #[test]
+    fn test_resolve_presets_extends_across_layers() {
+        let global = layer(
+            r#"
+            [presets]
+            rust = "ext:rs"
+            "#,
+        );
+        let local = layer(
+            r#"
+            [presets]
+            backend = { extends = "rust", query = "path:server/" }
+            api = { extends = "backend", query = "contains:route" }
+            rust = { extends = "rust", query = "!path:tests/" }
+            "#,
+        );
+
+        let presets = resolve_presets(&[global, local]).unwrap();
+        // `rust` extends the global `rust` it overrides; everything else sees the local one.
+        assert_eq!(presets["rust"].query(), "(ext:rs) & (!path:tests/)");
+        assert_eq!(
+            presets["backend"].query(),
+            "((ext:rs) & (!path:tests/)) & (path:server/)"
+        );
+        assert_eq!(
+            presets["api"].query(),
+            "(((ext:rs) & (!path:tests/)) & (path:server/)) & (contains:route)"
+        );
+    }
+
+    #[test]
+    fn test_resolve_presets_errors() {
+        let cycle = layer(
+            r#"
+            [presets]
+            a = { extends = "b", query = "ext:rs" }
+            b = { extends = "a", query = "ext:py" }
+            "#,
+        );
+        let err = resolve_presets(&[cycle]).unwrap_err().to_string();
+        assert!(err.starts_with("Preset inheritance cycle: "), "{err}");
+
+        let unknown = layer(
+            r#"
+            [presets]
+            a = { extends = "missing", query = "ext:rs" }
+            "#,
+        );
+        let err = resolve_presets(&[unknown]).unwrap_err().to_string();
+        assert_eq!(err, "Preset 'a' extends unknown preset 'missing'");
+    }
+
+    #[test]
+    fn test_preset_entry_round_trips_through_toml() {
+        let mut config = Config::default();
+        config
+            .presets
+            .insert("rust".to_string(), PresetEntry::from("ext:rs".to_string()));
+        config.presets.insert(
+            "backend".to_string(),
+            PresetEntry::Table {
+                query: "path:server/".to_string(),
+                extends: Some("rust".to_string()),
+            },
+        );
+        let toml_string = toml::to_string_pretty(&config).unwrap();
+        let parsed: Config = toml::from_str(&toml_string).unwrap();
+        assert_eq!(parsed.presets, config.presets);
+    }
+
+    #[test]
+    fn test_defaults_merge_into_keeps_cli_flags() {
+        let defaults: Defaults = toml::from_str(
+            r#"
+            format = "json-extended"
+            line-numbers = true
+            context = 3
+            theme = "InspiredGitHub"
+            pager = "less -R"
+            "#,
+        )
+        .unwrap();
+
+        let mut args = SearchArgs::default();
+        defaults.merge_into(&mut args);
+        assert_eq!(args.format, Format::JsonExtended);
+        assert!(args.line_numbers);
+        assert_eq!(args.context, Some(3));
+        assert_eq!(args.theme.as_deref(), Some("InspiredGitHub"));
+        assert_eq!(args.pager, Some(Some("less -R".to_string())));
+
+        let mut args = SearchArgs {
+            explicit_flags: ["format", "context"].map(String::from).into(),
+            format: Format::Paths,
+            context: Some(0),
+            ..Default::default()
+        };
+        defaults.merge_into(&mut args);
+        assert_eq!(args.format, Format::Paths);
+        assert_eq!(args.context, Some(0));
+
+        // Flags given on the command line win even at their built-in defaults.
+        let mut args = SearchArgs {
+            explicit_flags: ["format", "line_numbers"].map(String::from).into(),
+            ..Default::default()
+        };
+        defaults.merge_into(&mut args);
+        assert_eq!(args.format, Format::Hunks);
+        assert!(!args.line_numbers);
+    }
+
+    #[test]
+    fn test_defaults_reject_unknown_flags() {
+        let err = toml::from_str::<Config>("[defaults]\nformatt = \"paths\"\n").unwrap_err();
+        assert!(err.to_string().contains("formatt"));
+    }
 }
@@ -0,0 +1,22 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by `--quiet`. Warnings and status messages are printed from deep inside the
+/// search, e.g. by predicates, so this is process-wide rather than threaded through.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Silences (or restores) warnings and status messages for the rest of the process.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether warnings and status messages are silenced.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Prints `Warning: <message>` to stderr, unless silenced.
+pub fn warn(message: impl std::fmt::Display) {
+    if !is_quiet() {
+        eprintln!("Warning: {message}");
+    }
+}
@@ -1,11 +1,53 @@
 use anyhow::{anyhow, Context, Result};
-use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use tree_sitter::{Parser, Range, Tree};
 
+use crate::metrics::METRICS;
 use crate::parser::{AstNode, LogicalOperator, PredicateKey};
-use crate::predicates::PredicateEvaluator;
+use crate::predicates::code_aware::profiles::HunkGranularity;
+use crate::predicates::PredicateRegistry;
+
+/// Reads a file's text with any leading UTF-8 byte order mark removed, so a BOM
+/// can't hide a match on the first line. Everything that turns hunk byte offsets
+/// back into text reads files this way, so the offsets line up. Binary files have
+/// no text, and read as empty.
+pub fn read_source(path: impl AsRef<Path>) -> io::Result<String> {
+    Ok(read_text(path.as_ref())?.map_or_else(String::new, |(content, _)| content))
+}
+
+/// How much of a file is checked for a NUL byte to tell whether it's binary.
+const BINARY_CHECK_LEN: usize = 8000;
+
+/// Whether the file at `path` is binary, i.e. has a NUL byte near the start, as Git
+/// decides it. Only that start of the file is read.
+pub fn is_binary_file(path: impl AsRef<Path>) -> io::Result<bool> {
+    let mut start = Vec::with_capacity(BINARY_CHECK_LEN);
+    fs::File::open(path)?
+        .take(BINARY_CHECK_LEN as u64)
+        .read_to_end(&mut start)?;
+    Ok(start.contains(&0))
+}
+
+/// Like `read_source`, but also says whether the file wasn't valid UTF-8, in which
+/// case the invalid bytes have been replaced with U+FFFD. `None` if the file is
+/// binary, i.e. has a NUL byte near the start, as Git decides it.
+fn read_text(path: &Path) -> io::Result<Option<(String, bool)>> {
+    let bytes = fs::read(path)?;
+    if bytes[..bytes.len().min(BINARY_CHECK_LEN)].contains(&0) {
+        return Ok(None);
+    }
+    let (content, lossy) = match String::from_utf8(bytes) {
+        Ok(content) => (content, false),
+        Err(e) => (String::from_utf8_lossy(e.as_bytes()).into_owned(), true),
+    };
+    let content = match content.strip_prefix('\u{FEFF}') {
+        Some(stripped) => stripped.to_string(),
+        None => content,
+    };
+    Ok(Some((content, lossy)))
+}
 
 /// The result of an evaluation for a single file.
 #[derive(Debug, Clone)]
@@ -13,7 +55,40 @@ pub enum MatchResult {
     // For simple, non-hunkable predicates like `ext:rs` or `size:>10kb`
     Boolean(bool),
     // For code-aware predicates that can identify specific code blocks.
-    Hunks(Vec<Range>),
+    Hunks(Vec<Hunk>),
+}
+
+/// A matched region of a file. Code-aware matches on a definition also say what it
+/// defines, so `--format=symbols` doesn't have to parse the file again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hunk {
+    pub range: Range,
+    pub symbol: Option<Symbol>,
+}
+
+/// The definition a hunk points at, e.g. the name `User` of a `struct`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    /// Where the definition's name is in the file.
+    pub name: Range,
+    pub kind: &'static str,
+}
+
+impl From<Range> for Hunk {
+    fn from(range: Range) -> Self {
+        Hunk {
+            range,
+            symbol: None,
+        }
+    }
+}
+
+impl std::ops::Deref for Hunk {
+    type Target = Range;
+
+    fn deref(&self) -> &Range {
+        &self.range
+    }
 }
 
 /// Holds the context for a single file being evaluated.
@@ -24,6 +99,13 @@ pub struct FileContext {
     content: Option<String>,
     // Cache for the parsed tree-sitter AST
     tree: Option<Tree>,
+    /// Whether reading a file that isn't valid UTF-8 is an error, as with
+    /// `--strict-encoding`, rather than a lossy read.
+    strict_encoding: bool,
+    encoding_lossy: bool,
+    binary: bool,
+    /// Overrides the language profile's hunk granularity, as `--hunk-granularity` does.
+    hunk_granularity: Option<HunkGranularity>,
 }
 
 impl FileContext {
@@ -33,13 +115,73 @@ impl FileContext {
             root,
             content: None,
             tree: None,
+            strict_encoding: false,
+            encoding_lossy: false,
+            binary: false,
+            hunk_granularity: None,
         }
     }
 
+    /// Creates a context for a file whose content is already in memory, e.g. in a
+    /// browser where there is no file system. The file is never read from disk.
+    pub fn from_content(path: PathBuf, content: String) -> Self {
+        FileContext {
+            path,
+            root: PathBuf::new(),
+            content: Some(content),
+            tree: None,
+            strict_encoding: false,
+            encoding_lossy: false,
+            binary: false,
+            hunk_granularity: None,
+        }
+    }
+
+    /// Makes reading a file that isn't valid UTF-8 an error, instead of replacing
+    /// the invalid bytes.
+    pub fn with_strict_encoding(mut self, strict: bool) -> Self {
+        self.strict_encoding = strict;
+        self
+    }
+
+    /// Makes semantic matches report `granularity` hunks, whatever the language's
+    /// profile prefers.
+    pub fn with_hunk_granularity(mut self, granularity: Option<HunkGranularity>) -> Self {
+        self.hunk_granularity = granularity;
+        self
+    }
+
+    pub fn hunk_granularity(&self) -> Option<HunkGranularity> {
+        self.hunk_granularity
+    }
+
+    /// Whether the content was read lossily because the file isn't valid UTF-8.
+    pub fn encoding_lossy(&self) -> bool {
+        self.encoding_lossy
+    }
+
+    /// Whether the file turned out to be binary when its content was read. Its
+    /// content is then empty, and searches skip it.
+    pub fn is_binary(&self) -> bool {
+        self.binary
+    }
+
     pub fn get_content(&mut self) -> Result<&str> {
         if self.content.is_none() {
-            let content = fs::read_to_string(&self.path)
+            let text = read_text(&self.path)
                 .with_context(|| format!("Failed to read file {}", self.path.display()))?;
+            let Some((content, lossy)) = text else {
+                self.binary = true;
+                return Ok(self.content.insert(String::new()));
+            };
+            if lossy && self.strict_encoding {
+                return Err(anyhow!(
+                    "Failed to read file {}: it is not valid UTF-8",
+                    self.path.display()
+                ));
+            }
+            METRICS.bytes_read(content.len());
+            self.encoding_lossy = lossy;
             self.content = Some(content);
         }
         Ok(self.content.as_ref().unwrap())
@@ -61,61 +203,227 @@ impl FileContext {
         }
         Ok(self.tree.as_ref().unwrap())
     }
+
+    /// The content together with its tree, for reading the text of the tree's nodes.
+    pub fn get_content_and_tree(
+        &mut self,
+        language: tree_sitter::Language,
+    ) -> Result<(&str, &Tree)> {
+        self.get_tree(language)?;
+        Ok((self.content.as_ref().unwrap(), self.tree.as_ref().unwrap()))
+    }
+}
+
+/// How much work a predicate needs to evaluate a file, cheapest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EvaluationTier {
+    /// Only the path and file system metadata, e.g. `ext:` or `size:`.
+    Metadata,
+    /// The file's content, e.g. `contains:`.
+    Content,
+    /// The file's syntax tree, parsed with tree-sitter, e.g. `func:`.
+    Syntax,
+}
+
+impl EvaluationTier {
+    /// The tier of a predicate key. Keys added with [`PredicateKey::Other`] are
+    /// assumed to read the content.
+    pub fn of(key: &PredicateKey) -> Self {
+        match key {
+            PredicateKey::Ext
+            | PredicateKey::Name
+            | PredicateKey::IName
+            | PredicateKey::Path
+            | PredicateKey::IPath
+            | PredicateKey::In
+            | PredicateKey::Size
+            | PredicateKey::Modified
+            | PredicateKey::Since
+            | PredicateKey::Before => EvaluationTier::Metadata,
+            PredicateKey::Contains
+            | PredicateKey::Matches
+            | PredicateKey::Duplicate
+            | PredicateKey::Tag
+            | PredicateKey::Other(_) => EvaluationTier::Content,
+            _ => EvaluationTier::Syntax,
+        }
+    }
+}
+
+/// What evaluating a query against a file will cost, from [`Evaluator::estimate_cost`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueryCost {
+    /// Whether some predicate reads the file's content.
+    pub requires_content_read: bool,
+    /// Whether some predicate parses the file with tree-sitter.
+    pub requires_tree_sitter: bool,
+    /// How many predicates the query has. Short-circuiting may skip some of them.
+    pub estimated_predicates: usize,
+}
+
+/// A node evaluated by [`Evaluator::evaluate_traced`], and what it evaluated to.
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    /// How deeply the node is nested in the query; the root is 0.
+    pub depth: usize,
+    /// The node as written in a query, e.g. `func:main`, or `&`, `|` or `!`.
+    pub label: String,
+    pub result: MatchResult,
+    /// Whether an `&` or `|` skipped its right side.
+    pub short_circuited: bool,
+}
+
+/// The steps of a traced evaluation so far, in the order the nodes were entered.
+#[derive(Default)]
+struct Trace {
+    steps: Vec<TraceStep>,
+    depth: usize,
+}
+
+impl Trace {
+    /// Records `node` before evaluating it, and returns its step's index.
+    fn enter(&mut self, node: &AstNode) -> usize {
+        self.steps.push(TraceStep {
+            depth: self.depth,
+            label: node_label(node),
+            result: MatchResult::Boolean(false),
+            short_circuited: false,
+        });
+        self.depth += 1;
+        self.steps.len() - 1
+    }
+
+    fn exit(&mut self, step: usize, result: &MatchResult, short_circuited: bool) {
+        self.depth -= 1;
+        self.steps[step].result = result.clone();
+        self.steps[step].short_circuited = short_circuited;
+    }
+}
+
+/// How `node` itself is written in a query: `key:value` for a predicate, with the
+/// value quoted if it needs to be, or its operator.
+pub fn node_label(node: &AstNode) -> String {
+    match node {
+        AstNode::Predicate(key, value) => {
+            let needs_quotes = value.is_empty()
+                || value
+                    .chars()
+                    .any(|c| c.is_whitespace() || "&|!()'\"".contains(c));
+            if needs_quotes {
+                format!("{}:{value:?}", key.as_ref())
+            } else {
+                format!("{}:{value}", key.as_ref())
+            }
+        }
+        AstNode::LogicalOp(LogicalOperator::And, _, _) => "&".to_string(),
+        AstNode::LogicalOp(LogicalOperator::Or, _, _) => "|".to_string(),
+        AstNode::Not(_) => "!".to_string(),
+    }
 }
 
 /// The main evaluator struct. It holds the AST and the predicate registry.
 pub struct Evaluator {
     ast: AstNode,
-    registry: HashMap<PredicateKey, Box<dyn PredicateEvaluator + Send + Sync>>,
+    registry: PredicateRegistry,
 }
 
 impl Evaluator {
-    pub fn new(
-        ast: AstNode,
-        registry: HashMap<PredicateKey, Box<dyn PredicateEvaluator + Send + Sync>>,
-    ) -> Self {
+    pub fn new(ast: AstNode, registry: PredicateRegistry) -> Self {
         Evaluator { ast, registry }
     }
 
+    /// Estimates the cost of evaluating the query against a file, without evaluating
+    /// it, e.g. to decide whether to run it in a hot loop. `ext:rs & name:main.rs`
+    /// needs only metadata, while `contains:` reads the content and `func:` also
+    /// parses it.
+    pub fn estimate_cost(&self) -> QueryCost {
+        let mut cost = QueryCost::default();
+        add_node_cost(&self.ast, &mut cost);
+        cost
+    }
+
     /// Evaluates the query for a given file path.
     pub fn evaluate(&self, context: &mut FileContext) -> Result<MatchResult> {
-        self.evaluate_node(&self.ast, context)
+        self.evaluate_node(&self.ast, context, &mut None)
+    }
+
+    /// Like `evaluate`, but also records each node that was evaluated, in query
+    /// order, for `rdump explain --file`. Nodes skipped by short-circuiting are
+    /// left out.
+    pub fn evaluate_traced(
+        &self,
+        context: &mut FileContext,
+    ) -> Result<(MatchResult, Vec<TraceStep>)> {
+        let mut trace = Some(Trace::default());
+        let result = self.evaluate_node(&self.ast, context, &mut trace)?;
+        Ok((result, trace.map(|t| t.steps).unwrap_or_default()))
+    }
+
+    /// Recursively evaluates an AST node, recording it in `trace` if there is one.
+    fn evaluate_node(
+        &self,
+        node: &AstNode,
+        context: &mut FileContext,
+        trace: &mut Option<Trace>,
+    ) -> Result<MatchResult> {
+        let step = trace.as_mut().map(|t| t.enter(node));
+        let (result, short_circuited) = self.evaluate_node_inner(node, context, trace)?;
+        if let (Some(t), Some(step)) = (trace.as_mut(), step) {
+            t.exit(step, &result, short_circuited);
+        }
+        Ok(result)
     }
 
-    /// Recursively evaluates an AST node.
-    fn evaluate_node(&self, node: &AstNode, context: &mut FileContext) -> Result<MatchResult> {
+    /// Evaluates an AST node, and says whether a logical operator skipped its right side.
+    fn evaluate_node_inner(
+        &self,
+        node: &AstNode,
+        context: &mut FileContext,
+        trace: &mut Option<Trace>,
+    ) -> Result<(MatchResult, bool)> {
         match node {
-            AstNode::Predicate(key, value) => self.evaluate_predicate(key, value, context),
+            AstNode::Predicate(key, value) => {
+                Ok((self.evaluate_predicate(key, value, context)?, false))
+            }
             AstNode::LogicalOp(op, left, right) => {
-                let left_res = self.evaluate_node(left, context)?;
+                let left_res = self.evaluate_node(left, context, trace)?;
 
                 // Short-circuit AND if left is false
                 if *op == LogicalOperator::And && !left_res.is_match() {
-                    return Ok(MatchResult::Boolean(false));
+                    return Ok((MatchResult::Boolean(false), true));
                 }
 
                 // Short-circuit OR if left is a full-file match
                 if *op == LogicalOperator::Or {
                     if let MatchResult::Boolean(true) = left_res {
-                        return Ok(left_res);
+                        return Ok((left_res, true));
                     }
                 }
 
-                let right_res = self.evaluate_node(right, context)?;
-                Ok(left_res.combine_with(right_res, op))
+                let right_res = self.evaluate_node(right, context, trace)?;
+                Ok((left_res.combine_with(right_res, op), false))
             }
             AstNode::Not(inner_node) => {
-                // If the inner predicate of a NOT is not in the registry (e.g., a content
+                // If any predicate under a NOT is not in the registry (e.g., a content
                 // predicate during the metadata-only pass), we cannot definitively say the file
                 // *doesn't* match. We must assume it *could* match and let the full evaluator decide.
-                if let AstNode::Predicate(key, _) = &**inner_node {
-                    if !self.registry.contains_key(key) {
-                        return Ok(MatchResult::Boolean(true));
-                    }
+                if !self.can_evaluate(inner_node) {
+                    return Ok((MatchResult::Boolean(true), false));
                 }
-                let result = self.evaluate_node(inner_node, context)?;
-                Ok(MatchResult::Boolean(!result.is_match()))
+                let result = self.evaluate_node(inner_node, context, trace)?;
+                Ok((MatchResult::Boolean(!result.is_match()), false))
+            }
+        }
+    }
+
+    /// Whether every predicate in `node` is in the registry.
+    fn can_evaluate(&self, node: &AstNode) -> bool {
+        match node {
+            AstNode::Predicate(key, _) => self.registry.contains_key(key),
+            AstNode::LogicalOp(_, left, right) => {
+                self.can_evaluate(left) && self.can_evaluate(right)
             }
+            AstNode::Not(inner) => self.can_evaluate(inner),
         }
     }
 
@@ -136,7 +444,25 @@ impl Evaluator {
     }
 }
 
+/// Removes repeated hunks from sorted `hunks`, keeping the definition any of the
+/// copies points at.
+fn dedup_hunks(hunks: &mut Vec<Hunk>) {
+    hunks.dedup_by(|later, earlier| {
+        let same = later.range == earlier.range;
+        if same {
+            earlier.symbol = earlier.symbol.or(later.symbol);
+        }
+        same
+    });
+}
+
 impl MatchResult {
+    /// Hunks for `ranges` that don't point at any definition, as text predicates
+    /// like `contains:` find.
+    pub fn from_ranges(ranges: Vec<Range>) -> Self {
+        MatchResult::Hunks(ranges.into_iter().map(Hunk::from).collect())
+    }
+
     /// Returns true if the result is considered a match.
     pub fn is_match(&self) -> bool {
         match self {
@@ -163,7 +489,7 @@ impl MatchResult {
             (MatchResult::Hunks(mut a), MatchResult::Hunks(b)) => {
                 a.extend(b);
                 a.sort_by_key(|r| r.start_byte);
-                a.dedup();
+                dedup_hunks(&mut a);
                 MatchResult::Hunks(a)
             }
             // One is a hunk, the other is a full-file match (true). Keep the hunks.
@@ -187,7 +513,7 @@ impl MatchResult {
             (MatchResult::Hunks(mut a), MatchResult::Hunks(b)) => {
                 a.extend(b);
                 a.sort_by_key(|r| r.start_byte);
-                a.dedup();
+                dedup_hunks(&mut a);
                 MatchResult::Hunks(a)
             }
             // One is a hunk, the other is a non-match. Keep the hunks.
@@ -201,6 +527,22 @@ impl MatchResult {
     }
 }
 
+fn add_node_cost(node: &AstNode, cost: &mut QueryCost) {
+    match node {
+        AstNode::Predicate(key, _) => {
+            cost.estimated_predicates += 1;
+            let tier = EvaluationTier::of(key);
+            cost.requires_content_read |= tier >= EvaluationTier::Content;
+            cost.requires_tree_sitter |= tier == EvaluationTier::Syntax;
+        }
+        AstNode::LogicalOp(_, left, right) => {
+            add_node_cost(left, cost);
+            add_node_cost(right, cost);
+        }
+        AstNode::Not(inner) => add_node_cost(inner, cost),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,8 +566,8 @@ mod tests {
             start_point: Point { row: 0, column: 0 },
             end_point: Point { row: 0, column: 0 },
         }];
-        let result1 = MatchResult::Hunks(hunks1);
-        let result2 = MatchResult::Hunks(hunks2);
+        let result1 = MatchResult::from_ranges(hunks1);
+        let result2 = MatchResult::from_ranges(hunks2);
 
         let combined = result1.combine_with(result2, &LogicalOperator::And);
 
@@ -252,8 +594,8 @@ mod tests {
             start_point: Point { row: 0, column: 0 },
             end_point: Point { row: 0, column: 0 },
         }];
-        let result1 = MatchResult::Hunks(hunks1);
-        let result2 = MatchResult::Hunks(hunks2);
+        let result1 = MatchResult::from_ranges(hunks1);
+        let result2 = MatchResult::from_ranges(hunks2);
 
         let combined = result1.combine_with(result2, &LogicalOperator::Or);
 
@@ -283,6 +625,34 @@ mod tests {
         assert_eq!(context.get_content().unwrap(), "hello");
     }
 
+    #[test]
+    fn test_file_context_strips_bom() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("main.rs");
+        fs::write(&file_path, "\u{FEFF}fn main() {}\n").unwrap();
+
+        let mut context = FileContext::new(file_path.clone(), dir.path().to_path_buf());
+        assert_eq!(context.get_content().unwrap(), "fn main() {}\n");
+        assert_eq!(read_source(&file_path).unwrap(), "fn main() {}\n");
+    }
+
+    #[test]
+    fn test_file_context_reads_latin1_lossily() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("notes.txt");
+        // "café" in Latin-1: 0xE9 on its own isn't valid UTF-8.
+        fs::write(&file_path, b"caf\xe9\n").unwrap();
+
+        let mut context = FileContext::new(file_path.clone(), dir.path().to_path_buf());
+        assert_eq!(context.get_content().unwrap(), "caf\u{FFFD}\n");
+        assert!(context.encoding_lossy());
+
+        let mut strict =
+            FileContext::new(file_path, dir.path().to_path_buf()).with_strict_encoding(true);
+        let error = strict.get_content().unwrap_err().to_string();
+        assert!(error.contains("is not valid UTF-8"), "{error}");
+    }
+
     #[test]
     fn test_file_context_tree_caching() {
         let dir = tempdir().unwrap();
@@ -301,4 +671,160 @@ mod tests {
 
         assert_eq!(tree1_sexp, tree2_sexp);
     }
+
+    #[test]
+    fn test_custom_predicate_in_registry() {
+        use crate::parser::parse_query;
+        use crate::predicates::{create_metadata_predicate_registry, PredicateEvaluator};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct AlwaysTrue(Arc<AtomicUsize>);
+
+        impl PredicateEvaluator for AlwaysTrue {
+            fn evaluate(
+                &self,
+                _context: &mut FileContext,
+                _key: &PredicateKey,
+                _value: &str,
+            ) -> Result<MatchResult> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(MatchResult::Boolean(true))
+            }
+        }
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("main.rs");
+        fs::write(&file_path, "fn main() {}").unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut registry = create_metadata_predicate_registry();
+        registry.insert(
+            PredicateKey::Other("custom".to_string()),
+            AlwaysTrue(calls.clone()),
+        );
+        let evaluator = Evaluator::new(parse_query("ext:rs & custom:anything").unwrap(), registry);
+
+        let mut context = FileContext::new(file_path, dir.path().to_path_buf());
+        assert!(evaluator.evaluate(&mut context).unwrap().is_match());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_evaluate_traced() {
+        use crate::parser::parse_query;
+        use crate::predicates::create_predicate_registry;
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("main.rs");
+        fs::write(&file_path, "fn main() {}\n").unwrap();
+
+        let query = parse_query("ext:rs & (name:main.rs | contains:'not here') & func:main").unwrap();
+        let evaluator = Evaluator::new(query, create_predicate_registry());
+        let mut context = FileContext::new(file_path, dir.path().to_path_buf());
+        let (result, trace) = evaluator.evaluate_traced(&mut context).unwrap();
+        assert!(matches!(&result, MatchResult::Hunks(hunks) if hunks.len() == 1));
+
+        let steps: Vec<(usize, &str, bool)> = trace
+            .iter()
+            .map(|step| (step.depth, step.label.as_str(), step.short_circuited))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (0, "&", false),
+                (1, "&", false),
+                (2, "ext:rs", false),
+                (2, "|", true),
+                (3, "name:main.rs", false),
+                (1, "func:main", false),
+            ]
+        );
+        assert!(matches!(trace[4].result, MatchResult::Boolean(true)));
+    }
+
+    #[test]
+    fn test_node_label_quotes_values() {
+        let node = AstNode::Predicate(PredicateKey::Contains, "a b".to_string());
+        assert_eq!(node_label(&node), "contains:\"a b\"");
+        let node = AstNode::Predicate(PredicateKey::Func, "main".to_string());
+        assert_eq!(node_label(&node), "func:main");
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        use crate::parser::parse_query;
+        use crate::predicates::create_predicate_registry;
+
+        let cost = |query| {
+            Evaluator::new(parse_query(query).unwrap(), create_predicate_registry())
+                .estimate_cost()
+        };
+        assert_eq!(
+            cost("ext:rs & name:main.rs"),
+            QueryCost {
+                requires_content_read: false,
+                requires_tree_sitter: false,
+                estimated_predicates: 2,
+            }
+        );
+        assert_eq!(
+            cost("ext:rs & contains:TODO"),
+            QueryCost {
+                requires_content_read: true,
+                requires_tree_sitter: false,
+                estimated_predicates: 2,
+            }
+        );
+        assert_eq!(
+            cost("size:>1kb | !(func:main & ext:rs)"),
+            QueryCost {
+                requires_content_read: true,
+                requires_tree_sitter: true,
+                estimated_predicates: 3,
+            }
+        );
+        assert!(cost("custom:anything").requires_content_read);
+    }
+
+    #[test]
+    fn test_metadata_tier_matches_metadata_registry() {
+        use crate::predicates::{create_metadata_predicate_registry, create_predicate_registry};
+
+        let metadata_registry = create_metadata_predicate_registry();
+        let keys = [
+            "ext", "name", "iname", "path", "ipath", "in", "size", "modified", "contains",
+            "matches", "duplicate", "tag", "func", "import", "comment", "call", "hook",
+        ];
+        for key in keys.map(PredicateKey::from) {
+            assert!(create_predicate_registry().contains_key(&key), "{key:?}");
+            assert_eq!(
+                EvaluationTier::of(&key) == EvaluationTier::Metadata,
+                metadata_registry.contains_key(&key),
+                "{key:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_metadata_pass_keeps_negated_content_queries() {
+        use crate::parser::parse_query;
+        use crate::predicates::create_metadata_predicate_registry;
+
+        // The metadata pass can't tell whether `!(contains:x & ext:rs)` is false for a
+        // Rust file, so it has to keep it.
+        let evaluator = Evaluator::new(
+            parse_query("!(contains:x & ext:rs)").unwrap(),
+            create_metadata_predicate_registry(),
+        );
+        let mut context = FileContext::new(PathBuf::from("main.rs"), PathBuf::from("/"));
+        assert!(evaluator.evaluate(&mut context).unwrap().is_match());
+
+        // Metadata alone still decides `!(ext:rs)`.
+        let evaluator = Evaluator::new(
+            parse_query("!(ext:rs | name:*.rs)").unwrap(),
+            create_metadata_predicate_registry(),
+        );
+        assert!(!evaluator.evaluate(&mut context).unwrap().is_match());
+    }
 }
@@ -1,49 +1,159 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Local}; // For formatting timestamps
 use once_cell::sync::Lazy;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
 use std::fs;
 use std::io::Write;
 use std::ops::Range as StdRange;
 #[cfg(unix)]
-use std::os::unix::fs::PermissionsExt; // For Unix permissions
-use std::path::PathBuf;
+use std::os::unix::fs::{MetadataExt, PermissionsExt}; // For Unix permissions and ownership
+use std::path::{Component, Path, PathBuf};
 use syntect::easy::HighlightLines;
-use syntect::highlighting::{Style, ThemeSet};
+use syntect::highlighting::{Color, Style, Theme, ThemeSet};
+use syntect::html::{styled_line_to_highlighted_html, IncludeBackground};
 use syntect::parsing::SyntaxSet;
 use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
 use tree_sitter::Range;
+use unicode_width::UnicodeWidthChar;
 
 // We need to pass the format enum from main.rs
+use crate::diagnostics;
+use crate::evaluator::{is_binary_file, read_source, FileContext, Hunk};
+use crate::links::Permalinks;
+use crate::predicates::code_aware::{enclosing_scope, profiles::list_language_profiles};
+use crate::roots::{relative_to_root, unique_relative_path};
+use crate::template::{Placeholder, Template};
+use crate::tokens::estimate_tokens;
 use crate::Format;
 
 // Lazily load syntax and theme sets once.
 static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
 static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
 
+/// The highlighting theme used when `--theme` isn't given.
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// Looks up one of syntect's bundled themes by name.
+pub fn find_theme(name: &str) -> Result<&'static Theme> {
+    THEME_SET.themes.get(name).ok_or_else(|| {
+        let mut available: Vec<&str> = THEME_SET.themes.keys().map(String::as_str).collect();
+        available.sort();
+        anyhow!(
+            "Unknown theme '{}'. Available themes: {}",
+            name,
+            available.join(", ")
+        )
+    })
+}
+
+/// How wide `rich` draws its boxes when the terminal's width isn't known.
+pub const DEFAULT_RICH_WIDTH: usize = 80;
+
+/// `rich` boxes are never drawn narrower than this, however small the terminal.
+const RICH_MIN_WIDTH: usize = 20;
+
+/// Printed between files by the formats that have per-file headers.
+const FILE_SEPARATOR: &str = "\n---\n";
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 struct FileOutput {
     path: String,
-    content: String,
+    // Left out by `--format=json` when the file matched with hunks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    // Only populated by `--format=json-extended`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hunks: Option<Vec<SerializableHunk>>,
+    // The hunks left out by `--max-hunks-per-file`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    more_matches: Option<usize>,
+}
+
+/// A matched code block, with 1-based inclusive line numbers and byte offsets into the file.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct SerializableHunk {
+    start_line: usize,
+    end_line: usize,
+    start_byte: usize,
+    end_byte: usize,
+    text: String,
+}
+
+impl SerializableHunk {
+    fn from_range(range: &Range, content: &str) -> Self {
+        SerializableHunk {
+            start_line: range.start_point.row + 1,
+            end_line: range.end_point.row + 1,
+            start_byte: range.start_byte,
+            end_byte: range.end_byte,
+            text: content
+                .get(range.start_byte..range.end_byte)
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+
+    /// Builds a hunk covering whole lines, e.g. a hunk widened by `--context`.
+    /// `lines` is the 0-based, end-exclusive line range.
+    fn from_lines(lines: StdRange<usize>, content: &str) -> Self {
+        let mut line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .skip(lines.start);
+        let start_byte = line_starts.next().unwrap_or(content.len());
+        let end_byte = line_starts
+            .nth(lines.len().saturating_sub(1))
+            .map_or(content.len(), |next_line| next_line - 1);
+        let text = content[start_byte..end_byte].trim_end_matches('\r');
+        SerializableHunk {
+            start_line: lines.start + 1,
+            end_line: lines.end,
+            start_byte,
+            end_byte: start_byte + text.len(),
+            text: text.to_string(),
+        }
+    }
+}
+
+/// Looks up the name of the language profile that handles this file's extension.
+fn language_for_path(path: &std::path::Path) -> Option<String> {
+    let extension = path.extension().and_then(|s| s.to_str())?;
+    list_language_profiles()
+        .into_iter()
+        .find(|p| p.extensions.contains(&extension))
+        .map(|p| p.name.to_string())
 }
 
 fn print_markdown_format(
     writer: &mut impl Write,
-    matching_files: &[(PathBuf, Vec<Range>)],
+    matching_files: &[(PathBuf, Vec<Hunk>)],
     with_line_numbers: bool,
     with_headers: bool,
+    toc: bool,
 ) -> Result<()> {
+    // The table of contents links to headings, so the file headers become headings.
+    let toc = toc && with_headers;
+    if toc {
+        print_markdown_toc(writer, matching_files)?;
+    }
     for (i, (path, _)) in matching_files.iter().enumerate() {
         if with_headers {
             if i > 0 {
-                writeln!(writer, "
----
-")?;
+                writeln!(writer, "{FILE_SEPARATOR}")?;
+            }
+            if toc {
+                writeln!(writer, "## {}", markdown_file_heading(path))?;
+            } else {
+                writeln!(writer, "File: {}", path.display())?;
+                writeln!(writer, "---")?;
             }
-            writeln!(writer, "File: {}", path.display())?;
-            writeln!(writer, "---")?;
         }
-        let content = fs::read_to_string(path)?;
+        let content = read_source(path)?;
         let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
 
         // Markdown format should always use fenced content, not ANSI colors.
@@ -52,51 +162,431 @@ fn print_markdown_format(
     Ok(())
 }
 
+fn markdown_file_heading(path: &std::path::Path) -> String {
+    format!("File: {}", path.display())
+}
+
+/// Writes a `## Contents` list with a link to each file's heading, plus its hunk
+/// count and size.
+fn print_markdown_toc(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+) -> Result<()> {
+    let mut anchors = HashMap::new();
+    writeln!(writer, "## Contents\n")?;
+    github_anchor("Contents", &mut anchors);
+    for (path, hunks) in matching_files {
+        let size = fs::metadata(path)
+            .with_context(|| format!("Failed to read metadata for {}", path.display()))?
+            .len();
+        writeln!(
+            writer,
+            "- [{}](#{}) ({}, {})",
+            path.display(),
+            github_anchor(&markdown_file_heading(path), &mut anchors),
+            hunk_count_label(hunks.len()),
+            format_size(size)
+        )?;
+    }
+    writeln!(writer, "{FILE_SEPARATOR}")?;
+    Ok(())
+}
+
+/// The anchor GitHub gives a heading: lower-cased, with spaces turned into hyphens
+/// and other punctuation dropped. Repeated headings get `-1`, `-2`, ... appended,
+/// counted in `seen`.
+fn github_anchor(heading: &str, seen: &mut HashMap<String, usize>) -> String {
+    let anchor: String = heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect();
+    let count = seen.entry(anchor.clone()).or_insert(0);
+    let anchor = match *count {
+        0 => anchor,
+        n => format!("{anchor}-{n}"),
+    };
+    *count += 1;
+    anchor
+}
+
 fn print_cat_format(
     writer: &mut impl Write,
-    matching_files: &[(PathBuf, Vec<Range>)],
-    with_line_numbers: bool,
-    use_color: bool,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    options: &OutputOptions,
 ) -> Result<()> {
     for (path, _) in matching_files {
-        let content = fs::read_to_string(path)?;
-        if use_color {
+        let content = read_source(path)?;
+        if options.use_color {
             // To terminal
             print_highlighted_content(
                 writer,
                 &content,
                 path.extension().and_then(|s| s.to_str()).unwrap_or(""),
-                with_line_numbers,
+                options.with_line_numbers,
                 0,
+                find_theme(&options.theme)?,
             )?;
         } else {
-            print_plain_content(writer, &content, with_line_numbers, 0)?; // To file/pipe
+            print_plain_content(writer, &content, options.with_line_numbers, 0)?; // To file/pipe
+        }
+    }
+    Ok(())
+}
+
+/// Draws each file in a box `options.rich_width` columns wide: a header with its
+/// path, size, language and modification time, then its content, highlighted when
+/// `use_color` is set. Lines too long for the box are cut short with `…`.
+fn print_rich_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    options: &OutputOptions,
+) -> Result<()> {
+    // Leave room for the borders and a space either side of the text.
+    let width = options.rich_width.max(RICH_MIN_WIDTH) - 4;
+    let rule = "─".repeat(width + 2);
+    let theme = find_theme(&options.theme)?;
+    for (path, _) in matching_files {
+        let metadata = fs::metadata(path)
+            .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
+        let mut details = vec![format_size(metadata.len())];
+        details.extend(language_for_path(path));
+        if let Ok(modified) = metadata.modified() {
+            details.push(
+                DateTime::<Local>::from(modified)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+            );
+        }
+        let name = path.display().to_string();
+        let details = details.join(" · ");
+
+        writeln!(writer, "┌{rule}┐")?;
+        write_rich_row(writer, &[(None, &name)], width, options.use_color)?;
+        write_rich_row(writer, &[(None, &details)], width, options.use_color)?;
+        writeln!(writer, "├{rule}┤")?;
+        // Tabs have no width of their own, so they'd throw the right border out.
+        let content = read_source(path)?.replace('\t', "    ");
+        let lines: Vec<Vec<(Option<Style>, &str)>> = if options.use_color {
+            let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+            highlight_lines(&content, extension, theme)?
+                .into_iter()
+                .map(|ranges| {
+                    ranges
+                        .into_iter()
+                        .map(|(style, text)| (Some(style), text))
+                        .collect()
+                })
+                .collect()
+        } else {
+            content.lines().map(|line| vec![(None, line)]).collect()
+        };
+        for (i, mut line) in lines.into_iter().enumerate() {
+            let number = format!("{: >5} | ", i + 1);
+            if options.with_line_numbers {
+                line.insert(0, (None, &number));
+            }
+            write_rich_row(writer, &line, width, options.use_color)?;
         }
+        writeln!(writer, "└{rule}┘")?;
     }
     Ok(())
 }
 
+/// Writes one line of a `rich` box: `segments`, styled if `use_color` is set, cut
+/// short with `…` if wider than `width` columns, then padded out to the border.
+fn write_rich_row(
+    writer: &mut impl Write,
+    segments: &[(Option<Style>, &str)],
+    width: usize,
+    use_color: bool,
+) -> Result<()> {
+    let segments: Vec<(Option<Style>, &str)> = segments
+        .iter()
+        .map(|&(style, text)| (style, text.trim_end_matches(['\r', '\n'])))
+        .collect();
+    let char_width = |c: char| c.width().unwrap_or(0);
+    let full_width: usize = segments
+        .iter()
+        .flat_map(|(_, text)| text.chars())
+        .map(char_width)
+        .sum();
+    let cut = full_width > width;
+    let limit = if cut { width - 1 } else { width };
+
+    write!(writer, "│ ")?;
+    let mut used = 0;
+    let mut full = false;
+    for (style, text) in segments {
+        let mut end = 0;
+        for (i, c) in text.char_indices() {
+            if used + char_width(c) > limit {
+                full = true;
+                break;
+            }
+            used += char_width(c);
+            end = i + c.len_utf8();
+        }
+        let text = &text[..end];
+        match style {
+            Some(style) if use_color => write!(
+                writer,
+                "{}",
+                as_24_bit_terminal_escaped(&[(style, text)], false)
+            )?,
+            _ => write!(writer, "{text}")?,
+        }
+        if full {
+            break;
+        }
+    }
+    if use_color {
+        write!(writer, "\x1b[0m")?;
+    }
+    if cut {
+        write!(writer, "…")?;
+        used += 1;
+    }
+    writeln!(writer, "{} │", " ".repeat(width - used))?;
+    Ok(())
+}
+
+/// Builds the JSON representation of a matching file, with `language` and `hunks`
+/// only when `extended` is set.
+fn file_output(path: &PathBuf, hunks: &[Hunk], extended: bool) -> Result<FileOutput> {
+    let content = read_for_output(path)?;
+    let (language, hunks) = if extended {
+        let hunks = hunks
+            .iter()
+            .map(|range| SerializableHunk::from_range(range, &content))
+            .collect();
+        (language_for_path(path), Some(hunks))
+    } else {
+        (None, None)
+    };
+    Ok(FileOutput {
+        path: path.to_string_lossy().to_string(),
+        content: Some(content),
+        language,
+        hunks,
+        more_matches: None,
+    })
+}
+
+/// Builds the `--format=json` representation of a matching file: the matched hunks,
+/// widened by `--context` and merged like the `hunks` format, or the whole content
+/// for a whole-file match or with `--json-full-content`.
+fn json_file_output(path: &PathBuf, hunks: &[Hunk], options: &OutputOptions) -> Result<FileOutput> {
+    if hunks.is_empty() || options.json_full_content {
+        return file_output(path, hunks, false);
+    }
+    let content = read_for_output(path)?;
+    let lines: Vec<&str> = content.lines().collect();
+    let hunks = get_contextual_line_ranges(
+        hunks,
+        &lines,
+        options.before_context,
+        options.after_context,
+        options.merge_gap,
+    )
+    .into_iter()
+    .map(|range| SerializableHunk::from_lines(range, &content))
+    .collect();
+    Ok(FileOutput {
+        path: path.to_string_lossy().to_string(),
+        content: None,
+        language: None,
+        hunks: Some(hunks),
+        more_matches: None,
+    })
+}
+
+fn read_for_output(path: &PathBuf) -> Result<String> {
+    read_source(path)
+        .with_context(|| format!("Failed to read file for final output: {}", path.display()))
+}
+
+fn print_ndjson_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+) -> Result<()> {
+    for (path, hunks) in matching_files {
+        print_ndjson_line(writer, path, hunks, None)?;
+    }
+    Ok(())
+}
+
+/// Writes one file as a line of NDJSON. `more_matches` is the number of hunks
+/// `--max-hunks-per-file` left out, if it cut the file short.
+fn print_ndjson_line(
+    writer: &mut impl Write,
+    path: &PathBuf,
+    hunks: &[Hunk],
+    more_matches: Option<usize>,
+) -> Result<()> {
+    let mut output = file_output(path, hunks, true)?;
+    output.more_matches = more_matches;
+    serde_json::to_writer(&mut *writer, &output)?;
+    writeln!(writer)?;
+    // Flush per line so consumers like `jq` see each result immediately.
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the files as a JSON array. `more_matches` holds the number of hunks
+/// `--max-hunks-per-file` left out of each file it cut short.
 fn print_json_format(
     writer: &mut impl Write,
-    matching_files: &[(PathBuf, Vec<Range>)],
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    options: &OutputOptions,
+    more_matches: &HashMap<PathBuf, usize>,
 ) -> Result<()> {
     let mut outputs = Vec::new();
-    for (path, _) in matching_files {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read file for final output: {}", path.display()))?;
-        outputs.push(FileOutput {
-            path: path.to_string_lossy().to_string(),
-            content,
-        });
+    for (path, hunks) in matching_files {
+        let mut output = if options.format == Format::JsonExtended {
+            file_output(path, hunks, true)?
+        } else {
+            json_file_output(path, hunks, options)?
+        };
+        output.more_matches = more_matches.get(path).copied();
+        outputs.push(output);
     }
     // Use to_writer_pretty for readable JSON output
     serde_json::to_writer_pretty(writer, &outputs)?;
     Ok(())
 }
 
+/// Writes the same fields as `json-extended`, as a YAML sequence with one entry per
+/// file. Each entry is serialized on its own, so files can be written as they arrive.
+fn print_yaml_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+) -> Result<()> {
+    if matching_files.is_empty() {
+        writeln!(writer, "[]")?;
+    }
+    for (path, hunks) in matching_files {
+        // A one-element sequence renders as a single `- path: ...` entry.
+        serde_yaml::to_writer(&mut *writer, &[file_output(path, hunks, true)?])?;
+    }
+    Ok(())
+}
+
+/// Writes a SARIF 2.1.0 log for code scanning tools, with the query as the single
+/// rule and one result per hunk. Whole-file matches get a location without a region.
+fn print_sarif_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    query: &str,
+) -> Result<()> {
+    let rule_id = if query.is_empty() { "rdump" } else { query };
+    let message = format!("Matches rdump query `{rule_id}`");
+
+    let mut results = Vec::new();
+    for (path, hunks) in matching_files {
+        let artifact_location = json!({ "uri": sarif_uri(path) });
+        if hunks.is_empty() {
+            results.push(json!({
+                "ruleId": rule_id,
+                "ruleIndex": 0,
+                "level": "warning",
+                "message": { "text": message },
+                "locations": [{ "physicalLocation": { "artifactLocation": artifact_location } }],
+            }));
+            continue;
+        }
+        let content = read_for_output(path)?;
+        let lines: Vec<&str> = content.split('\n').collect();
+        for hunk in hunks {
+            results.push(json!({
+                "ruleId": rule_id,
+                "ruleIndex": 0,
+                "level": "warning",
+                "message": { "text": message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": artifact_location,
+                        "region": {
+                            "startLine": hunk.start_point.row + 1,
+                            "startColumn": sarif_column(&lines, hunk.start_point),
+                            "endLine": hunk.end_point.row + 1,
+                            "endColumn": sarif_column(&lines, hunk.end_point),
+                        },
+                    },
+                }],
+            }));
+        }
+    }
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": [{
+                        "id": rule_id,
+                        "shortDescription": { "text": message },
+                    }],
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_writer_pretty(&mut *writer, &log)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// The 1-based SARIF column of `point`. SARIF counts UTF-16 code units, while
+/// tree-sitter counts bytes.
+fn sarif_column(lines: &[&str], point: tree_sitter::Point) -> usize {
+    let units = lines
+        .get(point.row)
+        .and_then(|line| line.get(..point.column))
+        .map_or(point.column, |before| before.encode_utf16().count());
+    units + 1
+}
+
+/// Turns a result path into a SARIF artifact URI: relative paths use forward slashes
+/// without a leading `./`, absolute paths become `file://` URIs. Characters that
+/// aren't allowed in a URI are percent-encoded.
+fn sarif_uri(path: &std::path::Path) -> String {
+    let path = path.strip_prefix(".").unwrap_or(path);
+    let text = path.to_string_lossy().replace('\\', "/");
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            // Keep Windows drive letters (`C:`) readable.
+            b':' => encoded.push(':'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    if path.is_absolute() {
+        if encoded.starts_with('/') {
+            format!("file://{encoded}")
+        } else {
+            format!("file:///{encoded}")
+        }
+    } else {
+        encoded
+    }
+}
+
 fn print_paths_format(
     writer: &mut impl Write,
-    matching_files: &[(PathBuf, Vec<Range>)],
+    matching_files: &[(PathBuf, Vec<Hunk>)],
 ) -> Result<()> {
     for (path, _) in matching_files {
         writeln!(writer, "{}", path.display())?;
@@ -104,73 +594,863 @@ fn print_paths_format(
     Ok(())
 }
 
-fn print_find_format(
+/// The count `--format=count` reports for a file: its number of hunks, or 1 for a
+/// whole-file match.
+fn match_count(hunks: &[Hunk]) -> usize {
+    hunks.len().max(1)
+}
+
+fn print_count_lines(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+) -> Result<()> {
+    for (path, hunks) in matching_files {
+        writeln!(writer, "{}: {}", path.display(), match_count(hunks))?;
+    }
+    Ok(())
+}
+
+fn print_count_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+) -> Result<()> {
+    print_count_lines(writer, matching_files)?;
+    let total: usize = matching_files.iter().map(|(_, hunks)| match_count(hunks)).sum();
+    writeln!(writer, "total: {total}")?;
+    Ok(())
+}
+
+/// Prints each path followed by a NUL byte, like `find -print0`. Paths are written
+/// as raw bytes where the platform allows, so any file name survives `xargs -0`.
+fn print_paths0_format(
     writer: &mut impl Write,
-    matching_files: &[(PathBuf, Vec<Range>)],
+    matching_files: &[(PathBuf, Vec<Hunk>)],
 ) -> Result<()> {
     for (path, _) in matching_files {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            writer.write_all(path.as_os_str().as_bytes())?;
+        }
+        #[cfg(not(unix))]
+        write!(writer, "{}", path.display())?;
+        writer.write_all(b"\0")?;
+    }
+    Ok(())
+}
+
+/// Prints `path:line:column:text` for the first line of every hunk, like `grep -n`
+/// or `rg --vimgrep`. Whole-file matches have no position, so only the path is printed.
+fn print_grep_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    use_color: bool,
+) -> Result<()> {
+    for (path, hunks) in matching_files {
+        let path_text = if use_color {
+            format!("\x1b[35m{}\x1b[0m", path.display())
+        } else {
+            path.display().to_string()
+        };
+        if hunks.is_empty() {
+            writeln!(writer, "{path_text}")?;
+            continue;
+        }
+
+        let content = read_source(path)
+            .with_context(|| format!("Failed to read file for final output: {}", path.display()))?;
+        let lines: Vec<&str> = content.lines().collect();
+
+        let mut hunks: Vec<&Hunk> = hunks.iter().collect();
+        hunks.sort_by_key(|h| (h.start_byte, h.end_byte));
+        hunks.dedup_by_key(|h| h.start_byte);
+
+        for hunk in hunks {
+            let row = hunk.start_point.row;
+            let line = lines.get(row).copied().unwrap_or("");
+            let start = hunk.start_point.column.min(line.len());
+            let line_number = row + 1;
+            let column = start + 1;
+            if use_color {
+                // Highlight the matched span, up to the end of the first line.
+                let end = if hunk.end_point.row == row {
+                    hunk.end_point.column.clamp(start, line.len())
+                } else {
+                    line.len()
+                };
+                writeln!(
+                    writer,
+                    "{path_text}:\x1b[32m{line_number}\x1b[0m:{column}:{}\x1b[1;31m{}\x1b[0m{}",
+                    &line[..start],
+                    &line[start..end],
+                    &line[end..]
+                )?;
+            } else {
+                writeln!(writer, "{path_text}:{line_number}:{column}:{line}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints a `path:line:col: text` entry for every hunk, taken from the hunk's first
+/// line, in the shape Vim's default `errorformat` reads into the quickfix list.
+/// Whole-file matches still get an entry, `path:1:1: <query>`, so they land in the
+/// list too. Never colored.
+fn print_quickfix_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    query: &str,
+) -> Result<()> {
+    // The message must stay on one line.
+    let query = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    for (path, hunks) in matching_files {
+        if hunks.is_empty() {
+            writeln!(writer, "{}:1:1: {query}", path.display())?;
+            continue;
+        }
+
+        let content = read_source(path)
+            .with_context(|| format!("Failed to read file for final output: {}", path.display()))?;
+        let lines: Vec<&str> = content.lines().collect();
+
+        let mut hunks: Vec<&Hunk> = hunks.iter().collect();
+        hunks.sort_by_key(|h| (h.start_byte, h.end_byte));
+        hunks.dedup_by_key(|h| (h.start_byte, h.end_byte));
+
+        for hunk in hunks {
+            let row = hunk.start_point.row;
+            let line = lines.get(row).copied().unwrap_or("");
+            // Vim counts columns in bytes, as tree-sitter does.
+            let column = hunk.start_point.column.min(line.len()) + 1;
+            writeln!(
+                writer,
+                "{}:{}:{column}: {}",
+                path.display(),
+                row + 1,
+                line.trim()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints a permalink to the lines of every hunk, or to the file for whole-file matches.
+fn print_links_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    permalinks: &Permalinks,
+) -> Result<()> {
+    for (path, hunks) in matching_files {
+        if hunks.is_empty() {
+            writeln!(writer, "{}", permalinks.link(path, None))?;
+            continue;
+        }
+        let mut hunks: Vec<&Hunk> = hunks.iter().collect();
+        hunks.sort_by_key(|h| (h.start_byte, h.end_byte));
+        hunks.dedup_by_key(|h| (h.start_byte, h.end_byte));
+        for hunk in hunks {
+            let start = hunk.start_point.row + 1;
+            // A hunk that ends at the start of a line doesn't include that line.
+            let end = if hunk.end_point.column == 0 && hunk.end_point.row > hunk.start_point.row {
+                hunk.end_point.row
+            } else {
+                hunk.end_point.row + 1
+            };
+            writeln!(writer, "{}", permalinks.link(path, Some((start, end))))?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints a `name<TAB>path<TAB>line<TAB>kind` line for every hunk that is a definition,
+/// e.g. from `def:*`, giving a symbol index of the matching files. Hunks that aren't
+/// definitions (such as `contains:` matches) and whole-file matches are skipped.
+fn print_symbols_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+) -> Result<()> {
+    for (path, hunks) in matching_files {
+        let mut hunks: Vec<&Hunk> = hunks.iter().filter(|h| h.symbol.is_some()).collect();
+        if hunks.is_empty() {
+            continue;
+        }
+        hunks.sort_by_key(|h| (h.start_byte, h.end_byte));
+        hunks.dedup_by_key(|h| (h.start_byte, h.end_byte));
+        let content = read_source(path)?;
+
+        for hunk in hunks {
+            let Some(symbol) = hunk.symbol else { continue };
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}",
+                &content[symbol.name.start_byte..symbol.name.end_byte],
+                path.display(),
+                hunk.start_point.row + 1,
+                symbol.kind
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn print_csv_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+) -> Result<()> {
+    print_csv_header(writer)?;
+    print_csv_rows(writer, matching_files)
+}
+
+fn print_csv_header(writer: &mut impl Write) -> Result<()> {
+    writeln!(writer, "path,start_line,end_line,size,modified,match_count")?;
+    Ok(())
+}
+
+fn print_csv_rows(writer: &mut impl Write, matching_files: &[(PathBuf, Vec<Hunk>)]) -> Result<()> {
+    for (path, hunks) in matching_files {
         let metadata = fs::metadata(path)
             .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
-        let size = metadata.len();
         let modified: DateTime<Local> = DateTime::from(metadata.modified()?);
+        let path_field = csv_escape(&path.to_string_lossy());
+        let file_fields = format!("{},{},{}", metadata.len(), modified.to_rfc3339(), hunks.len());
 
-        // Get permissions (basic implementation)
-        let perms = metadata.permissions();
-        #[cfg(unix)]
-        let mode = perms.mode();
-        #[cfg(not(unix))]
-        let mode = 0; // Placeholder for non-unix
-        let perms_str = format_mode(mode);
+        if hunks.is_empty() {
+            // Whole-file match: no line span.
+            writeln!(writer, "{path_field},,,{file_fields}")?;
+        }
+        for hunk in hunks {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                path_field,
+                hunk.start_point.row + 1,
+                hunk.end_point.row + 1,
+                file_fields
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Quotes a CSV field if it contains a delimiter, quote, or newline (RFC 4180).
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn print_xml_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+) -> Result<()> {
+    print_xml_start(writer)?;
+    print_xml_files(writer, matching_files)?;
+    print_xml_end(writer)
+}
+
+fn print_xml_start(writer: &mut impl Write) -> Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, "<rdump>")?;
+    Ok(())
+}
+
+fn print_xml_end(writer: &mut impl Write) -> Result<()> {
+    writeln!(writer, "</rdump>")?;
+    Ok(())
+}
+
+/// Writes one `<file>` element per file, with a `<hunk>` for each matched region.
+/// A whole-file match is written as a single hunk spanning the entire file.
+fn print_xml_files(writer: &mut impl Write, matching_files: &[(PathBuf, Vec<Hunk>)]) -> Result<()> {
+    for (path, hunks) in matching_files {
+        let content = read_source(path)
+            .with_context(|| format!("Failed to read file for final output: {}", path.display()))?;
+        let lines: Vec<&str> = LinesWithEndings::from(&content).collect();
+        let line_spans: Vec<(usize, usize)> = if hunks.is_empty() {
+            vec![(0, lines.len().saturating_sub(1))]
+        } else {
+            hunks
+                .iter()
+                .map(|h| (h.start_point.row, h.end_point.row))
+                .collect()
+        };
+
+        writeln!(
+            writer,
+            r#"  <file path="{}">"#,
+            escape_markup(&path.to_string_lossy())
+        )?;
+        for (start, end) in line_spans {
+            let text = lines
+                .get(start..=end.min(lines.len().saturating_sub(1)))
+                .unwrap_or_default()
+                .join("");
+            writeln!(
+                writer,
+                r#"    <hunk start-line="{}" end-line="{}">{}</hunk>"#,
+                start + 1,
+                end + 1,
+                xml_cdata(&text)
+            )?;
+        }
+        writeln!(writer, "  </file>")?;
+    }
+    Ok(())
+}
+
+/// Escapes text for XML or HTML, including inside double-quoted attributes. Whitespace
+/// other than spaces is written as character references so parsers don't normalize it away.
+fn escape_markup(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            '\t' => escaped.push_str("&#9;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Wraps text in a CDATA section. A `]]>` in the text would end the section early,
+/// so it is split across two sections.
+fn xml_cdata(text: &str) -> String {
+    format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+}
 
-        // Format size into human-readable string
-        let size_str = format_size(size);
+/// Writes a standalone HTML page: an index of the matching files, then a collapsible
+/// section per file with highlighted code. Every line number is an anchor
+/// (`#file-2-L14`) so a specific line can be linked to.
+fn print_html_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    options: &OutputOptions,
+) -> Result<()> {
+    let theme = find_theme(&options.theme)?;
+    let background = theme.settings.background.unwrap_or(Color::WHITE);
+    let foreground = theme.settings.foreground.unwrap_or(Color::BLACK);
 
-        // Format time
-        let time_str = modified.format("%b %d %H:%M").to_string();
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, r#"<html lang="en">"#)?;
+    writeln!(writer, "<head>")?;
+    writeln!(writer, r#"<meta charset="utf-8">"#)?;
+    writeln!(writer, "<title>rdump results</title>")?;
+    writeln!(writer, "<style>")?;
+    writeln!(writer, "body {{ font-family: sans-serif; margin: 2em; }}")?;
+    writeln!(writer, "summary {{ cursor: pointer; font-family: monospace; font-size: 1.1em; }}")?;
+    writeln!(
+        writer,
+        "pre {{ background-color: {}; color: {}; padding: 1em; overflow-x: auto; }}",
+        css_color(background),
+        css_color(foreground)
+    )?;
+    writeln!(writer, "a.line-number {{ color: inherit; opacity: 0.5; text-decoration: none; user-select: none; }}")?;
+    writeln!(writer, ".hunk-separator {{ opacity: 0.5; }}")?;
+    writeln!(writer, "</style>")?;
+    writeln!(writer, "</head>")?;
+    writeln!(writer, "<body>")?;
+    writeln!(writer, "<h1>rdump results</h1>")?;
 
+    writeln!(writer, "<nav>")?;
+    writeln!(writer, "<p>{} matching file(s)</p>", matching_files.len())?;
+    writeln!(writer, "<ol>")?;
+    for (i, (path, _)) in matching_files.iter().enumerate() {
         writeln!(
             writer,
-            "{:<12} {:>8} {} {}",
+            r##"<li><a href="#file-{}">{}</a></li>"##,
+            i + 1,
+            escape_markup(&path.to_string_lossy())
+        )?;
+    }
+    writeln!(writer, "</ol>")?;
+    writeln!(writer, "</nav>")?;
+
+    for (i, (path, hunks)) in matching_files.iter().enumerate() {
+        let file_id = format!("file-{}", i + 1);
+        let content = read_source(path)
+            .with_context(|| format!("Failed to read file for final output: {}", path.display()))?;
+        let lines: Vec<&str> = LinesWithEndings::from(&content).collect();
+        let line_ranges = if hunks.is_empty() {
+            // Boolean match, show the whole file
+            std::iter::once(0..lines.len()).collect()
+        } else {
+            get_contextual_line_ranges(
+                hunks,
+                &lines,
+                options.before_context,
+                options.after_context,
+                options.merge_gap,
+            )
+        };
+
+        let syntax = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .and_then(|ext| SYNTAX_SET.find_syntax_by_extension(ext))
+            .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+
+        writeln!(writer, r#"<details id="{file_id}" open>"#)?;
+        writeln!(
+            writer,
+            "<summary>{}</summary>",
+            escape_markup(&path.to_string_lossy())
+        )?;
+        write!(writer, "<pre><code>")?;
+        for (block, range) in line_ranges.into_iter().enumerate() {
+            if block > 0 {
+                writeln!(writer, r#"<span class="hunk-separator">...</span>"#)?;
+            }
+            let mut highlighter = HighlightLines::new(syntax, theme);
+            for line_index in range {
+                let line_number = line_index + 1;
+                let ranges = highlighter.highlight_line(lines[line_index], &SYNTAX_SET)?;
+                let html = styled_line_to_highlighted_html(&ranges, IncludeBackground::No)?;
+                write!(
+                    writer,
+                    r##"<span id="{file_id}-L{line_number}"><a class="line-number" href="#{file_id}-L{line_number}">{line_number: >5}</a>  {html}</span>"##,
+                )?;
+                if !lines[line_index].ends_with('\n') {
+                    writeln!(writer)?;
+                }
+            }
+        }
+        writeln!(writer, "</code></pre>")?;
+        writeln!(writer, "</details>")?;
+    }
+
+    writeln!(writer, "</body>")?;
+    writeln!(writer, "</html>")?;
+    Ok(())
+}
+
+fn css_color(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn print_find_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    long: bool,
+) -> Result<()> {
+    for (path, _) in matching_files {
+        // Like `ls -l`, a symlink reached with --follow is described as a link.
+        let metadata = fs::symlink_metadata(path)
+            .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
+        print_find_line(writer, path, &metadata, long)?;
+    }
+    Ok(())
+}
+
+/// Prints one `ls -l`-style line of the find format from already-read metadata.
+fn print_find_line(
+    writer: &mut impl Write,
+    path: &std::path::Path,
+    metadata: &fs::Metadata,
+    long: bool,
+) -> Result<()> {
+    let size = metadata.len();
+    let modified: DateTime<Local> = DateTime::from(metadata.modified()?);
+
+    let perms_str = format_mode(metadata);
+
+    // Format size into human-readable string
+    let size_str = format_size(size);
+
+    // Format time
+    let time_str = modified.format("%b %d %H:%M").to_string();
+
+    if long {
+        let owner = format_owner(metadata);
+        writeln!(
+            writer,
+            "{:>10} {:<12} {:>3} {:<8} {:<8} {:>8} {} {}",
+            owner.inode,
             perms_str,
+            owner.nlink,
+            owner.user,
+            owner.group,
             size_str,
             time_str,
             path.display()
         )?;
+        return Ok(());
     }
+
+    writeln!(
+        writer,
+        "{:<12} {:>8} {} {}",
+        perms_str,
+        size_str,
+        time_str,
+        path.display()
+    )?;
     Ok(())
 }
 
-fn print_hunks_format(
+/// Prints aggregates over the matching files instead of their content: totals,
+/// breakdowns by extension and top-level directory, and the largest files.
+fn print_stats_format(
     writer: &mut impl Write,
-    matching_files: &[(PathBuf, Vec<Range>)],
-    with_line_numbers: bool,
-    with_headers: bool,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    roots: &[PathBuf],
+) -> Result<()> {
+    let sizes: Vec<u64> = matching_files
+        .par_iter()
+        .map(|(path, _)| {
+            fs::metadata(path)
+                .map(|m| m.len())
+                .with_context(|| format!("Failed to read metadata for {}", path.display()))
+        })
+        .collect::<Result<_>>()?;
+
+    let mut by_extension: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    let mut by_directory: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for ((path, _), &size) in matching_files.iter().zip(&sizes) {
+        let extension = path
+            .extension()
+            .map_or("(none)".to_string(), |e| e.to_string_lossy().to_string());
+        // The top directory under the file's root; a file in the root itself is in `.`.
+        let directory = unique_relative_path(roots, path)
+            .parent()
+            .and_then(|parent| {
+                parent.components().find_map(|c| match c {
+                    Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                    _ => None,
+                })
+            })
+            .unwrap_or_else(|| ".".to_string());
+        for (map, key) in [(&mut by_extension, extension), (&mut by_directory, directory)] {
+            let entry = map.entry(key).or_default();
+            entry.0 += 1;
+            entry.1 += size;
+        }
+    }
+
+    let total_hunks: usize = matching_files.iter().map(|(_, hunks)| hunks.len()).sum();
+    writeln!(writer, "Files:       {}", matching_files.len())?;
+    writeln!(writer, "Total size:  {}", format_size(sizes.iter().sum()))?;
+    writeln!(writer, "Total hunks: {total_hunks}")?;
+
+    for (title, map) in [("By extension", by_extension), ("By directory", by_directory)] {
+        if map.is_empty() {
+            continue;
+        }
+        writeln!(writer, "\n{title}:")?;
+        let mut rows: Vec<_> = map.into_iter().collect();
+        // Most files first; ties stay in name order.
+        rows.sort_by_key(|(_, (count, _))| std::cmp::Reverse(*count));
+        let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, (count, size)) in rows {
+            writeln!(
+                writer,
+                "  {name:<width$}  {count:>5} {:<5}  {:>8}",
+                if count == 1 { "file" } else { "files" },
+                format_size(size)
+            )?;
+        }
+    }
+
+    if !matching_files.is_empty() {
+        writeln!(writer, "\nLargest files:")?;
+        let mut largest: Vec<(u64, &PathBuf)> = sizes
+            .iter()
+            .copied()
+            .zip(matching_files.iter().map(|(path, _)| path))
+            .collect();
+        largest.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+        for (size, path) in largest.into_iter().take(10) {
+            writeln!(writer, "  {:>8}  {}", format_size(size), path.display())?;
+        }
+    }
+    Ok(())
+}
+
+/// Estimates the tokens in the part of a file that gets printed: the whole file for
+/// whole-file matches, otherwise the hunks with their context lines.
+pub fn estimate_file_tokens(
+    path: &std::path::Path,
+    hunks: &[Hunk],
+    options: &OutputOptions,
+) -> Result<usize> {
+    let content = read_source(path)
+        .with_context(|| format!("Failed to read file for final output: {}", path.display()))?;
+    if hunks.is_empty() {
+        return Ok(estimate_tokens(&content));
+    }
+    let lines: Vec<&str> = LinesWithEndings::from(&content).collect();
+    let blocks = get_contextual_line_ranges(
+        hunks,
+        &lines,
+        options.before_context,
+        options.after_context,
+        options.merge_gap,
+    );
+    Ok(blocks
+        .into_iter()
+        .map(|block| estimate_tokens(&lines[block].join("")))
+        .sum())
+}
+
+/// Estimates the tokens `file` takes up in the output, as `options` formats it: its
+/// header, fences and line numbers included. Colors are left out, since they don't
+/// reach a model as text.
+pub fn estimate_output_tokens(
+    file: &(PathBuf, Vec<Hunk>),
+    options: &OutputOptions,
+) -> Result<usize> {
+    let options = OutputOptions {
+        use_color: false,
+        ..options.clone()
+    };
+    let mut output = Vec::new();
+    print_files(&mut output, std::slice::from_ref(file), &options)?;
+    Ok(estimate_tokens(&String::from_utf8_lossy(&output)))
+}
+
+/// Prints each file's estimated token count, largest first, and the total, like `wc`.
+fn print_tokens_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    options: &OutputOptions,
+) -> Result<()> {
+    let mut counts: Vec<(usize, &PathBuf)> = matching_files
+        .par_iter()
+        .map(|(path, hunks)| Ok((estimate_file_tokens(path, hunks, options)?, path)))
+        .collect::<Result<_>>()?;
+    // Largest first; ties stay in path order.
+    counts.sort_by_key(|(tokens, _)| std::cmp::Reverse(*tokens));
+
+    let total: usize = counts.iter().map(|(tokens, _)| tokens).sum();
+    let width = total.to_string().len();
+    for (tokens, path) in counts {
+        writeln!(writer, "{tokens:>width$}  {}", path.display())?;
+    }
+    writeln!(writer, "{total:>width$}  total")?;
+    Ok(())
+}
+
+/// Renders `--template` once per match: once per file for whole-file matches, and
+/// once per block of lines (with context, like `hunks`) for hunk matches.
+fn print_template_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    options: &OutputOptions,
+) -> Result<()> {
+    let template = options
+        .template
+        .as_ref()
+        .ok_or_else(|| anyhow!("--format=template needs a --template string"))?;
+    for (path, hunks) in matching_files {
+        let content = read_source(path)
+            .with_context(|| format!("Failed to read file for final output: {}", path.display()))?;
+        let metadata = fs::metadata(path)
+            .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
+        let lines: Vec<&str> = LinesWithEndings::from(&content).collect();
+        let blocks = if hunks.is_empty() {
+            std::iter::once(0..lines.len()).collect()
+        } else {
+            get_contextual_line_ranges(
+                hunks,
+                &lines,
+                options.before_context,
+                options.after_context,
+                options.merge_gap,
+            )
+        };
+
+        for block in blocks {
+            let mut failure = None;
+            let rendered = template.render(|placeholder| match placeholder {
+                Placeholder::Path => path.display().to_string(),
+                Placeholder::RelPath => relative_to_root(&options.root, path)
+                    .display()
+                    .to_string(),
+                Placeholder::Size => format_size(metadata.len()),
+                Placeholder::Modified => match metadata.modified() {
+                    Ok(time) => DateTime::<Local>::from(time)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string(),
+                    Err(_) => "-".to_string(),
+                },
+                Placeholder::Lines => lines.len().to_string(),
+                Placeholder::Hunks => hunks.len().to_string(),
+                Placeholder::LineStart => (block.start + 1).to_string(),
+                Placeholder::LineEnd => block.end.max(block.start + 1).to_string(),
+                Placeholder::Content => {
+                    let mut text = Vec::new();
+                    if let Err(e) = print_plain_content(
+                        &mut text,
+                        &lines[block.clone()].join(""),
+                        options.with_line_numbers,
+                        block.start,
+                    ) {
+                        failure = Some(e);
+                    }
+                    let text = String::from_utf8_lossy(&text);
+                    text.strip_suffix('\n').unwrap_or(&text).to_string()
+                }
+            });
+            if let Some(e) = failure {
+                return Err(e);
+            }
+            writeln!(writer, "{rendered}")?;
+        }
+    }
+    Ok(())
+}
+
+/// A directory (or file) in the tree built by `print_tree_format`.
+#[derive(Default)]
+struct TreeNode {
+    children: BTreeMap<OsString, TreeNode>,
+    /// The matching file at this node, if it is a file rather than a directory.
+    file: Option<(u64, usize)>,
+}
+
+/// Prints the matching files as an indented directory tree, like `tree`, with each
+/// file annotated by its size and hunk count. Only directories that lead to a
+/// matching file are shown.
+fn print_tree_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    use_color: bool,
+) -> Result<()> {
+    let mut root = TreeNode::default();
+    for (path, hunks) in matching_files {
+        let size = fs::metadata(path)
+            .with_context(|| format!("Failed to read metadata for {}", path.display()))?
+            .len();
+        let mut node = &mut root;
+        for component in path.components() {
+            if component == Component::CurDir {
+                continue;
+            }
+            node = node
+                .children
+                .entry(component.as_os_str().to_os_string())
+                .or_default();
+        }
+        node.file = Some((size, hunks.len()));
+    }
+
+    writeln!(writer, "{}", if use_color { "\x1b[1;34m.\x1b[0m" } else { "." })?;
+    let (mut dirs, mut files) = (0, 0);
+    print_tree_children(writer, &root, "", use_color, &mut dirs, &mut files)?;
+    writeln!(
+        writer,
+        "\n{} {}, {} {}",
+        dirs,
+        if dirs == 1 { "directory" } else { "directories" },
+        files,
+        if files == 1 { "file" } else { "files" }
+    )?;
+    Ok(())
+}
+
+fn print_tree_children(
+    writer: &mut impl Write,
+    node: &TreeNode,
+    prefix: &str,
     use_color: bool,
-    context_lines: usize,
+    dirs: &mut usize,
+    files: &mut usize,
+) -> Result<()> {
+    let count = node.children.len();
+    for (i, (name, child)) in node.children.iter().enumerate() {
+        let is_last = i + 1 == count;
+        let branch = if is_last { "└── " } else { "├── " };
+        let name = name.to_string_lossy();
+        match child.file {
+            Some((size, hunk_count)) => {
+                *files += 1;
+                writeln!(
+                    writer,
+                    "{prefix}{branch}{name} ({}, {})",
+                    format_size(size),
+                    hunk_count_label(hunk_count)
+                )?;
+            }
+            None => {
+                *dirs += 1;
+                if use_color {
+                    writeln!(writer, "{prefix}{branch}\x1b[1;34m{name}\x1b[0m")?;
+                } else {
+                    writeln!(writer, "{prefix}{branch}{name}")?;
+                }
+            }
+        }
+        let child_prefix = format!("{prefix}{}", if is_last { "    " } else { "│   " });
+        print_tree_children(writer, child, &child_prefix, use_color, dirs, files)?;
+    }
+    Ok(())
+}
+
+fn print_hunks_format(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    options: &OutputOptions,
 ) -> Result<()> {
+    let with_headers = !options.no_headers;
+    let with_line_numbers = options.with_line_numbers;
+    let use_color = options.use_color;
+    let theme = find_theme(&options.theme)?;
     for (i, (path, hunks)) in matching_files.iter().enumerate() {
         if with_headers {
             if i > 0 {
-                writeln!(writer, "\n---\n")?;
+                writeln!(writer, "{FILE_SEPARATOR}")?;
             }
             writeln!(writer, "File: {}", path.display())?;
             writeln!(writer, "---")?;
         }
-        let content = fs::read_to_string(path)?;
+        let mut context = FileContext::new(path.clone(), PathBuf::new());
+        let content = context.get_content()?.to_string();
         let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
 
         if hunks.is_empty() {
             // Boolean match, print the whole file
-            print_content_with_style(writer, &content, extension, with_line_numbers, use_color, 0)?;
+            print_content_with_style(
+                writer,
+                &content,
+                extension,
+                with_line_numbers,
+                use_color,
+                0,
+                theme,
+            )?;
         } else {
             // Hunk match, print with context
             let lines: Vec<&str> = LinesWithEndings::from(&content).collect();
-            let line_ranges = get_contextual_line_ranges(hunks, &lines, context_lines);
+            let line_ranges = get_contextual_line_ranges(
+                hunks,
+                &lines,
+                options.before_context,
+                options.after_context,
+                options.merge_gap,
+            );
 
             for (i, range) in line_ranges.iter().enumerate() {
                 if i > 0 {
                     writeln!(writer, "...")?;
                 }
+                // Name the function/class the (first) hunk in this block belongs to.
+                let first_hunk = hunks
+                    .iter()
+                    .filter(|h| range.contains(&h.start_point.row))
+                    .min_by_key(|h| h.start_byte);
+                if let Some(scope) =
+                    first_hunk.and_then(|h| enclosing_scope(&mut context, h.start_byte))
+                {
+                    writeln!(writer, "// In: {scope}")?;
+                }
                 let hunk_content = lines[range.clone()].join("");
                 print_content_with_style(
                     writer,
@@ -179,6 +1459,7 @@ fn print_hunks_format(
                     with_line_numbers,
                     use_color,
                     range.start,
+                    theme,
                 )?;
             }
         }
@@ -186,36 +1467,335 @@ fn print_hunks_format(
     Ok(())
 }
 
-/// Formats and prints the final output to a generic writer based on the chosen format.
+/// Formats and prints the final output to a generic writer using `options`. Binary
+/// files are printed without content, with a warning, by the formats that print it.
 pub fn print_output(
     writer: &mut impl Write,
-    matching_files: &[(PathBuf, Vec<Range>)],
-    format: &Format,
-    with_line_numbers: bool,
-    no_headers: bool,
-    use_color: bool,
-    context_lines: usize,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    options: &OutputOptions,
 ) -> Result<()> {
-    match format {
-        Format::Find => print_find_format(writer, matching_files)?,
+    for (path, _) in matching_files {
+        warn_if_binary(path, &options.format)?;
+    }
+    print_files(writer, matching_files, options)
+}
+
+/// Whether `format` prints the files' content. The formats that only list paths or
+/// metadata print binary files like any other.
+fn shows_content(format: &Format) -> bool {
+    !matches!(
+        format,
+        Format::Find
+            | Format::FindLong
+            | Format::Paths
+            | Format::Paths0
+            | Format::Tree
+            | Format::Stats
+            | Format::Count
+            | Format::Links
+    )
+}
+
+/// Warns that `path`'s content is left out of `format`'s output if it's a binary
+/// file, which has no text to show.
+fn warn_if_binary(path: &Path, format: &Format) -> Result<()> {
+    if shows_content(format)
+        && is_binary_file(path)
+            .with_context(|| format!("Failed to read file for final output: {}", path.display()))?
+    {
+        diagnostics::warn(format!(
+            "leaving out the content of {}: it is a binary file",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Prints `matching_files` as `print_output` does, without its binary file warnings.
+fn print_files(
+    writer: &mut impl Write,
+    matching_files: &[(PathBuf, Vec<Hunk>)],
+    options: &OutputOptions,
+) -> Result<()> {
+    match options.format {
+        Format::Find => print_find_format(writer, matching_files, false)?,
+        Format::FindLong => print_find_format(writer, matching_files, true)?,
         Format::Paths => print_paths_format(writer, matching_files)?,
-        Format::Json => print_json_format(writer, matching_files)?,
-        Format::Cat => print_cat_format(writer, matching_files, with_line_numbers, use_color)?,
-        Format::Markdown => {
-            print_markdown_format(writer, matching_files, with_line_numbers, !no_headers)?
+        Format::Paths0 => print_paths0_format(writer, matching_files)?,
+        Format::Grep => print_grep_format(writer, matching_files, options.use_color)?,
+        Format::Quickfix => print_quickfix_format(writer, matching_files, &options.query)?,
+        Format::Links => {
+            let permalinks = options
+                .permalinks
+                .as_ref()
+                .ok_or_else(|| anyhow!("--format=links needs the repository's web address"))?;
+            print_links_format(writer, matching_files, permalinks)?
         }
-        Format::Hunks => print_hunks_format(
+        Format::Symbols => print_symbols_format(writer, matching_files)?,
+        Format::Tree => print_tree_format(writer, matching_files, options.use_color)?,
+        Format::Stats => print_stats_format(writer, matching_files, &options.root)?,
+        Format::Template => print_template_format(writer, matching_files, options)?,
+        Format::Tokens => print_tokens_format(writer, matching_files, options)?,
+        Format::Count => print_count_format(writer, matching_files)?,
+        Format::Csv => print_csv_format(writer, matching_files)?,
+        Format::Xml => print_xml_format(writer, matching_files)?,
+        Format::Json | Format::JsonExtended => {
+            print_json_format(writer, matching_files, options, &HashMap::new())?
+        }
+        Format::Sarif => print_sarif_format(writer, matching_files, &options.query)?,
+        Format::Ndjson => print_ndjson_format(writer, matching_files)?,
+        Format::Yaml => print_yaml_format(writer, matching_files)?,
+        Format::Html => print_html_format(writer, matching_files, options)?,
+        Format::Cat => print_cat_format(writer, matching_files, options)?,
+        Format::Markdown => print_markdown_format(
             writer,
             matching_files,
-            with_line_numbers,
-            !no_headers,
-            use_color,
-            context_lines,
+            options.with_line_numbers,
+            !options.no_headers,
+            options.toc,
         )?,
+        Format::Rich => print_rich_format(writer, matching_files, options)?,
+        Format::Hunks => print_hunks_format(writer, matching_files, options)?,
     }
     Ok(())
 }
 
+/// The output settings for a search, as passed to `print_output`.
+#[derive(Debug, Clone)]
+pub struct OutputOptions {
+    pub format: Format,
+    pub with_line_numbers: bool,
+    pub no_headers: bool,
+    pub use_color: bool,
+    pub before_context: usize,
+    pub after_context: usize,
+    pub merge_gap: usize,
+    /// The syntect theme used for highlighting, e.g. `base16-ocean.dark`.
+    pub theme: String,
+    /// The query that produced the results, reported by `sarif` as its rule id.
+    pub query: String,
+    /// The search roots; `{relpath}` in a template, and the directories `stats`
+    /// groups files by, are relative to the one a file was found under.
+    pub root: Vec<PathBuf>,
+    /// The parsed `--template`, used by the `template` format.
+    pub template: Option<Template>,
+    /// Whether `json` writes each file's full content instead of its hunks.
+    pub json_full_content: bool,
+    /// Whether `markdown` starts with a table of contents.
+    pub toc: bool,
+    /// Where the `links` format points to; only set for that format.
+    pub permalinks: Option<Permalinks>,
+    /// Keeps only each file's first hunks in the `hunks`, `grep` and `json` formats.
+    pub max_hunks_per_file: Option<usize>,
+    /// How many columns wide `rich` draws its boxes.
+    pub rich_width: usize,
+}
+
+impl OutputOptions {
+    /// Plain `format` output: no color, context, headers or extras, highlighted with
+    /// the default theme, for the root `.`.
+    pub fn new(format: Format) -> Self {
+        OutputOptions {
+            format,
+            with_line_numbers: false,
+            no_headers: false,
+            use_color: false,
+            before_context: 0,
+            after_context: 0,
+            merge_gap: 0,
+            theme: DEFAULT_THEME.to_string(),
+            query: String::new(),
+            root: vec![PathBuf::from(".")],
+            template: None,
+            json_full_content: false,
+            toc: false,
+            permalinks: None,
+            max_hunks_per_file: None,
+            rich_width: DEFAULT_RICH_WIDTH,
+        }
+    }
+}
+
+/// Prints results one file at a time, so output can start before a search has
+/// finished. JSON documents, the HTML file index, the directory tree, the stats summary
+/// and the token counts need every result, so `json`, `json-extended`, `sarif`, `html`,
+/// `tree`, `stats` and `tokens` results are buffered until `finish`, as are `markdown`
+/// results with a table of contents.
+pub struct StreamingPrinter<W: Write> {
+    writer: W,
+    options: OutputOptions,
+    files_written: usize,
+    buffered: Vec<(PathBuf, Vec<Hunk>)>,
+    /// The running total for `--format=count`.
+    total_count: usize,
+    /// The hunks `--max-hunks-per-file` left out of buffered files, by path.
+    more_matches: HashMap<PathBuf, usize>,
+}
+
+impl<W: Write> StreamingPrinter<W> {
+    pub fn new(writer: W, options: OutputOptions) -> Self {
+        StreamingPrinter {
+            writer,
+            options,
+            files_written: 0,
+            buffered: Vec::new(),
+            total_count: 0,
+            more_matches: HashMap::new(),
+        }
+    }
+
+    /// Prints a single matching file using metadata that's already been read, e.g. to
+    /// sort the files, instead of reading it again. Only the find formats use it.
+    pub fn print_with_metadata(
+        &mut self,
+        file: (PathBuf, Vec<Hunk>),
+        metadata: Option<fs::Metadata>,
+    ) -> Result<()> {
+        let long = match self.options.format {
+            Format::Find => false,
+            Format::FindLong => true,
+            _ => return self.print(file),
+        };
+        let Some(metadata) = metadata else {
+            return self.print(file);
+        };
+        print_find_line(&mut self.writer, &file.0, &metadata, long)?;
+        self.files_written += 1;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Keeps the first `--max-hunks-per-file` hunks, in file order, for the formats
+    /// it applies to, and returns how many were left out.
+    fn cap_hunks(&self, mut file: (PathBuf, Vec<Hunk>)) -> ((PathBuf, Vec<Hunk>), usize) {
+        let applies = matches!(
+            self.options.format,
+            Format::Hunks
+                | Format::Grep
+                | Format::Quickfix
+                | Format::Json
+                | Format::JsonExtended
+                | Format::Ndjson
+        );
+        match self.options.max_hunks_per_file {
+            Some(max) if applies && file.1.len() > max => {
+                file.1.sort_by_key(|hunk| hunk.start_byte);
+                let more = file.1.len() - max;
+                file.1.truncate(max);
+                (file, more)
+            }
+            _ => (file, 0),
+        }
+    }
+
+    /// Prints a single matching file and flushes it to the writer. A binary file's
+    /// content is left out, with a warning, by the formats that print content.
+    pub fn print(&mut self, file: (PathBuf, Vec<Hunk>)) -> Result<()> {
+        warn_if_binary(&file.0, &self.options.format)?;
+        let (file, more_matches) = self.cap_hunks(file);
+        if more_matches > 0 {
+            self.more_matches.insert(file.0.clone(), more_matches);
+        }
+        let options = &self.options;
+        match options.format {
+            Format::Json
+            | Format::JsonExtended
+            | Format::Sarif
+            | Format::Html
+            | Format::Tree
+            | Format::Stats
+            | Format::Tokens => {
+                self.buffered.push(file);
+                return Ok(());
+            }
+            // The table of contents has to come before the first file.
+            Format::Markdown if options.toc => {
+                self.buffered.push(file);
+                return Ok(());
+            }
+            Format::Csv => {
+                // The header is only written once, before the first row.
+                if self.files_written == 0 {
+                    print_csv_header(&mut self.writer)?;
+                }
+                print_csv_rows(&mut self.writer, std::slice::from_ref(&file))?;
+            }
+            Format::Xml => {
+                if self.files_written == 0 {
+                    print_xml_start(&mut self.writer)?;
+                }
+                print_xml_files(&mut self.writer, std::slice::from_ref(&file))?;
+            }
+            Format::Count => {
+                print_count_lines(&mut self.writer, std::slice::from_ref(&file))?;
+                self.total_count += match_count(&file.1);
+            }
+            Format::Ndjson => {
+                let more_matches = (more_matches > 0).then_some(more_matches);
+                print_ndjson_line(&mut self.writer, &file.0, &file.1, more_matches)?;
+            }
+            _ => {
+                let has_headers = matches!(options.format, Format::Hunks | Format::Markdown)
+                    && !options.no_headers;
+                if has_headers && self.files_written > 0 {
+                    writeln!(self.writer, "{FILE_SEPARATOR}")?;
+                }
+                print_files(&mut self.writer, std::slice::from_ref(&file), options)?;
+                // Every line of a quickfix list has to be a location.
+                if more_matches > 0 && options.format != Format::Quickfix {
+                    writeln!(self.writer, "... (+{more_matches} more matches)")?;
+                }
+            }
+        }
+        self.files_written += 1;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes anything that was held back until all results were known.
+    pub fn finish(mut self) -> Result<()> {
+        match self.options.format {
+            Format::Json | Format::JsonExtended => {
+                print_json_format(
+                    &mut self.writer,
+                    &self.buffered,
+                    &self.options,
+                    &self.more_matches,
+                )?;
+            }
+            Format::Sarif => {
+                print_sarif_format(&mut self.writer, &self.buffered, &self.options.query)?
+            }
+            Format::Html => print_html_format(&mut self.writer, &self.buffered, &self.options)?,
+            Format::Tree => {
+                print_tree_format(&mut self.writer, &self.buffered, self.options.use_color)?
+            }
+            Format::Stats => {
+                print_stats_format(&mut self.writer, &self.buffered, &self.options.root)?
+            }
+            Format::Tokens => {
+                print_tokens_format(&mut self.writer, &self.buffered, &self.options)?
+            }
+            Format::Markdown if self.options.toc => {
+                print_files(&mut self.writer, &self.buffered, &self.options)?
+            }
+            Format::Count => writeln!(self.writer, "total: {}", self.total_count)?,
+            // An empty CSV result still gets its header row.
+            Format::Csv if self.files_written == 0 => print_csv_header(&mut self.writer)?,
+            Format::Yaml if self.files_written == 0 => print_yaml_format(&mut self.writer, &[])?,
+            Format::Xml => {
+                if self.files_written == 0 {
+                    print_xml_start(&mut self.writer)?;
+                }
+                print_xml_end(&mut self.writer)?;
+            }
+            _ => {}
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
 /// Helper to choose the correct printing function based on color/style preference.
 fn print_content_with_style(
     writer: &mut impl Write,
@@ -224,6 +1804,7 @@ fn print_content_with_style(
     with_line_numbers: bool,
     use_color: bool,
     start_line_number: usize,
+    theme: &Theme,
 ) -> Result<()> {
     if use_color {
         print_highlighted_content(
@@ -232,18 +1813,22 @@ fn print_content_with_style(
             extension,
             with_line_numbers,
             start_line_number,
+            theme,
         )
     } else {
         print_plain_content(writer, content, with_line_numbers, start_line_number)
     }
 }
 
-/// Given a set of byte-offset ranges, calculate the line number ranges including context,
-/// and merge any overlapping ranges.
-fn get_contextual_line_ranges(
-    hunks: &[Range],
+/// Given a set of byte-offset ranges, calculate the line number ranges including
+/// `before`/`after` lines of context, and merge any overlapping ranges, or ranges
+/// separated by at most `merge_gap` lines.
+pub(crate) fn get_contextual_line_ranges(
+    hunks: &[Hunk],
     lines: &[&str],
-    context_lines: usize,
+    before: usize,
+    after: usize,
+    merge_gap: usize,
 ) -> Vec<StdRange<usize>> {
     if hunks.is_empty() || lines.is_empty() {
         return vec![];
@@ -254,8 +1839,8 @@ fn get_contextual_line_ranges(
         let start_line = hunk.start_point.row;
         let end_line = hunk.end_point.row;
 
-        let context_start = start_line.saturating_sub(context_lines);
-        let context_end = (end_line + context_lines).min(lines.len() - 1);
+        let context_start = start_line.saturating_sub(before);
+        let context_end = (end_line + after).min(lines.len() - 1);
 
         if context_end >= context_start {
             line_ranges.push(context_start..context_end + 1);
@@ -263,12 +1848,12 @@ fn get_contextual_line_ranges(
     }
     line_ranges.sort_by_key(|r| r.start);
 
-    // Merge overlapping ranges
+    // Merge overlapping (or nearly adjacent) ranges
     let mut merged_ranges = Vec::new();
     let mut iter = line_ranges.into_iter();
     if let Some(mut current) = iter.next() {
         for next in iter {
-            if next.start <= current.end {
+            if next.start <= current.end + merge_gap {
                 current.end = current.end.max(next.end);
             } else {
                 merged_ranges.push(current);
@@ -287,19 +1872,12 @@ fn print_highlighted_content(
     extension: &str,
     with_line_numbers: bool,
     start_line_number: usize,
+    theme: &Theme,
 ) -> Result<()> {
-    let syntax = SYNTAX_SET
-        .find_syntax_by_extension(extension)
-        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-
-    let theme = &THEME_SET.themes["base16-ocean.dark"];
-    let mut highlighter = HighlightLines::new(syntax, theme);
-
-    for (i, line) in LinesWithEndings::from(content).enumerate() {
+    for (i, ranges) in highlight_lines(content, extension, theme)?.iter().enumerate() {
         if with_line_numbers {
             write!(writer, "{: >5} | ", start_line_number + i + 1)?;
         }
-        let ranges: Vec<(Style, &str)> = highlighter.highlight_line(line, &SYNTAX_SET)?;
         let escaped = as_24_bit_terminal_escaped(&ranges[..], false);
         write!(writer, "{escaped}")?;
     }
@@ -308,6 +1886,22 @@ fn print_highlighted_content(
     Ok(())
 }
 
+/// Splits `content` into lines, keeping their line endings, and highlights each one
+/// with the syntax for `extension`.
+pub(crate) fn highlight_lines<'a>(
+    content: &'a str,
+    extension: &str,
+    theme: &Theme,
+) -> Result<Vec<Vec<(Style, &'a str)>>> {
+    let syntax = SYNTAX_SET
+        .find_syntax_by_extension(extension)
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    LinesWithEndings::from(content)
+        .map(|line| Ok(highlighter.highlight_line(line, &SYNTAX_SET)?))
+        .collect()
+}
+
 /// Prints plain content, optionally with line numbers.
 fn print_plain_content(
     writer: &mut impl Write,
@@ -325,48 +1919,113 @@ fn print_plain_content(
     Ok(())
 }
 
-/// Prints content inside a Markdown code fence.
-fn print_markdown_fenced_content(
-    writer: &mut impl Write,
-    content: &str,
-    extension: &str,
-    with_line_numbers: bool,
-    start_line_number: usize,
-) -> Result<()> {
-    writeln!(writer, "```{extension}")?;
-    // print_plain_content handles line numbers correctly
-    print_plain_content(writer, content, with_line_numbers, start_line_number)?;
-    writeln!(writer, "```")?;
-    Ok(())
+/// Prints content inside a Markdown code fence.
+fn print_markdown_fenced_content(
+    writer: &mut impl Write,
+    content: &str,
+    extension: &str,
+    with_line_numbers: bool,
+    start_line_number: usize,
+) -> Result<()> {
+    writeln!(writer, "```{extension}")?;
+    // print_plain_content handles line numbers correctly
+    print_plain_content(writer, content, with_line_numbers, start_line_number)?;
+    writeln!(writer, "```")?;
+    Ok(())
+}
+
+/// The `ls -l`-style mode column, e.g. `-rw-r--r--`: the file type (`-`, `d` or
+/// `l`), then the permissions.
+fn format_mode(metadata: &fs::Metadata) -> String {
+    let file_type = metadata.file_type();
+    let kind = if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_dir() {
+        'd'
+    } else {
+        '-'
+    };
+    format!("{kind}{}", format_permissions(metadata))
+}
+
+#[cfg(unix)]
+fn format_permissions(metadata: &fs::Metadata) -> String {
+    let mode = metadata.permissions().mode();
+    let user_r = if mode & 0o400 != 0 { 'r' } else { '-' };
+    let user_w = if mode & 0o200 != 0 { 'w' } else { '-' };
+    let user_x = if mode & 0o100 != 0 { 'x' } else { '-' };
+    let group_r = if mode & 0o040 != 0 { 'r' } else { '-' };
+    let group_w = if mode & 0o020 != 0 { 'w' } else { '-' };
+    let group_x = if mode & 0o010 != 0 { 'x' } else { '-' };
+    let other_r = if mode & 0o004 != 0 { 'r' } else { '-' };
+    let other_w = if mode & 0o002 != 0 { 'w' } else { '-' };
+    let other_x = if mode & 0o001 != 0 { 'x' } else { '-' };
+    format!("{user_r}{user_w}{user_x}{group_r}{group_w}{group_x}{other_r}{other_w}{other_x}")
+}
+
+/// Windows has a read-only flag instead of permission bits, so show everyone able
+/// to read the file, and to write it unless it's read-only, as Git Bash's `ls` does.
+#[cfg(not(unix))]
+fn format_permissions(metadata: &fs::Metadata) -> String {
+    if metadata.permissions().readonly() {
+        "r--r--r--"
+    } else {
+        "rw-rw-rw-"
+    }
+    .to_string()
+}
+
+/// The `ls -li`-style ownership columns shown by `--format=find-long`.
+struct OwnerColumns {
+    inode: String,
+    nlink: String,
+    user: String,
+    group: String,
+}
+
+#[cfg(unix)]
+fn format_owner(metadata: &fs::Metadata) -> OwnerColumns {
+    use nix::unistd::{Gid, Group, Uid, User};
+
+    // Fall back to the numeric id when there is no matching user/group entry.
+    let user = User::from_uid(Uid::from_raw(metadata.uid()))
+        .ok()
+        .flatten()
+        .map_or_else(|| metadata.uid().to_string(), |u| u.name);
+    let group = Group::from_gid(Gid::from_raw(metadata.gid()))
+        .ok()
+        .flatten()
+        .map_or_else(|| metadata.gid().to_string(), |g| g.name);
+
+    OwnerColumns {
+        inode: metadata.ino().to_string(),
+        nlink: metadata.nlink().to_string(),
+        user,
+        group,
+    }
+}
+
+#[cfg(not(unix))]
+fn format_owner(_metadata: &fs::Metadata) -> OwnerColumns {
+    // Placeholders for platforms without inodes or uid/gid ownership.
+    OwnerColumns {
+        inode: "-".to_string(),
+        nlink: "-".to_string(),
+        user: "-".to_string(),
+        group: "-".to_string(),
+    }
 }
 
-fn format_mode(mode: u32) -> String {
-    #[cfg(unix)]
-    {
-        let user_r = if mode & 0o400 != 0 { 'r' } else { '-' };
-        let user_w = if mode & 0o200 != 0 { 'w' } else { '-' };
-        let user_x = if mode & 0o100 != 0 { 'x' } else { '-' };
-        let group_r = if mode & 0o040 != 0 { 'r' } else { '-' };
-        let group_w = if mode & 0o020 != 0 { 'w' } else { '-' };
-        let group_x = if mode & 0o010 != 0 { 'x' } else { '-' };
-        let other_r = if mode & 0o004 != 0 { 'r' } else { '-' };
-        let other_w = if mode & 0o002 != 0 { 'w' } else { '-' };
-        let other_x = if mode & 0o001 != 0 { 'x' } else { '-' };
-        format!("-{user_r}{user_w}{user_x}{group_r}{group_w}{group_x}{other_r}{other_w}{other_x}")
-    }
-    #[cfg(not(unix))]
-    {
-        // Basic fallback for non-Unix platforms
-        if mode & 0o200 != 0 {
-            "-rw-------"
-        } else {
-            "-r--------"
-        }
-        .to_string()
+/// Describes how many hunks a file matched with, e.g. `3 hunks` or `whole file`.
+fn hunk_count_label(hunk_count: usize) -> String {
+    match hunk_count {
+        0 => "whole file".to_string(),
+        1 => "1 hunk".to_string(),
+        n => format!("{n} hunks"),
     }
 }
 
-fn format_size(bytes: u64) -> String {
+pub(crate) fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -385,6 +2044,7 @@ fn format_size(bytes: u64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::evaluator::Symbol;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -400,7 +2060,11 @@ mod tests {
         let file = create_temp_file_with_content("a\nb");
         let paths = vec![(file.path().to_path_buf(), vec![])];
         let mut writer = Vec::new();
-        print_output(&mut writer, &paths, &Format::Cat, true, false, false, 0).unwrap();
+        let options = OutputOptions {
+            with_line_numbers: true,
+            ..OutputOptions::new(Format::Cat)
+        };
+        print_output(&mut writer, &paths, &options).unwrap();
         let output = String::from_utf8(writer).unwrap();
         assert_eq!(output, "    1 | a\n    2 | b\n");
     }
@@ -414,12 +2078,52 @@ mod tests {
             (file2.path().to_path_buf(), vec![]),
         ];
         let mut writer = Vec::new();
-        print_output(&mut writer, &paths, &Format::Paths, false, false, false, 0).unwrap();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Paths)).unwrap();
         let output = String::from_utf8(writer).unwrap();
         let expected = format!("{}\n{}\n", file1.path().display(), file2.path().display());
         assert_eq!(output, expected);
     }
 
+    #[test]
+    fn test_github_anchor() {
+        let mut seen = HashMap::new();
+        assert_eq!(github_anchor("File: ./src/main.rs", &mut seen), "file-srcmainrs");
+        assert_eq!(github_anchor("File: ./src/main_rs", &mut seen), "file-srcmain_rs");
+        // `main.rs` and `mainrs` collide once punctuation is dropped.
+        assert_eq!(github_anchor("File: ./src/mainrs", &mut seen), "file-srcmainrs-1");
+        assert_eq!(github_anchor("File: ./Ünïcode Dir/a-b.md", &mut seen), "file-ünïcode-dira-bmd");
+    }
+
+    #[test]
+    fn test_format_paths0() {
+        let paths = vec![
+            (PathBuf::from("./my file.rs"), vec![]),
+            (PathBuf::from("./line\nbreak.rs"), vec![]),
+        ];
+        let mut writer = Vec::new();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Paths0)).unwrap();
+        assert_eq!(writer, b"./my file.rs\0./line\nbreak.rs\0");
+    }
+
+    #[test]
+    fn test_format_count() {
+        let hunk = |row: usize| {
+            Hunk::from(Range {
+                start_byte: row * 2,
+                end_byte: row * 2 + 1,
+                start_point: tree_sitter::Point { row, column: 0 },
+                end_point: tree_sitter::Point { row, column: 1 },
+            })
+        };
+        let paths = vec![
+            (PathBuf::from("./a.rs"), vec![hunk(0), hunk(2)]),
+            (PathBuf::from("./b.rs"), vec![]),
+        ];
+        let mut writer = Vec::new();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Count)).unwrap();
+        assert_eq!(String::from_utf8(writer).unwrap(), "./a.rs: 2\n./b.rs: 1\ntotal: 3\n");
+    }
+
     #[test]
     fn test_format_markdown_with_fences() {
         let file = create_temp_file_with_content("line 1");
@@ -427,16 +2131,7 @@ mod tests {
         let mut writer = Vec::new();
 
         // Test with use_color = false to get markdown fences
-        print_output(
-            &mut writer,
-            &paths,
-            &Format::Markdown,
-            false,
-            false,
-            false,
-            0,
-        )
-        .unwrap();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Markdown)).unwrap();
 
         let output = String::from_utf8(writer).unwrap();
 
@@ -455,7 +2150,11 @@ mod tests {
 
         let paths = vec![(rs_path, vec![])];
         let mut writer = Vec::new();
-        print_output(&mut writer, &paths, &Format::Cat, false, false, true, 0).unwrap();
+        let options = OutputOptions {
+            use_color: true,
+            ..OutputOptions::new(Format::Cat)
+        };
+        print_output(&mut writer, &paths, &options).unwrap();
         let output = String::from_utf8(writer).unwrap();
 
         // Check for evidence of ANSI color, not the exact codes which can be brittle.
@@ -473,7 +2172,11 @@ mod tests {
         let mut writer = Vec::new();
 
         // Test with use_color = true, which should be ignored for the Markdown format.
-        print_output(&mut writer, &paths, &Format::Markdown, false, false, true, 0).unwrap();
+        let options = OutputOptions {
+            use_color: true,
+            ..OutputOptions::new(Format::Markdown)
+        };
+        print_output(&mut writer, &paths, &options).unwrap();
 
         let output = String::from_utf8(writer).unwrap();
 
@@ -493,9 +2196,691 @@ mod tests {
         let file = create_temp_file_with_content("hello");
         let paths = vec![(file.path().to_path_buf(), vec![])];
         let mut writer = Vec::new();
-        print_output(&mut writer, &paths, &Format::Find, false, false, false, 0).unwrap();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Find)).unwrap();
         let output = String::from_utf8(writer).unwrap();
         assert!(output.contains("B")); // Size
         assert!(output.contains(&file.path().display().to_string()));
     }
+
+    #[test]
+    fn test_format_mode_file_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        fs::write(&file, "hello").unwrap();
+        assert!(format_mode(&fs::metadata(dir.path()).unwrap()).starts_with('d'));
+        assert!(format_mode(&fs::metadata(&file).unwrap()).starts_with('-'));
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(&file, &link).unwrap();
+            assert!(format_mode(&fs::symlink_metadata(&link).unwrap()).starts_with('l'));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(windows), ignore = "checks the Windows permissions fallback")]
+    fn test_format_find_on_windows() {
+        let writable = create_temp_file_with_content("hello");
+        let read_only = create_temp_file_with_content("hello");
+        let mut permissions = fs::metadata(read_only.path()).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(read_only.path(), permissions).unwrap();
+
+        let paths = vec![
+            (writable.path().to_path_buf(), vec![]),
+            (read_only.path().to_path_buf(), vec![]),
+        ];
+        let mut writer = Vec::new();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Find)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].starts_with("-rw-rw-rw- "), "{output}");
+        assert!(lines[1].starts_with("-r--r--r-- "), "{output}");
+        // Size, then a modification time like `Jan 02 15:04`.
+        let columns: Vec<&str> = lines[0].split_whitespace().collect();
+        assert_eq!(columns[1], "5B", "{output}");
+        assert!(columns[4].contains(':'), "{output}");
+    }
+
+    #[test]
+    fn test_format_json_omits_extended_fields() {
+        let file = create_temp_file_with_content("fn main() {}");
+        let paths = vec![(file.path().to_path_buf(), vec![])];
+        let mut writer = Vec::new();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Json)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+        assert!(!output.contains("\"hunks\""));
+        assert!(!output.contains("\"language\""));
+    }
+
+    #[test]
+    fn test_format_json_reports_hunks_with_context() {
+        let file = create_temp_file_with_content("fn a() {}\n\nfn b() {\n    1\n}\n");
+        let hunk = Hunk::from(Range {
+            start_byte: 11,
+            end_byte: 27,
+            start_point: tree_sitter::Point { row: 2, column: 0 },
+            end_point: tree_sitter::Point { row: 4, column: 1 },
+        });
+        let paths = vec![(file.path().to_path_buf(), vec![hunk])];
+        let mut writer = Vec::new();
+        let options = OutputOptions {
+            before_context: 1,
+            ..OutputOptions::new(Format::Json)
+        };
+        print_output(&mut writer, &paths, &options).unwrap();
+
+        let outputs: Vec<FileOutput> = serde_json::from_slice(&writer).unwrap();
+        assert_eq!(outputs[0].content, None);
+        assert_eq!(outputs[0].language, None);
+        assert_eq!(
+            outputs[0].hunks,
+            Some(vec![SerializableHunk {
+                start_line: 2,
+                end_line: 5,
+                start_byte: 10,
+                end_byte: 27,
+                text: "\nfn b() {\n    1\n}".to_string(),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_format_json_extended_includes_hunks() {
+        let file = create_temp_file_with_content("fn a() {}\n\nfn b() {\n}\n");
+        let rs_path = file.path().with_extension("rs");
+        std::fs::rename(file.path(), &rs_path).unwrap();
+
+        let hunk = Hunk::from(Range {
+            start_byte: 11,
+            end_byte: 21,
+            start_point: tree_sitter::Point { row: 2, column: 0 },
+            end_point: tree_sitter::Point { row: 3, column: 1 },
+        });
+        let paths = vec![(rs_path.clone(), vec![hunk])];
+        let mut writer = Vec::new();
+        print_output(
+            &mut writer,
+            &paths,
+            &OutputOptions::new(Format::JsonExtended),
+        )
+        .unwrap();
+        std::fs::remove_file(&rs_path).unwrap();
+
+        let outputs: Vec<FileOutput> = serde_json::from_slice(&writer).unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].language.as_deref(), Some("Rust"));
+        let hunks = outputs[0].hunks.as_ref().unwrap();
+        assert_eq!(
+            hunks,
+            &vec![SerializableHunk {
+                start_line: 3,
+                end_line: 4,
+                start_byte: 11,
+                end_byte: 21,
+                text: "fn b() {\n}".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_merge_adjacent_hunks() {
+        let lines: Vec<&str> = vec!["line\n"; 20];
+        let hunk_on_line = |row: usize| {
+            Hunk::from(Range {
+                start_byte: row * 5,
+                end_byte: row * 5 + 4,
+                start_point: tree_sitter::Point { row, column: 0 },
+                end_point: tree_sitter::Point { row, column: 4 },
+            })
+        };
+        // Hunks on lines 5 and 12 (0-based rows 4 and 11).
+        let hunks = vec![hunk_on_line(4), hunk_on_line(11)];
+
+        assert_eq!(
+            get_contextual_line_ranges(&hunks, &lines, 0, 0, 0),
+            vec![4..5, 11..12]
+        );
+        assert_eq!(get_contextual_line_ranges(&hunks, &lines, 0, 0, 10), vec![4..12]);
+        // The gap is exactly 6 lines, so a gap of 5 is not enough.
+        assert_eq!(
+            get_contextual_line_ranges(&hunks, &lines, 0, 0, 5),
+            vec![4..5, 11..12]
+        );
+        assert_eq!(get_contextual_line_ranges(&hunks, &lines, 0, 0, 6), vec![4..12]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_format_find_long() {
+        let file = create_temp_file_with_content("hello");
+        let paths = vec![(file.path().to_path_buf(), vec![])];
+        let mut writer = Vec::new();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::FindLong)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+
+        let columns: Vec<&str> = output.split_whitespace().collect();
+        let inode: u64 = columns[0].parse().unwrap();
+        let nlink: u64 = columns[2].parse().unwrap();
+        assert!(inode > 0, "inode column should be non-zero: {output}");
+        assert!(nlink > 0, "link count column should be non-zero: {output}");
+        assert!(columns[1].starts_with('-'), "permissions column: {output}");
+        assert!(output.contains(&file.path().display().to_string()));
+    }
+
+    #[test]
+    fn test_asymmetric_context() {
+        let lines: Vec<&str> = vec!["line\n"; 20];
+        let hunk = Hunk::from(Range {
+            start_byte: 50,
+            end_byte: 54,
+            start_point: tree_sitter::Point { row: 10, column: 0 },
+            end_point: tree_sitter::Point { row: 10, column: 4 },
+        });
+        let hunks = vec![hunk];
+
+        assert_eq!(get_contextual_line_ranges(&hunks, &lines, 0, 0, 0), vec![10..11]);
+        assert_eq!(get_contextual_line_ranges(&hunks, &lines, 0, 3, 0), vec![10..14]);
+        assert_eq!(get_contextual_line_ranges(&hunks, &lines, 2, 0, 0), vec![8..11]);
+        // Context is clamped to the file boundaries.
+        assert_eq!(get_contextual_line_ranges(&hunks, &lines, 15, 15, 0), vec![0..20]);
+
+        // Rows 4 and 10: the first hunk's after-context reaches the second hunk's
+        // before-context, so they merge, though neither side alone would.
+        let hunks = vec![
+            Hunk::from(Range {
+                start_byte: 20,
+                end_byte: 24,
+                start_point: tree_sitter::Point { row: 4, column: 0 },
+                end_point: tree_sitter::Point { row: 4, column: 4 },
+            }),
+            hunk,
+        ];
+        assert_eq!(get_contextual_line_ranges(&hunks, &lines, 2, 3, 0), vec![2..14]);
+        assert_eq!(
+            get_contextual_line_ranges(&hunks, &lines, 0, 4, 0),
+            vec![4..9, 10..15]
+        );
+        assert_eq!(get_contextual_line_ranges(&hunks, &lines, 0, 5, 0), vec![4..16]);
+    }
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("src/main.rs"), "src/main.rs");
+        assert_eq!(csv_escape("a,b.rs"), "\"a,b.rs\"");
+        assert_eq!(csv_escape("say \"hi\".rs"), "\"say \"\"hi\"\".rs\"");
+    }
+
+    #[test]
+    fn test_format_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("with,comma.txt");
+        std::fs::write(&file_path, "a\nb\nc\n").unwrap();
+        let hunk = |row: usize| {
+            Hunk::from(Range {
+                start_byte: row * 2,
+                end_byte: row * 2 + 1,
+                start_point: tree_sitter::Point { row, column: 0 },
+                end_point: tree_sitter::Point { row, column: 1 },
+            })
+        };
+        let paths = vec![
+            (file_path.clone(), vec![hunk(0), hunk(2)]),
+            (file_path.clone(), vec![]),
+        ];
+        let mut writer = Vec::new();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Csv)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+        let rows: Vec<&str> = output.lines().collect();
+
+        assert_eq!(rows[0], "path,start_line,end_line,size,modified,match_count");
+        assert_eq!(rows.len(), 4);
+        let quoted_path = format!("\"{}\"", file_path.display());
+        assert!(rows[1].starts_with(&format!("{quoted_path},1,1,6,")));
+        assert!(rows[1].ends_with(",2"));
+        assert!(rows[2].starts_with(&format!("{quoted_path},3,3,6,")));
+        assert!(rows[3].starts_with(&format!("{quoted_path},,,6,")));
+        assert!(rows[3].ends_with(",0"));
+    }
+
+    #[test]
+    fn test_format_ndjson() {
+        let file1 = create_temp_file_with_content("a");
+        let file2 = create_temp_file_with_content("b\nc");
+        let paths = vec![
+            (file1.path().to_path_buf(), vec![]),
+            (file2.path().to_path_buf(), vec![]),
+        ];
+        let mut writer = Vec::new();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Ndjson)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first: FileOutput = serde_json::from_str(lines[0]).unwrap();
+        let second: FileOutput = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(first.content.as_deref(), Some("a"));
+        assert_eq!(second.content.as_deref(), Some("b\nc"));
+        assert_eq!(second.hunks, Some(vec![]));
+    }
+
+    #[test]
+    fn test_rich_format_draws_a_box_per_file() {
+        let mut file = tempfile::Builder::new().suffix(".rs").tempfile().unwrap();
+        file.write_all(b"fn main() {\n\tprintln!(\"hi\");\n}\n").unwrap();
+        let paths = vec![(file.path().to_path_buf(), vec![])];
+        let mut writer = Vec::new();
+        print_output(&mut writer, &paths, &OutputOptions::new(Format::Rich)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], format!("┌{}┐", "─".repeat(DEFAULT_RICH_WIDTH - 2)));
+        assert!(lines[1].contains(&file.path().display().to_string()));
+        assert!(lines[2].contains("Rust"), "{output}");
+        assert!(lines[3].starts_with('├'));
+        assert_eq!(lines[5], format!("│     println!(\"hi\");{} │", " ".repeat(57)));
+        assert!(lines[7].starts_with('└'));
+        for line in &lines {
+            assert_eq!(line.chars().count(), DEFAULT_RICH_WIDTH, "{line:?}");
+        }
+    }
+
+    #[test]
+    fn test_rich_format_cuts_long_lines_to_the_width() {
+        let file = create_temp_file_with_content("short\nthis line is far too long for the box\n");
+        let paths = vec![(file.path().to_path_buf(), vec![])];
+        let options = OutputOptions {
+            with_line_numbers: true,
+            rich_width: 30,
+            ..OutputOptions::new(Format::Rich)
+        };
+        let mut writer = Vec::new();
+        print_output(&mut writer, &paths, &options).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+
+        assert!(output.contains("│     1 | short              │\n"), "{output}");
+        assert!(output.contains("│     2 | this line is far … │\n"), "{output}");
+        assert!(output.lines().all(|line| line.chars().count() == 30));
+    }
+
+    #[test]
+    fn test_streaming_printer_matches_print_output() {
+        let file1 = create_temp_file_with_content("a\n");
+        let file2 = create_temp_file_with_content("b\n");
+        let files = vec![
+            (file1.path().to_path_buf(), vec![]),
+            (file2.path().to_path_buf(), vec![]),
+        ];
+
+        for format in [
+            Format::Hunks,
+            Format::Markdown,
+            Format::Rich,
+            Format::Json,
+            Format::Csv,
+            Format::Xml,
+            Format::Yaml,
+            Format::Html,
+            Format::Tree,
+            Format::Stats,
+            Format::Tokens,
+        ] {
+            let options = OutputOptions::new(format.clone());
+            let mut expected = Vec::new();
+            print_output(&mut expected, &files, &options).unwrap();
+
+            let mut streamed = Vec::new();
+            let mut printer = StreamingPrinter::new(&mut streamed, options);
+            for file in files.clone() {
+                printer.print(file).unwrap();
+            }
+            printer.finish().unwrap();
+
+            assert_eq!(
+                String::from_utf8(streamed).unwrap(),
+                String::from_utf8(expected).unwrap(),
+                "streamed output differs for {format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_escape_markup() {
+        assert_eq!(
+            escape_markup(r#"a&b <"c"> 'd'"#),
+            "a&amp;b &lt;&quot;c&quot;&gt; &apos;d&apos;"
+        );
+        assert_eq!(escape_markup("tab\there"), "tab&#9;here");
+    }
+
+    #[test]
+    fn test_format_xml_round_trips() {
+        let content = "let end = \"]]>\";\nlet café = \"日本語 ✓\";\n<tag attr='x'> & more\n";
+        let file = create_temp_file_with_content(content);
+        let hunk = Hunk::from(Range {
+            start_byte: 0,
+            end_byte: 16,
+            start_point: tree_sitter::Point { row: 0, column: 0 },
+            end_point: tree_sitter::Point { row: 0, column: 16 },
+        });
+        let files = vec![
+            (file.path().to_path_buf(), vec![hunk]),
+            (file.path().to_path_buf(), vec![]),
+        ];
+
+        let mut writer = Vec::new();
+        print_output(&mut writer, &files, &OutputOptions::new(Format::Xml)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+
+        let doc = roxmltree::Document::parse(&output).unwrap();
+        let root = doc.root_element();
+        assert_eq!(root.tag_name().name(), "rdump");
+        let file_nodes: Vec<_> = root.children().filter(|n| n.has_tag_name("file")).collect();
+        assert_eq!(file_nodes.len(), 2);
+        assert_eq!(
+            file_nodes[0].attribute("path"),
+            Some(file.path().to_string_lossy().as_ref())
+        );
+
+        let hunk_text = |file: &roxmltree::Node| -> (String, String, String) {
+            let hunk = file.children().find(|n| n.has_tag_name("hunk")).unwrap();
+            // CDATA split across sections comes back as several text nodes.
+            let text: String = hunk.children().filter_map(|n| n.text()).collect();
+            (
+                hunk.attribute("start-line").unwrap().to_string(),
+                hunk.attribute("end-line").unwrap().to_string(),
+                text,
+            )
+        };
+        assert_eq!(
+            hunk_text(&file_nodes[0]),
+            (
+                "1".to_string(),
+                "1".to_string(),
+                "let end = \"]]>\";\n".to_string()
+            )
+        );
+        assert_eq!(
+            hunk_text(&file_nodes[1]),
+            ("1".to_string(), "3".to_string(), content.to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_yaml_uses_block_scalars() {
+        let file = create_temp_file_with_content("fn a() {}\n\nfn b() {}\n");
+        let hunk = Hunk::from(Range {
+            start_byte: 11,
+            end_byte: 20,
+            start_point: tree_sitter::Point { row: 2, column: 0 },
+            end_point: tree_sitter::Point { row: 2, column: 9 },
+        });
+        let files = vec![(file.path().to_path_buf(), vec![hunk])];
+
+        let mut writer = Vec::new();
+        print_output(&mut writer, &files, &OutputOptions::new(Format::Yaml)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+
+        // Multi-line content stays readable instead of becoming an escaped string.
+        assert!(output.contains("  content: |\n    fn a() {}\n\n    fn b() {}\n"));
+        let parsed: Vec<FileOutput> = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(parsed[0].content.as_deref(), Some("fn a() {}\n\nfn b() {}\n"));
+        let hunks = parsed[0].hunks.as_ref().unwrap();
+        assert_eq!(hunks[0].start_line, 3);
+        assert_eq!(hunks[0].text, "fn b() {}");
+
+        let mut empty = Vec::new();
+        print_output(&mut empty, &[], &OutputOptions::new(Format::Yaml)).unwrap();
+        let parsed: Vec<FileOutput> = serde_yaml::from_slice(&empty).unwrap();
+        assert!(parsed.is_empty());
+    }
+
+    #[test]
+    fn test_format_html() {
+        let file = create_temp_file_with_content("fn a() {}\nfn main() {\n    let x = 1 < 2;\n}\n");
+        let hunk = Hunk::from(Range {
+            start_byte: 10,
+            end_byte: 20,
+            start_point: tree_sitter::Point { row: 1, column: 0 },
+            end_point: tree_sitter::Point { row: 3, column: 1 },
+        });
+        let files = vec![(file.path().to_path_buf(), vec![hunk])];
+
+        let mut writer = Vec::new();
+        print_output(&mut writer, &files, &OutputOptions::new(Format::Html)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+
+        assert!(output.starts_with("<!DOCTYPE html>"));
+        // The index links to the file's collapsible section.
+        assert!(output.contains(r##"<a href="#file-1">"##));
+        assert!(output.contains(r#"<details id="file-1" open>"#));
+        // Line numbers are anchors, and only the hunk's lines are shown.
+        assert!(output.contains(r##"<a class="line-number" href="#file-1-L2">"##));
+        assert!(output.contains(r#"id="file-1-L4""#));
+        assert!(!output.contains(r#"id="file-1-L1""#));
+        // Code is escaped.
+        assert!(output.contains("&lt;"));
+        assert!(!output.contains("1 < 2"));
+        assert!(output.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_find_theme() {
+        assert!(find_theme(DEFAULT_THEME).is_ok());
+        let err = find_theme("no-such-theme").unwrap_err().to_string();
+        assert!(err.contains("Unknown theme 'no-such-theme'"));
+        assert!(err.contains("InspiredGitHub"));
+    }
+
+    #[test]
+    fn test_format_grep() {
+        let file = create_temp_file_with_content("use std::fs;\nfn main() {\n    let x = 1;\n}\n");
+        let hunk = Hunk::from(Range {
+            start_byte: 16,
+            end_byte: 20,
+            start_point: tree_sitter::Point { row: 1, column: 3 },
+            end_point: tree_sitter::Point { row: 1, column: 7 },
+        });
+        let whole_file = create_temp_file_with_content("plain");
+        let files = vec![
+            (file.path().to_path_buf(), vec![hunk]),
+            (whole_file.path().to_path_buf(), vec![]),
+        ];
+
+        let mut writer = Vec::new();
+        print_output(&mut writer, &files, &OutputOptions::new(Format::Grep)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+        let expected = format!(
+            "{}:2:4:fn main() {{\n{}\n",
+            file.path().display(),
+            whole_file.path().display()
+        );
+        assert_eq!(output, expected);
+
+        let mut colored = Vec::new();
+        let options = OutputOptions {
+            use_color: true,
+            ..OutputOptions::new(Format::Grep)
+        };
+        print_output(&mut colored, &files, &options).unwrap();
+        let colored = String::from_utf8(colored).unwrap();
+        assert!(colored.contains(":4:fn \x1b[1;31mmain\x1b[0m() {"));
+    }
+
+    #[test]
+    fn test_format_quickfix() {
+        let file = create_temp_file_with_content("fn main() {\n    helper();\n}\n");
+        let call = Hunk::from(Range {
+            start_byte: 16,
+            end_byte: 24,
+            start_point: tree_sitter::Point { row: 1, column: 4 },
+            end_point: tree_sitter::Point { row: 1, column: 12 },
+        });
+        let function = Hunk::from(Range {
+            start_byte: 0,
+            end_byte: 27,
+            start_point: tree_sitter::Point { row: 0, column: 0 },
+            end_point: tree_sitter::Point { row: 2, column: 1 },
+        });
+        let whole_file = create_temp_file_with_content("plain");
+        let files = vec![
+            // Two hunks on neighbouring lines still give two entries.
+            (file.path().to_path_buf(), vec![call, function]),
+            (whole_file.path().to_path_buf(), vec![]),
+        ];
+
+        let mut writer = Vec::new();
+        print_quickfix_format(&mut writer, &files, "call:helper |\n func:main").unwrap();
+        let output = String::from_utf8(writer).unwrap();
+        let expected = format!(
+            "{0}:1:1: fn main() {{\n{0}:2:5: helper();\n{1}:1:1: call:helper | func:main\n",
+            file.path().display(),
+            whole_file.path().display()
+        );
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_format_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("a/b/deep.rs"), "fn x() {}\n").unwrap();
+        fs::write(dir.path().join("a/top.rs"), "").unwrap();
+        let hunk = Hunk::from(Range {
+            start_byte: 0,
+            end_byte: 9,
+            start_point: tree_sitter::Point { row: 0, column: 0 },
+            end_point: tree_sitter::Point { row: 0, column: 9 },
+        });
+        let files = vec![
+            (dir.path().join("a/b/deep.rs"), vec![hunk]),
+            (dir.path().join("a/top.rs"), vec![]),
+        ];
+
+        let mut writer = Vec::new();
+        print_output(&mut writer, &files, &OutputOptions::new(Format::Tree)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+
+        assert!(output.starts_with(".\n"));
+        assert!(output.contains("    ├── b\n"));
+        assert!(output.contains("    │   └── deep.rs (10B, 1 hunk)\n"));
+        assert!(output.contains("    └── top.rs (0B, whole file)\n"));
+        assert!(output.ends_with(" files\n"));
+    }
+
+    #[test]
+    fn test_format_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/a.rs"), "0123456789").unwrap();
+        fs::write(dir.path().join("b.txt"), "01234").unwrap();
+        let hunk = Hunk::from(Range {
+            start_byte: 0,
+            end_byte: 1,
+            start_point: tree_sitter::Point { row: 0, column: 0 },
+            end_point: tree_sitter::Point { row: 0, column: 1 },
+        });
+        let files = vec![
+            (dir.path().join("src/a.rs"), vec![hunk, hunk]),
+            (dir.path().join("b.txt"), vec![]),
+        ];
+
+        let mut writer = Vec::new();
+        print_output(&mut writer, &files, &OutputOptions::new(Format::Stats)).unwrap();
+        let output = String::from_utf8(writer).unwrap();
+
+        assert!(output.starts_with("Files:       2\nTotal size:  15B\nTotal hunks: 2\n"));
+        assert!(output.contains("By extension:\n  rs       1 file        10B\n  txt      1 file         5B\n"));
+        // The largest file comes first.
+        let largest = output.split("Largest files:\n").nth(1).unwrap();
+        assert!(largest.starts_with("       10B  "));
+        assert!(largest.lines().next().unwrap().ends_with("a.rs"));
+    }
+
+    #[test]
+    fn test_sarif_uri() {
+        assert_eq!(sarif_uri(std::path::Path::new("./src/main.rs")), "src/main.rs");
+        assert_eq!(
+            sarif_uri(std::path::Path::new("src/my file#1.rs")),
+            "src/my%20file%231.rs"
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            sarif_uri(std::path::Path::new("/tmp/a.rs")),
+            "file:///tmp/a.rs"
+        );
+    }
+
+    #[test]
+    fn test_format_sarif() {
+        let file = create_temp_file_with_content("import os\neval(x)\n");
+        let hunk = Hunk::from(Range {
+            start_byte: 10,
+            end_byte: 14,
+            start_point: tree_sitter::Point { row: 1, column: 0 },
+            end_point: tree_sitter::Point { row: 1, column: 4 },
+        });
+        let files = vec![
+            (file.path().to_path_buf(), vec![hunk]),
+            (file.path().to_path_buf(), vec![]),
+        ];
+        let mut writer = Vec::new();
+        print_sarif_format(&mut writer, &files, "call:eval & ext:py").unwrap();
+        let sarif: serde_json::Value = serde_json::from_slice(&writer).unwrap();
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "rdump");
+        assert_eq!(run["tool"]["driver"]["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "call:eval & ext:py");
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "call:eval & ext:py");
+        let region = &results[0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 2);
+        assert_eq!(region["endLine"], 2);
+        assert_eq!(region["startColumn"], 1);
+        // Whole-file matches have no region.
+        assert!(results[1]["locations"][0]["physicalLocation"]["region"].is_null());
+    }
+
+    #[test]
+    fn test_format_symbols() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "struct User;\n\nimpl User {\n    fn new() {}\n}\n").unwrap();
+        let range = |start_byte, end_byte, row| Range {
+            start_byte,
+            end_byte,
+            start_point: tree_sitter::Point { row, column: 0 },
+            end_point: tree_sitter::Point { row, column: 0 },
+        };
+        let definition = |range, name, kind| Hunk {
+            range,
+            symbol: Some(Symbol { name, kind }),
+        };
+        // The `User` name of the struct, the whole impl block, `new`'s name, and a
+        // match that isn't a definition.
+        let hunks = vec![
+            definition(range(7, 11, 0), range(7, 11, 0), "struct"),
+            definition(range(14, 43, 2), range(19, 23, 2), "impl"),
+            definition(range(33, 36, 3), range(33, 36, 3), "function"),
+            Hunk::from(range(30, 32, 3)),
+        ];
+
+        let mut writer = Vec::new();
+        print_output(
+            &mut writer,
+            &[(path.clone(), hunks)],
+            &OutputOptions::new(Format::Symbols),
+        )
+        .unwrap();
+        let output = String::from_utf8(writer).unwrap();
+        let path = path.display();
+        assert_eq!(
+            output,
+            format!("User\t{path}\t1\tstruct\nUser\t{path}\t3\timpl\nnew\t{path}\t4\tfunction\n")
+        );
+    }
 }
@@ -1,17 +1,114 @@
-// Declare all our modules
+//! rdump is a command-line tool for finding and dumping code, driven by a small
+//! query language (RQL). The `rdump` binary is a thin wrapper around this crate.
+//!
+//! # Adding predicates
+//!
+//! Every predicate key in a query is dispatched to a [`PredicateEvaluator`] looked up
+//! in a [`PredicateRegistry`]. Start from
+//! [`create_predicate_registry`](predicates::create_predicate_registry) and register
+//! your own evaluator under [`PredicateKey::Other`] to add a key without touching
+//! the parser:
+//!
+//! ```
+//! use anyhow::Result;
+//! use rdump::evaluator::{Evaluator, FileContext, MatchResult};
+//! use rdump::parser::{parse_query, PredicateKey};
+//! use rdump::predicates::{create_predicate_registry, PredicateEvaluator};
+//!
+//! /// `semver:1` matches files whose name contains a `1.x.y` version.
+//! struct SemverEvaluator;
+//!
+//! impl PredicateEvaluator for SemverEvaluator {
+//!     fn evaluate(
+//!         &self,
+//!         context: &mut FileContext,
+//!         _key: &PredicateKey,
+//!         value: &str,
+//!     ) -> Result<MatchResult> {
+//!         let name = context.path.file_name().unwrap_or_default().to_string_lossy();
+//!         Ok(MatchResult::Boolean(name.contains(&format!("{value}."))))
+//!     }
+//! }
+//!
+//! let mut registry = create_predicate_registry();
+//! registry.insert(PredicateKey::Other("semver".to_string()), SemverEvaluator);
+//! let evaluator = Evaluator::new(parse_query("semver:1 & ext:tgz")?, registry);
+//! # let _ = evaluator;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! # Estimating query cost
+//!
+//! [`Evaluator::estimate_cost`](evaluator::Evaluator::estimate_cost) tells whether a
+//! query can be answered from file metadata alone, or needs to read or parse files,
+//! before running it:
+//!
+//! ```
+//! use rdump::evaluator::Evaluator;
+//! use rdump::parser::parse_query;
+//! use rdump::predicates::create_predicate_registry;
+//!
+//! let evaluator = Evaluator::new(parse_query("ext:rs & name:main.rs")?, create_predicate_registry());
+//! let cost = evaluator.estimate_cost();
+//! assert!(!cost.requires_content_read && !cost.requires_tree_sitter);
+//! assert_eq!(cost.estimated_predicates, 2);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! [`PredicateEvaluator`]: predicates::PredicateEvaluator
+//! [`PredicateRegistry`]: predicates::PredicateRegistry
+//! [`PredicateKey::Other`]: parser::PredicateKey::Other
+
+// Declare all our modules. Those that touch the file system, spawn processes or
+// threads, or drive the terminal only make up the command line tool, so the wasm
+// build leaves them out.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod archive;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod commands;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod config;
+pub mod diagnostics;
 pub mod evaluator;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod formatter;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod links;
+pub mod metrics;
 pub mod parser;
 pub mod predicates;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod progress;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod roots;
+pub mod template;
+pub mod tokens;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tui;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod types;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::builder::BoolishValueParser;
+#[cfg(not(target_arch = "wasm32"))]
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::process::ExitCode;
+
+use predicates::code_aware::profiles::HunkGranularity;
 
 // Bring our command functions into scope
-use commands::{lang::run_lang, preset::run_preset, search::run_search};
+#[cfg(not(target_arch = "wasm32"))]
+use commands::{
+    completions::run_completions, config::run_config, explain::run_explain, lang::run_lang,
+    preset::run_preset, search::run_search, stats::run_stats, types::run_type,
+};
 
 // These structs and enums define the public API of our CLI.
 // They need to be public so the `commands` modules can use them.
@@ -29,16 +126,27 @@ pub struct Cli {
 pub enum Commands {
     /// Search for files using a query (default command).
     #[command(visible_alias = "s")]
-    Search(SearchArgs),
+    Search(Box<SearchArgs>),
     /// List supported languages and their available predicates.
     #[command(visible_alias = "l")]
     Lang(LangArgs),
     /// Manage saved presets.
     #[command(visible_alias = "p")]
     Preset(PresetArgs),
+    /// List the file types usable with `search --type`.
+    Type(TypeArgs),
+    /// Count files, definitions, functions and imports per language.
+    Stats(StatsArgs),
+    /// Show how a query is parsed and evaluated, and optionally trace it against a file.
+    Explain(ExplainArgs),
+    /// Print a shell completion script, e.g. `rdump completions bash > /etc/bash_completion.d/rdump`.
+    Completions(CompletionsArgs),
+    /// Manage the config files.
+    Config(ConfigArgs),
 }
 
-#[derive(Debug, Clone, ValueEnum, Default, PartialEq)]
+#[derive(Debug, Clone, ValueEnum, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ColorChoice {
     #[default]
     Auto,
@@ -46,30 +154,62 @@ pub enum ColorChoice {
     Never,
 }
 
+/// The order matching files are output in, for `--sort`.
+#[derive(Debug, Clone, Copy, ValueEnum, Default, PartialEq)]
+pub enum SortKey {
+    /// By path
+    #[default]
+    Path,
+    /// By size, smallest first
+    Size,
+    /// By modification time, oldest first
+    Modified,
+    /// By number of matching hunks, fewest first
+    Matches,
+    /// By extension, then by path
+    Ext,
+}
+
 #[derive(Parser, Debug, Default)]
 pub struct SearchArgs {
+    /// The flags given on the command line or through an environment variable, by
+    /// id. `[defaults]` in the config only fills in the others.
+    #[arg(skip)]
+    pub explicit_flags: BTreeSet<String>,
     /// The query string to search for, using rdump Query Language (RQL).
     ///
     /// RQL supports logical operators (&, |, !), parentheses, and key:value predicates.
     /// Values with spaces must be quoted (e.g., contains:'fn main').
     ///
     /// METADATA PREDICATES:
-    ///   ext:<str>          - File extension (e.g., "rs", "toml")
+    ///   ext:<str>          - File extension (e.g., "rs", "toml"), or a list ("rs,py,go")
     ///   name:<glob>        - File name glob pattern (e.g., "test_*.rs")
+    ///   iname:<glob>       - Alias of name: (name matching is already case-insensitive)
     ///   path:<str>         - Substring in the full file path
-    ///   in:<path>          - Directory path to search within
-    ///   size:[>|<]<num>[kb|mb] - File size (e.g., ">10kb")
+    ///   ipath:<str>        - Case-insensitive variant of path: (e.g., "src/Main.rs")
+    ///   in:<path>          - Directory path to search within, or a list ("src,tests")
+    ///   size:[>|<|=]<num>[kb|mb|gb|tb|pb] - File size (e.g., ">10kb", "<0.5pb");
+    ///                        no operator means an exact byte count (e.g., "4096")
     ///   modified:[>|<]<num>[h|d|w] - Modified time (e.g., "<2d")
+    ///                      Also accepts today, yesterday, last-week, last-month, last-year
+    ///   since:<time>       - Shortcut for modified:> (e.g., "2024-01-01", "2d", "last-week")
+    ///   before:<time>      - Shortcut for modified:< (e.g., "2024-01-01")
     ///
     /// CONTENT PREDICATES:
     ///   contains:<str>     - Literal string a file contains
     ///   matches:<regex>    - Regular expression a file's content matches
+    ///   duplicate:<bool>   - File content is identical to another matching file ("true")
     ///
     #[doc = "CODE-AWARE PREDICATES for supported languages:"]
     ///   def:<str>          - A generic definition (class, struct, enum, etc.)
     ///   func:<str>         - A function or method
+    ///   signature:<str>    - Text in a function's signature (e.g., "fn new(config: &Config)")
+    ///   return_type:<str>  - Text in a function's return type (e.g., "Result", "Promise")
     ///   import:<str>       - An import or use statement
     ///   call:<str>         - A function or method call site
+    ///   method_call:<str>  - A method call only (`self.connect()`; Rust)
+    ///   free_call:<str>    - A free function call only (`connect()`, `db::connect()`; Rust)
+    ///   env:<str>          - An environment variable read (e.g., "DATABASE_URL")
     ///
     /// GRANULAR DEFINITIONS:
     ///   class:<str>        - A class definition
@@ -78,12 +218,31 @@ pub struct SearchArgs {
     ///   interface:<str>    - An interface definition
     ///   trait:<str>        - A trait definition
     ///   type:<str>         - A type alias
-    ///   impl:<str>         - An implementation block (e.g., `impl User`)
+    ///   impl:<str>         - Implementation blocks for a type (`impl User`, `impl Display for User`)
     ///   macro:<str>        - A macro definition
+    ///   generic:<str>      - Text in generic parameters or a where clause (e.g., "Serialize")
+    ///   module:<str>       - A module declaration (Rust `mod`, Go `package`, Python `__init__.py` or `__all__`)
+    ///   decorator:<str>    - A Python definition with the named decorator (e.g., "property")
     ///
     /// SYNTACTIC CONTENT:
     ///   comment:<str>      - Text inside a comment (e.g., "TODO", "FIXME")
+    ///   tag:<str>          - A comment starting with a marker (e.g., "FIXME"), or "." for any
+    ///                        of TODO, FIXME, HACK, XXX, BUG, NOTE and configured `tags`
     ///   str:<str>          - Text inside a string literal
+    ///   url:<str>          - A URL inside a string literal (e.g., "internal.corp", or "." for any)
+    ///
+    /// SAFETY (Rust):
+    ///   unsafe:<str>       - An unsafe block, fn, impl or trait mentioning the identifier
+    ///                        (e.g., "transmute"), or "*" for any unsafe region
+    ///
+    /// METRICS:
+    ///   complexity:[>|<|=]<num> - Functions whose approximate cyclomatic complexity
+    ///                        satisfies the comparison (e.g., ">15"; Rust, Python, TypeScript)
+    ///   funclines:[>|<|=]<num> - Functions spanning more/fewer lines (e.g., ">100"); with
+    ///                        `&`, any function in the file counts, not just the one named
+    ///
+    /// WEB:
+    ///   route:<str>        - An HTTP route handler whose path contains the value (e.g., "/users")
     ///
     #[doc = "REACT-SPECIFIC PREDICATES (.jsx, .tsx):"]
     ///   component:<str>    - A React component definition
@@ -91,28 +250,216 @@ pub struct SearchArgs {
     ///   hook:<str>         - A React hook call (e.g., `useState`, `useEffect`)
     ///   customhook:<str>   - A custom hook definition (e.g., `useAuth`)
     ///   prop:<str>         - A prop being passed to a JSX element
-    #[arg(verbatim_doc_comment, name = "QUERY")]
+    #[arg(verbatim_doc_comment, name = "QUERY", env = "RDUMP_QUERY")]
     pub query: Option<String>,
     #[arg(long, short)]
     pub preset: Vec<String>,
-    #[arg(short, long, default_value = ".")]
-    pub root: PathBuf,
+    /// Only search files whose name matches this glob, i.e. add `& name:GLOB` to the
+    /// query, which can then be left out. Can be repeated; a file must match all of them
+    /// (for alternatives, query `ext:rs,toml` instead)
+    #[arg(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+    /// Leave out files matching this RQL query, i.e. search `(QUERY) & !(EXCLUDE)`, or
+    /// whose name matches this glob if it has no `key:`, e.g. '*.test.ts'. Can be
+    /// repeated; a file matching any of them is left out
+    #[arg(short = 'x', long, value_name = "RQL|GLOB")]
+    pub exclude: Vec<String>,
+    /// Show the files the query does NOT match instead. Only file-listing formats
+    /// (paths, find, json, ...) are allowed. --include, --type and --exclude still
+    /// limit which files are looked at
+    #[arg(short = 'v', long, visible_alias = "invert")]
+    pub invert_match: bool,
+    /// Show every matching file in full, even when the query only matched parts of
+    /// it, e.g. `func:route` to pick the files that define a route
+    #[arg(long)]
+    pub whole_file: bool,
+    /// Only search files of this type, e.g. `rust` for `ext:rs`, i.e. add
+    /// `& ext:EXTS` to the query, which can then be left out. Can be repeated; a file
+    /// of any of the types is searched. See `rdump type list`
+    #[arg(short = 't', long = "type", value_name = "TYPE")]
+    pub file_type: Vec<String>,
+    /// The directory to search. Can be repeated to search several directories at once
+    #[arg(short, long, value_name = "DIR", default_value = ".", env = "RDUMP_ROOT")]
+    pub root: Vec<PathBuf>,
+    /// Search the whole project instead of the current directory: the nearest parent
+    /// directory with a Cargo.toml, package.json, pyproject.toml, go.mod or .git.
+    /// Ignored when --root is given. `--workspace=false` overrides `[defaults]`
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", default_value = "false", action = ArgAction::Set, value_parser = BoolishValueParser::new(), value_name = "BOOL")]
+    pub workspace: bool,
+    /// Print paths as absolute paths, rather than under the --root they were found in
+    #[arg(long)]
+    pub absolute_paths: bool,
+    /// Search only the files listed in PATH, one per line, instead of walking --root.
+    /// `-` reads the list from stdin, e.g. `git diff --name-only | rdump search ...
+    /// --files-from -`. Relative paths are resolved against --root; ignore rules don't apply
+    #[arg(long, value_name = "PATH")]
+    pub files_from: Option<PathBuf>,
+    /// Like --files-from, but the paths are separated by NUL characters, as from
+    /// `find -print0`
+    #[arg(long, value_name = "PATH", conflicts_with = "files_from")]
+    pub files_from0: Option<PathBuf>,
     #[arg(short, long)]
     pub output: Option<PathBuf>,
-    #[arg(short, long)]
+    /// Write each matching file's output to its own file under this directory,
+    /// e.g. `src/main.rs` -> `<DIR>/src/main.rs.md`
+    #[arg(long, value_name = "DIR", conflicts_with = "output")]
+    pub output_dir: Option<PathBuf>,
+    /// Bundle the matching files, plus an INDEX.md of the query and hunks, into a
+    /// .zip or .tar.gz archive. Symlinks are stored as links, not followed
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["output", "output_dir"])]
+    pub archive: Option<PathBuf>,
+    /// Copy the formatted output to the system clipboard instead of printing it
+    #[arg(long, conflicts_with_all = ["output", "output_dir", "archive"])]
+    pub copy: bool,
+    /// Browse the matches in a terminal UI: the files on the left, a highlighted
+    /// preview of the selected file's hunks on the right
+    #[arg(long, conflicts_with_all = ["output", "output_dir", "archive", "copy"])]
+    pub interactive: bool,
+    /// Number the lines of the output. `--line-numbers=false` overrides `[defaults]`
+    #[arg(short, long, num_args = 0..=1, require_equals = true, default_missing_value = "true", default_value = "false", action = ArgAction::Set, value_parser = BoolishValueParser::new(), value_name = "BOOL")]
     pub line_numbers: bool,
     #[arg(long, help = "Alias for --format=cat, useful for piping")]
     pub no_headers: bool,
-    #[arg(long, value_enum, default_value_t = Format::Hunks)]
+    /// Print only the results: no file headers or separators, no progress, and no
+    /// warnings or summaries on stderr
+    #[arg(short, long, overrides_with = "verbose")]
+    pub quiet: bool,
+    /// Print headers, warnings and summaries as usual, undoing an earlier --quiet
+    #[arg(long, overrides_with = "quiet")]
+    pub verbose: bool,
+    /// Print nothing and stop at the first matching file. Exits with 0 if a file
+    /// matched, 1 if none did and 2 on an error, like `grep -q`
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["dry_run", "dry_run_count", "dry_run_report", "interactive"]
+    )]
+    pub silent: bool,
+    #[arg(long, value_enum, default_value_t = Format::Hunks, env = "RDUMP_FORMAT")]
     pub format: Format,
+    /// Render each match through a template (implies --format=template), e.g.
+    /// '- [{relpath}]({relpath}) ({size})'. Placeholders: {path}, {relpath}, {size},
+    /// {modified}, {lines}, {content}, {hunks}, {line_start}, {line_end}
+    #[arg(long, value_name = "TEMPLATE", allow_hyphen_values = true)]
+    pub template: Option<String>,
+    /// Make --format=json write every file's full content, as before hunks were added,
+    /// instead of just the matched hunks
+    #[arg(long)]
+    pub json_full_content: bool,
+    /// Show at most N hunks per file in the `hunks`, `grep`, `quickfix`, `json` and
+    /// `ndjson` formats, the first ones in the file, followed by `... (+K more
+    /// matches)` or a `more_matches` field. Counting formats still count every hunk
+    #[arg(long, value_name = "N")]
+    pub max_hunks_per_file: Option<NonZeroUsize>,
+    /// What a semantic match like `func:main` shows: the whole definition (`body`,
+    /// the default), just the matched name (`name`), or the whole file (`file`)
+    #[arg(long, value_enum, value_name = "GRANULARITY")]
+    pub hunk_granularity: Option<HunkGranularity>,
+    /// Start --format=markdown with a table of contents linking to each file
+    #[arg(long)]
+    pub toc: bool,
+    /// Cap the width of --format=rich boxes, which otherwise fill the terminal
+    #[arg(long, value_name = "N")]
+    pub rich_max_width: Option<usize>,
+    /// The repository's web address for --format=links, e.g.
+    /// https://git.example.com/org/repo, instead of the one derived from the origin remote
+    #[arg(long, value_name = "URL")]
+    pub link_base: Option<String>,
+    /// Print each file as soon as it is found, instead of waiting for earlier files in path order
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", default_value = "false", action = ArgAction::Set, value_parser = BoolishValueParser::new(), value_name = "BOOL")]
+    pub unsorted: bool,
+    /// The order to output matching files in. Any order other than the default waits
+    /// for the whole search to finish
+    #[arg(long, value_enum, value_name = "KEY", default_value_t = SortKey::Path)]
+    pub sort: SortKey,
+    /// Output matching files in the reverse of the --sort order, e.g. `--sort modified
+    /// --reverse` for the most recently modified first
+    #[arg(long)]
+    pub reverse: bool,
+    /// Output at most N matching files, after sorting. Without --sort or --sample, the
+    /// search stops as soon as N files have been found
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+    /// Skip the first N matching files, after sorting, e.g. to page through results
+    /// with --limit
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub offset: usize,
+    /// Print a random sample of N matching files instead of all of them
+    #[arg(long, value_name = "N")]
+    pub sample: Option<usize>,
+    /// Seed for --sample, to get the same selection on every run
+    #[arg(long, requires = "sample")]
+    pub seed: Option<u64>,
+    /// Only output files that fit within N estimated LLM tokens, counting everything
+    /// printed for them, headers and fences included. Files are taken in output
+    /// order (see --sort); a file that would go over the budget is skipped
+    #[arg(long, value_name = "N")]
+    pub max_tokens: Option<usize>,
+    /// Skip files larger than SIZE, e.g. `10mb`, without reading them. How many were
+    /// skipped is reported on stderr
+    #[arg(long, value_name = "SIZE", value_parser = |size: &str| predicates::parse_size(size))]
+    pub max_filesize: Option<u64>,
+    /// Fail on files that aren't valid UTF-8, instead of searching them with the
+    /// invalid bytes replaced
     #[arg(long)]
+    pub strict_encoding: bool,
+    /// Don't respect .gitignore, .rdumpignore or the directories skipped by default.
+    /// `--no-ignore=false` overrides `[defaults]`
+    #[arg(long, env = "RDUMP_NO_IGNORE", num_args = 0..=1, require_equals = true, default_missing_value = "true", default_value = "false", action = ArgAction::Set, value_parser = BoolishValueParser::new(), value_name = "BOOL")]
     pub no_ignore: bool,
+    /// Search the directories rdump skips by default, such as node_modules/, target/
+    /// and .git/, while still respecting .gitignore and .rdumpignore
     #[arg(long)]
+    pub no_default_ignore: bool,
+    /// Also skip files matching the patterns in this gitignore-style file, e.g. a
+    /// .dockerignore, even with --no-ignore. Can be repeated
+    #[arg(long, value_name = "PATH")]
+    pub ignore_file: Vec<PathBuf>,
+    /// Don't read .rdumpignore files, while still respecting .gitignore
+    #[arg(long)]
+    pub no_rdumpignore: bool,
+    /// Search hidden files and directories, those starting with `.`. `--hidden=false`
+    /// overrides `[defaults]`
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", default_value = "false", action = ArgAction::Set, value_parser = BoolishValueParser::new(), value_name = "BOOL")]
     pub hidden: bool,
-    #[arg(long, value_enum, default_value_t = ColorChoice::Auto, help = "When to use syntax highlighting")]
+    /// Descend into symlinked directories. Link loops are skipped with a warning, and a
+    /// file reachable through several links is only searched once
+    #[arg(short = 'L', long)]
+    pub follow: bool,
+    /// List the files that would be searched, one per line, without evaluating the
+    /// query. Handy for checking ignore rules; no query is needed
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Like --dry-run, but print only the number of files that would be searched
+    #[arg(long, conflicts_with = "dry_run")]
+    pub dry_run_count: bool,
+    /// Walk and apply the query's metadata predicates (`ext:`, `in:`, `size:`...)
+    /// without reading any file, then report how many files the query would
+    /// evaluate, how many bytes it would read, and the largest files
+    #[arg(long, conflicts_with_all = ["dry_run", "dry_run_count"])]
+    pub dry_run_report: bool,
+    /// Search on N threads instead of one per logical CPU. --jobs 1 evaluates files
+    /// one at a time, which is handy when debugging or on shared CI machines.
+    /// Directories are always walked on one thread
+    #[arg(short, long, visible_alias = "threads", value_name = "N")]
+    pub jobs: Option<NonZeroUsize>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ColorChoice::Auto,
+        env = "RDUMP_COLOR",
+        help = "When to use syntax highlighting"
+    )]
     pub color: ColorChoice,
+    /// Syntax highlighting theme for colored and HTML output [default: base16-ocean.dark]
+    #[arg(long)]
+    pub theme: Option<String>,
     #[arg(long)]
     pub max_depth: Option<usize>,
+    /// Skip files shallower than this: 1 is the files directly in a root, 2 those
+    /// one directory down, and so on
+    #[arg(long, value_name = "N")]
+    pub min_depth: Option<usize>,
     #[arg(
         long,
         short = 'C',
@@ -120,10 +467,100 @@ pub struct SearchArgs {
         help = "Show LINES of context around matches for --format=hunks"
     )]
     pub context: Option<usize>,
+    #[arg(
+        long,
+        short = 'B',
+        value_name = "LINES",
+        help = "Show LINES of context before matches (overrides -C)"
+    )]
+    pub before_context: Option<usize>,
+    #[arg(
+        long,
+        short = 'A',
+        value_name = "LINES",
+        help = "Show LINES of context after matches (overrides -C)"
+    )]
+    pub after_context: Option<usize>,
+    #[arg(
+        long,
+        value_name = "LINES",
+        default_value_t = 0,
+        help = "Merge hunks separated by at most LINES lines into a single block"
+    )]
+    pub merge_adjacent_hunks: usize,
 
     /// List files with metadata instead of dumping content. Alias for --format=find
     #[arg(long)]
     pub find: bool,
+    /// Print matching paths terminated by NUL instead of newline, for `xargs -0`,
+    /// like `find -print0`. Alias for --format=paths0
+    #[arg(long)]
+    pub print0: bool,
+    /// Print `path: N` for each matching file, where N is its number of hunks, and a
+    /// total, like `grep -c`. Alias for --format=count
+    #[arg(long)]
+    pub count: bool,
+    /// After the output, print to stderr how many files were walked, evaluated and
+    /// matched, how many bytes were read, and how long each stage took
+    #[arg(long)]
+    pub stats: bool,
+    /// Exit with 1 when no file matches and 2 on an error, like grep, instead of
+    /// 0 whenever the search runs
+    #[arg(long)]
+    pub exit_code: bool,
+    /// Show a running count of scanned and matched files, and the file being
+    /// evaluated, on stderr. On by default when stderr is a terminal and the search
+    /// has at least 500 files
+    #[arg(long, overrides_with = "no_progress")]
+    pub progress: bool,
+    /// Never show the progress line
+    #[arg(long, overrides_with = "progress")]
+    pub no_progress: bool,
+    /// Page the output when stdout is a terminal, through CMD, or $PAGER, or `less -R`.
+    /// CMD has to be given as `--pager=CMD`, so the query after `--pager` isn't taken
+    /// for it
+    #[arg(long, value_name = "CMD", require_equals = true, overrides_with = "no_pager")]
+    pub pager: Option<Option<String>>,
+    /// Never page the output, even if a pager is configured
+    #[arg(long, overrides_with = "pager")]
+    pub no_pager: bool,
+    /// After printing the results, open each matching file at its first match in CMD,
+    /// or $VISUAL, or $EDITOR. vim, nvim, emacs, nano and code are opened at the line.
+    /// CMD has to be given as `--editor=CMD`, so the query after `--editor` isn't
+    /// taken for it
+    #[arg(long, value_name = "CMD", require_equals = true)]
+    pub editor: Option<Option<String>>,
+    /// Print the commands --editor would run instead of running them
+    #[arg(long)]
+    pub dry_run_editor: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct StatsArgs {
+    /// The directory to scan
+    #[arg(short, long, value_name = "DIR", default_value = ".")]
+    pub root: PathBuf,
+    /// Don't respect .gitignore, .rdumpignore or the directories skipped by default
+    #[arg(long)]
+    pub no_ignore: bool,
+    /// Scan hidden files and directories, those starting with `.`
+    #[arg(long)]
+    pub hidden: bool,
+    #[arg(short, long, value_enum, default_value_t = StatsFormat::Table)]
+    pub format: StatsFormat,
+    /// Parse files on N threads instead of one per logical CPU
+    #[arg(short, long, visible_alias = "threads", value_name = "N")]
+    pub jobs: Option<NonZeroUsize>,
+}
+
+/// The output format of `rdump stats`.
+#[derive(Debug, Clone, Copy, ValueEnum, Default, PartialEq)]
+pub enum StatsFormat {
+    /// An aligned table, one row per language
+    #[default]
+    Table,
+    /// A JSON array, one object per language
+    Json,
 }
 
 #[derive(Parser, Debug)]
@@ -140,6 +577,61 @@ pub enum LangAction {
     Describe { language: String },
 }
 
+#[derive(Parser, Debug)]
+pub struct ExplainArgs {
+    /// The query to explain
+    #[arg(name = "QUERY")]
+    pub query: Option<String>,
+    /// Splice in saved presets, as with `search --preset`
+    #[arg(long, short)]
+    pub preset: Vec<String>,
+    /// Also evaluate the query against this file, printing each predicate's result
+    /// and the hunks it produced
+    #[arg(long, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct CompletionsArgs {
+    /// The shell to print the completion script for
+    #[arg(value_enum)]
+    pub shell: clap_complete::Shell,
+}
+
+#[derive(Parser, Debug)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    /// Write a starter `.rdump.toml` in the current directory.
+    Init {
+        /// Overwrite an existing `.rdump.toml`
+        #[arg(long)]
+        force: bool,
+    },
+    /// Upgrade the global and local config files to the current format.
+    Migrate {
+        /// Print the changes without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct TypeArgs {
+    #[command(subcommand)]
+    pub action: Option<TypeAction>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum TypeAction {
+    /// List all file types and their extensions.
+    List,
+}
+
 #[derive(Parser, Debug)]
 pub struct PresetArgs {
     #[command(subcommand)]
@@ -162,36 +654,136 @@ pub enum PresetAction {
         #[arg(required = true)]
         name: String,
     },
+    /// Print presets as a file `preset import` can read, to share them.
+    Export {
+        /// The presets to export, e.g. `--names rust,docs` [default: all]
+        #[arg(long, value_delimiter = ',')]
+        names: Vec<String>,
+        #[arg(long, value_enum, default_value_t = PresetFileFormat::Toml)]
+        format: PresetFileFormat,
+    },
+    /// Add the presets in an exported file to the global config file.
+    Import {
+        /// A file written by `preset export`; `.json` files are read as JSON,
+        /// anything else as TOML
+        file: PathBuf,
+        /// Replace presets that already exist with a different query
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+/// The formats `preset export` can write.
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum PresetFileFormat {
+    Toml,
+    Json,
 }
 
-#[derive(Debug, Clone, ValueEnum, Default, PartialEq)]
+#[derive(Debug, Clone, ValueEnum, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Format {
     /// Show only the specific code blocks ("hunks") that match a semantic query
     #[default]
     Hunks,
     /// Human-readable markdown with file headers
     Markdown,
+    /// Each file in a box sized to the terminal, headed by its path, size, language
+    /// and modification time; `markdown` when not writing to a terminal
+    Rich,
     /// Machine-readable JSON
     Json,
+    /// JSON that also includes each file's language and matched hunk positions
+    JsonExtended,
+    /// YAML with the same fields as `json-extended`, using block scalars for content
+    Yaml,
+    /// SARIF 2.1.0 for code scanning tools, with one result per hunk
+    Sarif,
+    /// Newline-delimited JSON: one compact object per file, flushed as it is written
+    Ndjson,
     /// A simple list of matching file paths
     Paths,
+    /// Matching file paths, each terminated by a NUL byte, for `xargs -0`
+    Paths0,
+    /// `path:line:column:text` for each hunk, like `grep -n`; just the path for whole-file matches
+    Grep,
+    /// `path:line:col: text` once per hunk, for Vim's `:cexpr system('rdump ...')`
+    Quickfix,
+    /// A GitHub or GitLab permalink to the lines of each hunk, at the current commit
+    Links,
+    /// A symbol index: `name<TAB>path<TAB>line<TAB>kind` per definition (try `def:*`)
+    Symbols,
+    /// An indented directory tree of matching files, with each file's size and hunk count
+    Tree,
+    /// Each match rendered through `--template`
+    Template,
+    /// Estimated LLM tokens per file, largest first, with a total
+    Tokens,
+    /// `path: N` per file, where N is its number of hunks (1 for a whole-file
+    /// match), with a total, like `grep -c`
+    Count,
+    /// A summary of the matches instead of their content: totals, breakdowns by
+    /// extension and top-level directory, and the largest files
+    Stats,
+    /// One CSV row per hunk (or per file for whole-file matches)
+    Csv,
+    /// XML with a `<file>` element per file and a CDATA `<hunk>` per match
+    Xml,
+    /// A standalone HTML page with a file index and highlighted, linkable lines
+    Html,
     /// Raw concatenated file content, for piping
     Cat,
     /// `ls`-like output with file metadata
     Find,
+    /// Like `find`, plus inode, hard link count, and owner/group columns (`ls -li`)
+    FindLong,
 }
 
-// This is the function that will be called from main.rs
-pub fn run() -> Result<()> {
-    let cli = Cli::parse();
+// This is the function that will be called from main.rs. It returns the status
+// to exit with, which `search --exit-code` sets from the matches.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run() -> anyhow::Result<ExitCode> {
+    #[cfg(target_os = "linux")]
+    if std::env::var_os(commands::search::CLIPBOARD_HOLDER_VAR).is_some() {
+        commands::search::hold_clipboard()?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     match cli.command {
-        Commands::Search(args) => run_search(args),
+        Commands::Search(mut args) => {
+            if let Some(search) = matches.subcommand_matches("search") {
+                args.explicit_flags = explicit_flags(search);
+            }
+            return run_search(*args);
+        }
         Commands::Lang(args) => {
             // Default to `list` if no subcommand is given for `lang`
             let action = args.action.unwrap_or(LangAction::List);
             run_lang(action)
         }
         Commands::Preset(args) => run_preset(args.action),
-    }
-}
\ No newline at end of file
+        Commands::Type(args) => run_type(args.action.unwrap_or(TypeAction::List)),
+        Commands::Stats(args) => run_stats(args),
+        Commands::Explain(args) => run_explain(args),
+        Commands::Completions(args) => run_completions(args),
+        Commands::Config(args) => run_config(args.action),
+    }?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The ids of the arguments in `matches` that weren't left at their default value.
+#[cfg(not(target_arch = "wasm32"))]
+fn explicit_flags(matches: &ArgMatches) -> BTreeSet<String> {
+    matches
+        .ids()
+        .filter(|id| {
+            matches!(
+                matches.value_source(id.as_str()),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        })
+        .map(|id| id.to_string())
+        .collect()
+}
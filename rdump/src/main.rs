@@ -1,6 +1,7 @@
 use anyhow::Result;
 use rdump::run;
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     run()
 }
\ No newline at end of file
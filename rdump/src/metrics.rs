@@ -0,0 +1,161 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Counters and stage timings for `--stats`. Files are read deep inside predicate
+/// evaluation, on rayon workers, so like `diagnostics` this is process-wide rather
+/// than threaded through.
+pub static METRICS: Metrics = Metrics::new();
+
+/// What a search did, for `--stats`. Every field only grows until `reset`.
+pub struct Metrics {
+    files_walked: AtomicUsize,
+    files_evaluated: AtomicUsize,
+    files_matched: AtomicUsize,
+    hunks_matched: AtomicUsize,
+    bytes_read: AtomicU64,
+    walk_nanos: AtomicU64,
+    evaluate_nanos: AtomicU64,
+    format_nanos: AtomicU64,
+}
+
+/// The parts of a search that `--stats` times separately.
+#[derive(Debug, Clone, Copy)]
+pub enum Stage {
+    /// Walking the roots for candidate files.
+    Walk,
+    /// Evaluating the query against the candidates.
+    Evaluate,
+    /// Formatting and writing the matches.
+    Format,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Metrics {
+            files_walked: AtomicUsize::new(0),
+            files_evaluated: AtomicUsize::new(0),
+            files_matched: AtomicUsize::new(0),
+            hunks_matched: AtomicUsize::new(0),
+            bytes_read: AtomicU64::new(0),
+            walk_nanos: AtomicU64::new(0),
+            evaluate_nanos: AtomicU64::new(0),
+            format_nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Zeroes every counter, so a search in a process that already ran one, e.g. a
+    /// test or a library user, reports only its own work.
+    pub fn reset(&self) {
+        for counter in [
+            &self.files_walked,
+            &self.files_evaluated,
+            &self.files_matched,
+            &self.hunks_matched,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        for counter in [
+            &self.bytes_read,
+            &self.walk_nanos,
+            &self.evaluate_nanos,
+            &self.format_nanos,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn files_walked(&self, count: usize) {
+        self.files_walked.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Records a file that passed the metadata pre-filter and was fully evaluated.
+    pub fn file_evaluated(&self) {
+        self.files_evaluated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a matching file with `hunks` hunks (0 for a whole-file match).
+    pub fn file_matched(&self, hunks: usize) {
+        self.files_matched.fetch_add(1, Ordering::Relaxed);
+        self.hunks_matched.fetch_add(hunks, Ordering::Relaxed);
+    }
+
+    pub fn bytes_read(&self, bytes: usize) {
+        self.bytes_read.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_time(&self, stage: Stage, elapsed: Duration) {
+        let nanos = match stage {
+            Stage::Walk => &self.walk_nanos,
+            Stage::Evaluate => &self.evaluate_nanos,
+            Stage::Format => &self.format_nanos,
+        };
+        nanos.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// The summary printed by `--stats`, for a search that took `total` overall.
+    pub fn summary(&self, total: Duration) -> Summary<'_> {
+        Summary {
+            metrics: self,
+            total,
+        }
+    }
+}
+
+/// Displays the `--stats` summary.
+pub struct Summary<'a> {
+    metrics: &'a Metrics,
+    total: Duration,
+}
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = self.metrics;
+        let count = |counter: &AtomicUsize| counter.load(Ordering::Relaxed);
+        let time = |nanos: &AtomicU64| Duration::from_nanos(nanos.load(Ordering::Relaxed));
+        writeln!(f, "Files walked:     {}", count(&m.files_walked))?;
+        writeln!(f, "Files evaluated:  {}", count(&m.files_evaluated))?;
+        writeln!(f, "Files matched:    {}", count(&m.files_matched))?;
+        writeln!(f, "Hunks matched:    {}", count(&m.hunks_matched))?;
+        writeln!(f, "Bytes read:       {}", m.bytes_read.load(Ordering::Relaxed))?;
+        writeln!(
+            f,
+            "Time:             {:.3?} (walk {:.3?}, evaluate {:.3?}, format {:.3?})",
+            self.total,
+            time(&m.walk_nanos),
+            time(&m.evaluate_nanos),
+            time(&m.format_nanos)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let metrics = Metrics::new();
+        metrics.files_walked(10);
+        metrics.file_evaluated();
+        metrics.file_evaluated();
+        metrics.file_matched(3);
+        metrics.bytes_read(2048);
+        metrics.add_time(Stage::Walk, Duration::from_millis(2));
+        metrics.add_time(Stage::Walk, Duration::from_millis(3));
+
+        let summary = metrics.summary(Duration::from_millis(10)).to_string();
+        assert!(summary.contains("Files walked:     10\n"), "{summary}");
+        assert!(summary.contains("Files evaluated:  2\n"), "{summary}");
+        assert!(summary.contains("Files matched:    1\n"), "{summary}");
+        assert!(summary.contains("Hunks matched:    3\n"), "{summary}");
+        assert!(summary.contains("Bytes read:       2048\n"), "{summary}");
+        assert!(summary.contains("(walk 5.000ms, evaluate 0.000ns"), "{summary}");
+
+        metrics.reset();
+        let summary = metrics.summary(Duration::ZERO).to_string();
+        assert!(summary.contains("Files walked:     0\n"), "{summary}");
+        assert!(summary.contains("Bytes read:       0\n"), "{summary}");
+        assert!(summary.contains("(walk 0.000ns,"), "{summary}");
+    }
+}
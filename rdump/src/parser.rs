@@ -3,6 +3,7 @@ use pest::iterators::{Pair, Pairs};
 pub use pest::Parser;
 use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest_derive::Parser;
+use serde::{Serialize, Serializer};
 
 #[derive(Parser)]
 #[grammar = "rql.pest"]
@@ -23,31 +24,56 @@ lazy_static::lazy_static! {
 pub enum PredicateKey {
     Ext,
     Name,
+    IName,
     Path,
+    IPath,
     Contains,
     Matches,
     Size,
     Modified,
+    Since,
+    Before,
     In,
+    Duplicate,
     // --- SEMANTIC PREDICATES ---
     // Generic
     Def,
     Func,
+    Signature,
+    ReturnType,
     Import,
     // Granular Definitions
     Class,
     Struct,
+    Record,
+    Sealed,
     Enum,
     Interface,
     Trait,
     Type,
     Impl,
     Macro,
+    Module,
+    Generic,
+    Decorator,
+    Annotation,
     // Syntactic Content
     Comment,
+    Tag,
     Str,
+    Url,
     // Usage
     Call,
+    MethodCall,
+    FreeCall,
+    Env,
+    // Web
+    Route,
+    // Safety
+    Unsafe,
+    // Metrics
+    Complexity,
+    FuncLines,
     // --- React-specific Predicates ---
     Component,
     Element,
@@ -58,31 +84,60 @@ pub enum PredicateKey {
     Other(String),
 }
 
+/// Keys serialize as they're written in a query, e.g. `"func"`.
+impl Serialize for PredicateKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+
 impl AsRef<str> for PredicateKey {
     fn as_ref(&self) -> &str {
         match self {
             PredicateKey::Ext => "ext",
             PredicateKey::Name => "name",
+            PredicateKey::IName => "iname",
             PredicateKey::Path => "path",
+            PredicateKey::IPath => "ipath",
             PredicateKey::Contains => "contains",
             PredicateKey::Matches => "matches",
             PredicateKey::Size => "size",
             PredicateKey::Modified => "modified",
+            PredicateKey::Since => "since",
+            PredicateKey::Before => "before",
             PredicateKey::In => "in",
+            PredicateKey::Duplicate => "duplicate",
             PredicateKey::Def => "def",
             PredicateKey::Func => "func",
+            PredicateKey::Signature => "signature",
+            PredicateKey::ReturnType => "return_type",
             PredicateKey::Import => "import",
             PredicateKey::Class => "class",
             PredicateKey::Struct => "struct",
+            PredicateKey::Record => "record",
+            PredicateKey::Sealed => "sealed",
             PredicateKey::Enum => "enum",
             PredicateKey::Interface => "interface",
             PredicateKey::Trait => "trait",
             PredicateKey::Type => "type",
             PredicateKey::Impl => "impl",
             PredicateKey::Macro => "macro",
+            PredicateKey::Module => "module",
+            PredicateKey::Generic => "generic",
+            PredicateKey::Decorator => "decorator",
+            PredicateKey::Annotation => "annotation",
             PredicateKey::Comment => "comment",
+            PredicateKey::Tag => "tag",
             PredicateKey::Str => "str",
+            PredicateKey::Url => "url",
             PredicateKey::Call => "call",
+            PredicateKey::MethodCall => "method_call",
+            PredicateKey::FreeCall => "free_call",
+            PredicateKey::Env => "env",
+            PredicateKey::Route => "route",
+            PredicateKey::Unsafe => "unsafe",
+            PredicateKey::Complexity => "complexity",
+            PredicateKey::FuncLines => "funclines",
             PredicateKey::Component => "component",
             PredicateKey::Element => "element",
             PredicateKey::Hook => "hook",
@@ -98,27 +153,49 @@ impl From<&str> for PredicateKey {
         match s {
             "ext" => Self::Ext,
             "name" => Self::Name,
+            "iname" => Self::IName,
             "path" => Self::Path,
+            "ipath" => Self::IPath,
             "contains" => Self::Contains,
             "matches" => Self::Matches,
             "size" => Self::Size,
             "modified" => Self::Modified,
+            "since" => Self::Since,
+            "before" => Self::Before,
             "in" => Self::In,
+            "duplicate" => Self::Duplicate,
             // --- SEMANTIC ---
             "def" => Self::Def,
             "func" => Self::Func,
+            "signature" => Self::Signature,
+            "return_type" => Self::ReturnType,
             "import" => Self::Import,
             "class" => Self::Class,
             "struct" => Self::Struct,
+            "record" => Self::Record,
+            "sealed" => Self::Sealed,
             "enum" => Self::Enum,
             "interface" => Self::Interface,
             "trait" => Self::Trait,
             "type" => Self::Type,
             "impl" => Self::Impl,
             "macro" => Self::Macro,
+            "module" => Self::Module,
+            "generic" => Self::Generic,
+            "decorator" => Self::Decorator,
+            "annotation" => Self::Annotation,
             "comment" => Self::Comment,
+            "tag" => Self::Tag,
             "str" => Self::Str,
+            "url" => Self::Url,
             "call" => Self::Call,
+            "method_call" => Self::MethodCall,
+            "free_call" => Self::FreeCall,
+            "env" => Self::Env,
+            "route" => Self::Route,
+            "unsafe" => Self::Unsafe,
+            "complexity" => Self::Complexity,
+            "funclines" => Self::FuncLines,
             // --- REACT ---
             "component" => Self::Component,
             "element" => Self::Element,
@@ -131,14 +208,16 @@ impl From<&str> for PredicateKey {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AstNode {
     Predicate(PredicateKey, String),
     LogicalOp(LogicalOperator, Box<AstNode>, Box<AstNode>),
     Not(Box<AstNode>),
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LogicalOperator {
     And,
     Or,
@@ -165,7 +244,7 @@ fn build_ast_from_expression_pairs(pairs: Pairs<Rule>) -> Result<AstNode> {
     if pairs
         .clone()
         .last()
-        .map_or(false, |p| matches!(p.as_rule(), Rule::AND | Rule::OR))
+        .is_some_and(|p| matches!(p.as_rule(), Rule::AND | Rule::OR))
     {
         return Err(anyhow!(
             "Invalid query syntax: query cannot end with an operator."
@@ -208,7 +287,11 @@ fn build_ast_from_term(pair: Pair<Rule>) -> Result<AstNode> {
                 let key_pair = predicate_parts.next().ok_or_else(|| anyhow!("Missing key in predicate for rule {:?}", rule))?;
                 let value_pair = predicate_parts.next().ok_or_else(|| anyhow!("Missing value in predicate for key '{}'", key_pair.as_str()))?;
                 let key = PredicateKey::from(key_pair.as_str());
-                let value = unescape_value(value_pair.as_str());
+                let literal = !matches!(
+                    key,
+                    PredicateKey::Matches | PredicateKey::Path | PredicateKey::IPath
+                );
+                let value = unescape_value(value_pair.as_str(), literal);
                 Ok(AstNode::Predicate(key, value))
             } else {
                 Err(anyhow!("Invalid predicate: empty inner rule"))
@@ -234,7 +317,7 @@ fn build_ast_from_term(pair: Pair<Rule>) -> Result<AstNode> {
     }
 }
 
-fn unescape_value(value: &str) -> String {
+fn unescape_value(value: &str, literal: bool) -> String {
     let quote_char = value.chars().next();
     if quote_char == Some('"') || quote_char == Some('\'') {
         let inner = &value[1..value.len() - 1];
@@ -242,8 +325,18 @@ fn unescape_value(value: &str) -> String {
         let mut chars = inner.chars();
         while let Some(c) = chars.next() {
             if c == '\\' {
-                if let Some(next_c) = chars.next() {
-                    unescaped.push(next_c);
+                // In a literal value, `\n` and `\t` stand for a newline and a tab,
+                // e.g. in a multi-line `contains:`; a regex or glob gets them as
+                // written. Any other escaped character stands for itself.
+                match chars.next() {
+                    Some('n') if literal => unescaped.push('\n'),
+                    Some('t') if literal => unescaped.push('\t'),
+                    Some(next_c @ ('n' | 't')) => {
+                        unescaped.push('\\');
+                        unescaped.push(next_c);
+                    }
+                    Some(next_c) => unescaped.push(next_c),
+                    None => {}
                 }
             } else {
                 unescaped.push(c);
@@ -269,6 +362,19 @@ mod tests {
         assert_eq!(ast, *predicate(PredicateKey::Ext, "rs"));
     }
 
+    #[test]
+    fn test_parse_since_and_before() {
+        let ast = parse_query("since:2024-01-01 & before:>yesterday").unwrap();
+        assert_eq!(
+            ast,
+            AstNode::LogicalOp(
+                LogicalOperator::And,
+                predicate(PredicateKey::Since, "2024-01-01"),
+                predicate(PredicateKey::Before, ">yesterday"),
+            )
+        );
+    }
+
     #[test]
     fn test_parse_predicate_with_quoted_value() {
         let ast = parse_query("name:\"foo bar\"").unwrap();
@@ -330,10 +436,20 @@ mod tests {
 
     #[test]
     fn test_unescape_value() {
-        assert_eq!(unescape_value(r#""hello \"world\"""#), "hello \"world\"");
-        assert_eq!(unescape_value(r#"'hello \'world\'""#), "hello 'world'");
-        assert_eq!(unescape_value(r#""a \\ b""#), "a \\ b");
-        assert_eq!(unescape_value("no_quotes"), "no_quotes");
+        assert_eq!(
+            unescape_value(r#""hello \"world\"""#, true),
+            "hello \"world\""
+        );
+        assert_eq!(
+            unescape_value(r#"'hello \'world\'""#, true),
+            "hello 'world'"
+        );
+        assert_eq!(unescape_value(r#""a \\ b""#, true), "a \\ b");
+        assert_eq!(unescape_value("no_quotes", true), "no_quotes");
+        assert_eq!(unescape_value(r#""foo()\nbar()""#, true), "foo()\nbar()");
+        assert_eq!(unescape_value(r#"'a\tb'"#, true), "a\tb");
+        assert_eq!(unescape_value(r"no\nquotes", true), r"no\nquotes");
+        assert_eq!(unescape_value(r#""a\nb\.""#, false), r"a\nb.");
     }
 
     #[test]
@@ -350,10 +466,28 @@ mod tests {
         let ast_func = parse_query("func:get_user").unwrap();
         assert_eq!(ast_func, *predicate(PredicateKey::Func, "get_user"));
 
+        let ast_signature = parse_query("signature:'fn new(config: &Config)'").unwrap();
+        assert_eq!(
+            ast_signature,
+            *predicate(PredicateKey::Signature, "fn new(config: &Config)")
+        );
+
         let ast_import = parse_query("import:serde").unwrap();
         assert_eq!(ast_import, *predicate(PredicateKey::Import, "serde"));
     }
 
+    #[test]
+    fn test_parse_case_insensitive_metadata_predicates() {
+        assert_eq!(
+            parse_query("ipath:src/Main.rs").unwrap(),
+            *predicate(PredicateKey::IPath, "src/Main.rs")
+        );
+        assert_eq!(
+            parse_query("iname:'README*'").unwrap(),
+            *predicate(PredicateKey::IName, "README*")
+        );
+    }
+
     #[test]
     fn test_parse_granular_and_syntactic_predicates() {
         assert_eq!(
@@ -368,14 +502,66 @@ mod tests {
             parse_query("comment:TODO").unwrap(),
             *predicate(PredicateKey::Comment, "TODO")
         );
+        assert_eq!(
+            parse_query("tag:FIXME").unwrap(),
+            *predicate(PredicateKey::Tag, "FIXME")
+        );
         assert_eq!(
             parse_query("str:'api_key'").unwrap(),
             *predicate(PredicateKey::Str, "api_key")
         );
+        assert_eq!(
+            parse_query("url:internal.corp").unwrap(),
+            *predicate(PredicateKey::Url, "internal.corp")
+        );
         assert_eq!(
             parse_query("call:my_func").unwrap(),
             *predicate(PredicateKey::Call, "my_func")
         );
+        assert_eq!(
+            parse_query("method_call:connect").unwrap(),
+            *predicate(PredicateKey::MethodCall, "connect")
+        );
+        assert_eq!(
+            parse_query("free_call:connect").unwrap(),
+            *predicate(PredicateKey::FreeCall, "connect")
+        );
+        assert_eq!(
+            parse_query("env:DATABASE_URL").unwrap(),
+            *predicate(PredicateKey::Env, "DATABASE_URL")
+        );
+        assert_eq!(
+            parse_query("route:/users").unwrap(),
+            *predicate(PredicateKey::Route, "/users")
+        );
+        assert_eq!(
+            parse_query("complexity:>15").unwrap(),
+            *predicate(PredicateKey::Complexity, ">15")
+        );
+        assert_eq!(
+            parse_query("unsafe:transmute").unwrap(),
+            *predicate(PredicateKey::Unsafe, "transmute")
+        );
+        assert_eq!(
+            parse_query("funclines:>100").unwrap(),
+            *predicate(PredicateKey::FuncLines, ">100")
+        );
+        assert_eq!(
+            parse_query("module:tests").unwrap(),
+            *predicate(PredicateKey::Module, "tests")
+        );
+        assert_eq!(
+            parse_query("return_type:Result").unwrap(),
+            *predicate(PredicateKey::ReturnType, "Result")
+        );
+        assert_eq!(
+            parse_query("generic:Serialize").unwrap(),
+            *predicate(PredicateKey::Generic, "Serialize")
+        );
+        assert_eq!(
+            parse_query("decorator:lru_cache").unwrap(),
+            *predicate(PredicateKey::Decorator, "lru_cache")
+        );
     }
 
     #[test]
@@ -1,10 +1,18 @@
-use crate::evaluator::{FileContext, MatchResult};
+use crate::diagnostics;
+use crate::evaluator::{FileContext, Hunk, MatchResult, Symbol};
 use crate::parser::PredicateKey;
-use crate::predicates::PredicateEvaluator;
+use crate::predicates::{helpers, PredicateEvaluator};
 use anyhow::{Context, Result};
-use tree_sitter::{Query, QueryCursor};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tree_sitter::{Node, Query, QueryCursor, Range};
 
 pub mod profiles;
+use profiles::{HunkGranularity, MatchStrategy};
+
+/// Matches URLs (`scheme://...`) inside string literals for the `url:` predicate.
+static URL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"[A-Za-z][A-Za-z0-9+.-]*://[^\s"'`]+"#).unwrap());
 
 /// The evaluator that uses tree-sitter to perform code-aware queries.
 #[derive(Debug, Clone)]
@@ -29,22 +37,46 @@ impl PredicateEvaluator for CodeAwareEvaluator {
             None => return Ok(MatchResult::Boolean(false)), // Not a supported language for this predicate.
         };
 
+        // A Python package is declared by its `__init__.py`, named after its directory.
+        if let PredicateKey::Module = key {
+            if context.path.file_name().is_some_and(|name| name == "__init__.py") {
+                let package = context
+                    .path
+                    .parent()
+                    .and_then(|dir| dir.file_name())
+                    .and_then(|name| name.to_str());
+                if package.is_some_and(|package| is_wildcard(value) || package == value) {
+                    return Ok(MatchResult::Boolean(true));
+                }
+            }
+        }
+
         // 2. Get the tree-sitter query string for the specific predicate.
-        let ts_query_str = match profile.queries.get(key) {
+        // `url:` is built on top of the string literal captures, and `funclines:`
+        // on top of the function name captures.
+        let query_key = match key {
+            PredicateKey::Url => &PredicateKey::Str,
+            PredicateKey::FuncLines => &PredicateKey::Func,
+            _ => key,
+        };
+        let ts_query_str = match profile.queries.get(query_key) {
             Some(q) if !q.is_empty() => q,
             _ => return Ok(MatchResult::Boolean(false)), // This predicate is not implemented for this language yet.
         };
+        let granularity = context
+            .hunk_granularity()
+            .unwrap_or(profile.hunk_granularity);
 
         // 3. Get content and lazily get the parsed tree from the file context.
         let content = context.get_content()?.to_string(); // Clone to avoid borrow issues
         let tree = match context.get_tree(profile.language.clone()) {
             Ok(tree) => tree,
             Err(e) => {
-                eprintln!(
-                    "Warning: Failed to parse {}: {}. Skipping.",
+                diagnostics::warn(format!(
+                    "Failed to parse {}: {}. Skipping.",
                     context.path.display(),
                     e
-                );
+                ));
                 return Ok(MatchResult::Boolean(false));
             }
         };
@@ -53,12 +85,32 @@ impl PredicateEvaluator for CodeAwareEvaluator {
         let query = Query::new(&profile.language, ts_query_str)
             .with_context(|| format!("Failed to compile tree-sitter query for key {key:?}"))?;
         let mut cursor = QueryCursor::new();
-        let mut ranges = Vec::new();
+
+        // `complexity:` scores whole functions rather than comparing captured text.
+        if let PredicateKey::Complexity = key {
+            return Ok(MatchResult::Hunks(complexity_hunks(
+                &query,
+                &mut cursor,
+                tree.root_node(),
+                &content,
+                value,
+            )?));
+        }
+
+        let mut hunks = Vec::new();
 
         // 5. Execute the query and check for a match.
         let captures = cursor.matches(&query, tree.root_node(), content.as_bytes());
 
         for m in captures {
+            // A query may capture a wider `@hunk` node (e.g. a whole route handler)
+            // to report instead of the `@match` node whose text is compared.
+            let hunk_node = m
+                .captures
+                .iter()
+                .find(|c| query.capture_names()[c.index as usize] == "hunk")
+                .map(|c| c.node);
+
             for capture in m.captures {
                 // We only care about nodes captured with the name `@match`.
                 let capture_name = &query.capture_names()[capture.index as usize];
@@ -67,28 +119,405 @@ impl PredicateEvaluator for CodeAwareEvaluator {
                 }
 
                 let captured_node = capture.node;
+
+                // `funclines:` measures the whole function the name belongs to.
+                if let PredicateKey::FuncLines = key {
+                    let func_node = captured_node.parent().unwrap_or(captured_node);
+                    let lines = func_node.end_position().row - func_node.start_position().row + 1;
+                    if helpers::parse_and_compare_count(lines as u64, value)? {
+                        hunks.push(node_hunk(func_node));
+                    }
+                    continue;
+                }
+
+                // `signature:` compares the function's text up to (not including) its body,
+                // but reports the whole function.
+                if let PredicateKey::Signature = key {
+                    let end = captured_node
+                        .child_by_field_name("body")
+                        .map_or(captured_node.end_byte(), |body| body.start_byte());
+                    let signature = collapse_whitespace(&content[captured_node.start_byte()..end]);
+                    if signature.contains(&collapse_whitespace(value)) {
+                        hunks.push(node_hunk(captured_node));
+                    }
+                    continue;
+                }
+
                 let captured_text = captured_node.utf8_text(content.as_bytes())?;
 
                 // Use the correct matching strategy based on the predicate type.
                 let is_match = match key {
+                    // Imports are compared the way the language's profile prefers.
+                    PredicateKey::Import => import_matches(
+                        profile.import_match_strategy,
+                        captured_node,
+                        &content,
+                        value,
+                    ),
                     // Content-based predicates check for substrings.
-                    PredicateKey::Import | PredicateKey::Comment | PredicateKey::Str => {
-                        captured_text.contains(value)
+                    PredicateKey::Comment
+                    | PredicateKey::Str
+                    | PredicateKey::ReturnType
+                    | PredicateKey::Generic => captured_text.contains(value),
+                    // URLs are found in string literals and filtered by substring.
+                    PredicateKey::Url => URL_REGEX
+                        .find_iter(captured_text)
+                        .any(|url| is_wildcard(value) || url.as_str().contains(value)),
+                    // Environment variables match the (unquoted) variable name exactly.
+                    PredicateKey::Env => {
+                        is_wildcard(value)
+                            || captured_text.trim_matches(|c| c == '"' || c == '\'' || c == '`')
+                                == value
+                    }
+                    // Routes match the path string by substring, or any route with a wildcard.
+                    PredicateKey::Route => is_wildcard(value) || captured_text.contains(value),
+                    // Unsafe regions match any region, or those mentioning the identifier.
+                    PredicateKey::Unsafe => {
+                        is_wildcard(value)
+                            || value == "true"
+                            || captured_text
+                                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                                .any(|ident| ident == value)
                     }
                     // Hook predicates can match any hook (`hook:.`) or a specific one
                     PredicateKey::Hook | PredicateKey::CustomHook => {
-                        value == "." || captured_text == value
+                        is_wildcard(value) || captured_text == value
                     }
                     // Definition-based predicates require an exact match on the identifier, unless a wildcard is used.
-                    _ => value == "." || captured_text == value,
+                    _ => is_wildcard(value) || captured_text == value,
                 };
 
                 if is_match {
-                    ranges.push(captured_node.range());
+                    hunks.push(node_hunk(hunk_node.unwrap_or_else(|| {
+                        granular_node(granularity, captured_node, tree.root_node())
+                    })));
                 }
             }
         }
 
-        Ok(MatchResult::Hunks(ranges))
+        Ok(MatchResult::Hunks(hunks))
+    }
+}
+
+/// Whether `value` matches anything: `.` or `*` (e.g. `def:*` for every definition).
+fn is_wildcard(value: &str) -> bool {
+    matches!(value, "." | "*")
+}
+
+/// The node to report as the hunk for the matched `node` under `granularity`. With
+/// `Body`, a node that is the `name` of its parent (a function, class, struct...)
+/// reports the parent; anything else, like a call or an import, reports itself.
+fn granular_node<'tree>(
+    granularity: HunkGranularity,
+    node: Node<'tree>,
+    root: Node<'tree>,
+) -> Node<'tree> {
+    match granularity {
+        HunkGranularity::Name => node,
+        HunkGranularity::Body => node
+            .parent()
+            .filter(|parent| parent.child_by_field_name("name") == Some(node))
+            .unwrap_or(node),
+        HunkGranularity::File => root,
+    }
+}
+
+/// A hunk reporting `node`, along with the definition it points at, if any.
+fn node_hunk(node: Node) -> Hunk {
+    Hunk {
+        range: node.range(),
+        symbol: definition_symbol(node),
+    }
+}
+
+/// Whether the import statement `node` matches `value` under `strategy`.
+fn import_matches(strategy: MatchStrategy, node: Node, content: &str, value: &str) -> bool {
+    let text = &content[node.byte_range()];
+    match strategy {
+        MatchStrategy::Substring => text.contains(value),
+        MatchStrategy::Exact => {
+            is_wildcard(value)
+                || imported_names(node, content)
+                    .iter()
+                    .any(|name| name == value)
+        }
+    }
+}
+
+/// The modules and names an import statement brings in: `os.path` for
+/// `import os.path as p`, and `os` and `os.path` for `from os import path`.
+/// Relative modules drop their leading dots, so `from .models import User` yields
+/// `models` and `models.User`. Statements without `name` fields yield their whole text.
+fn imported_names(node: Node, content: &str) -> Vec<String> {
+    let text = |node: Node| content[node.byte_range()].to_string();
+    let module = node
+        .child_by_field_name("module_name")
+        .map(|module| text(module).trim_start_matches('.').to_string())
+        .filter(|module| !module.is_empty());
+    let mut cursor = node.walk();
+    let mut names: Vec<String> = node
+        .children_by_field_name("name", &mut cursor)
+        .map(|name| {
+            // `import numpy as np` imports `numpy`.
+            let name = name.child_by_field_name("name").unwrap_or(name);
+            match &module {
+                Some(module) => format!("{module}.{}", text(name)),
+                None => text(name),
+            }
+        })
+        .collect();
+    match module {
+        Some(module) => names.push(module),
+        None if names.is_empty() => names.push(text(node)),
+        None => {}
+    }
+    names
+}
+
+/// Collapses runs of whitespace (including newlines) into single spaces, so multi-line
+/// signatures can be matched with a one-line value.
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Runs the profile query for `key` against the file and returns the range and text
+/// of every `@match` capture. Returns `None` if the language or predicate is unsupported.
+pub(crate) fn find_captures(
+    context: &mut FileContext,
+    key: &PredicateKey,
+) -> Result<Option<Vec<(Range, String)>>> {
+    let extension = context
+        .path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+    let binding = profiles::list_language_profiles();
+    let profile = match binding.iter().find(|p| p.extensions.contains(&extension.as_str())) {
+        Some(p) => p,
+        None => return Ok(None),
+    };
+    let ts_query_str = match profile.queries.get(key) {
+        Some(q) if !q.is_empty() => q,
+        _ => return Ok(None),
+    };
+
+    let content = context.get_content()?.to_string();
+    let tree = match context.get_tree(profile.language.clone()) {
+        Ok(tree) => tree,
+        Err(e) => {
+            diagnostics::warn(format!(
+                "Failed to parse {}: {}. Skipping.",
+                context.path.display(),
+                e
+            ));
+            return Ok(None);
+        }
+    };
+
+    let query = Query::new(&profile.language, ts_query_str)
+        .with_context(|| format!("Failed to compile tree-sitter query for key {key:?}"))?;
+    let mut cursor = QueryCursor::new();
+    let mut captures = Vec::new();
+    for m in cursor.matches(&query, tree.root_node(), content.as_bytes()) {
+        for capture in m.captures {
+            if query.capture_names()[capture.index as usize] == "match" {
+                let text = capture.node.utf8_text(content.as_bytes())?.to_string();
+                captures.push((capture.node.range(), text));
+            }
+        }
+    }
+    Ok(Some(captures))
+}
+
+/// Describes the scopes enclosing `byte` in a supported source file, outermost first,
+/// e.g. `impl User::new()` or `class Cart::total()`. Returns `None` for unsupported
+/// languages and for top-level code.
+pub fn enclosing_scope(context: &mut FileContext, byte: usize) -> Option<String> {
+    let extension = context.path.extension().and_then(|s| s.to_str())?.to_string();
+    let profile = profiles::list_language_profiles()
+        .into_iter()
+        .find(|p| p.extensions.contains(&extension.as_str()))?;
+    let (content, tree) = context.get_content_and_tree(profile.language.clone()).ok()?;
+
+    let mut scopes = Vec::new();
+    let mut node = tree.root_node().descendant_for_byte_range(byte, byte);
+    while let Some(current) = node {
+        let name = |field: &str| {
+            current
+                .child_by_field_name(field)
+                .and_then(|n| n.utf8_text(content.as_bytes()).ok())
+        };
+        let label = match current.kind() {
+            "function_item" | "function_definition" | "function_declaration"
+            | "method_definition" | "method_declaration" | "constructor_declaration" => {
+                name("name").map(|n| format!("{n}()"))
+            }
+            "impl_item" => name("type").map(|n| format!("impl {n}")),
+            "trait_item" => name("name").map(|n| format!("trait {n}")),
+            "mod_item" => name("name").map(|n| format!("mod {n}")),
+            "class_definition" | "class_declaration" => name("name").map(|n| format!("class {n}")),
+            "interface_declaration" => name("name").map(|n| format!("interface {n}")),
+            _ => None,
+        };
+        scopes.extend(label);
+        node = current.parent();
+    }
+
+    if scopes.is_empty() {
+        return None;
+    }
+    scopes.reverse();
+    Some(scopes.join("::"))
+}
+
+/// Describes the definition `node` points at, e.g. the name `User` of a `struct`.
+/// The node may be the definition's name (as captured by `def:`) or the whole
+/// definition (as reported by `impl:`). Returns `None` if it isn't a definition.
+fn definition_symbol(node: Node) -> Option<Symbol> {
+    fn name_of(item: Node) -> Option<Node> {
+        item.child_by_field_name("name")
+            .or_else(|| item.child_by_field_name("type"))
+    }
+    // A captured name's parent is the definition; a reported definition names itself.
+    let (item, name_node) = match name_of(node) {
+        Some(name) => (node, name),
+        None => {
+            let parent = node.parent()?;
+            (name_of(parent) == Some(node)).then_some((parent, node))?
+        }
+    };
+    let kind = match item.kind() {
+        "function_item" | "function_definition" | "function_declaration"
+        | "function_signature_item" => "function",
+        "method_definition" | "method_declaration" | "constructor_declaration" => "method",
+        "struct_item" => "struct",
+        "enum_item" | "enum_declaration" => "enum",
+        "trait_item" => "trait",
+        "impl_item" => "impl",
+        "type_item" | "type_alias_declaration" => "type",
+        // Go declares structs and interfaces as named types.
+        "type_spec" => match item.child_by_field_name("type").map(|t| t.kind()) {
+            Some("struct_type") => "struct",
+            Some("interface_type") => "interface",
+            _ => "type",
+        },
+        "class_definition" | "class_declaration" => "class",
+        "interface_declaration" => "interface",
+        "record_declaration" => "record",
+        "annotation_type_declaration" => "annotation",
+        "macro_definition" => "macro",
+        "mod_item" => "module",
+        "variable_declarator" => "variable",
+        _ => return None,
+    };
+    Some(Symbol {
+        name: name_node.range(),
+        kind,
+    })
+}
+
+/// Scores each function captured as `@match` by counting the `@branch` nodes
+/// (if/match/for/while/&&/||, as defined by the language profile) whose innermost
+/// enclosing function it is, and returns the functions whose approximate
+/// cyclomatic complexity (`1 + branches`) satisfies `value` (e.g. `>15`).
+fn complexity_hunks(
+    query: &Query,
+    cursor: &mut QueryCursor,
+    root: Node,
+    content: &str,
+    value: &str,
+) -> Result<Vec<Hunk>> {
+    let mut functions: Vec<(Node, u64)> = Vec::new();
+    let mut branches: Vec<(usize, usize)> = Vec::new();
+
+    for m in cursor.matches(query, root, content.as_bytes()) {
+        for capture in m.captures {
+            match query.capture_names()[capture.index as usize] {
+                "match" => functions.push((capture.node, 1)),
+                "branch" => branches.push((capture.node.start_byte(), capture.node.end_byte())),
+                _ => {}
+            }
+        }
+    }
+    // A node captured by several matches is still one branch point.
+    branches.sort_unstable();
+    branches.dedup();
+
+    for &(start, end) in &branches {
+        // Attribute the branch to the smallest function containing it, so a nested
+        // function's branches don't also inflate its parent's score.
+        let innermost = functions
+            .iter_mut()
+            .filter(|(f, _)| f.start_byte() <= start && end <= f.end_byte())
+            .min_by_key(|(f, _)| f.end_byte() - f.start_byte());
+        if let Some((_, score)) = innermost {
+            *score += 1;
+        }
+    }
+
+    let mut hunks = Vec::new();
+    for (function, score) in functions {
+        if helpers::parse_and_compare_count(score, value)? {
+            hunks.push(node_hunk(function));
+        }
+    }
+    Ok(hunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree_sitter::Parser;
+
+    /// Runs `import_matches` against the first statement of a Python snippet.
+    fn python_import_matches(source: &str, strategy: MatchStrategy, value: &str) -> bool {
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_python::language()).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let statement = tree.root_node().child(0).unwrap();
+        import_matches(strategy, statement, source, value)
+    }
+
+    #[test]
+    fn test_definition_symbol() {
+        let source = "struct User;\n\nimpl User {\n    fn new() -> u8 { 1 }\n}\n";
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let node_at = |text: &str| {
+            let start = source.find(text).unwrap();
+            tree.root_node()
+                .descendant_for_byte_range(start, start + text.len())
+                .unwrap()
+        };
+        let describe = |node| {
+            let symbol = definition_symbol(node)?;
+            let name = &source[symbol.name.start_byte..symbol.name.end_byte];
+            Some((name, symbol.kind))
+        };
+
+        assert_eq!(describe(node_at("User")), Some(("User", "struct")));
+        assert_eq!(describe(node_at("new")), Some(("new", "function")));
+        assert_eq!(describe(node_at("impl User")), Some(("User", "impl")));
+        // A return type belongs to a function but doesn't name it.
+        assert_eq!(describe(node_at("u8")), None);
+    }
+
+    #[test]
+    fn test_import_match_strategies() {
+        let aliased = "import numpy as np\n";
+        assert!(python_import_matches(aliased, MatchStrategy::Exact, "numpy"));
+        assert!(!python_import_matches(aliased, MatchStrategy::Exact, "np"));
+        assert!(python_import_matches(aliased, MatchStrategy::Substring, "np"));
+
+        let from = "from os import path, sep\n";
+        assert!(python_import_matches(from, MatchStrategy::Exact, "os.sep"));
+        assert!(!python_import_matches(from, MatchStrategy::Exact, "sys.sep"));
+
+        let relative = "from .models import User\n";
+        assert!(python_import_matches(relative, MatchStrategy::Exact, "models"));
+        assert!(python_import_matches(relative, MatchStrategy::Exact, "models.User"));
+        assert!(python_import_matches("from . import views\n", MatchStrategy::Exact, "views"));
     }
 }
@@ -1,4 +1,4 @@
-use super::LanguageProfile;
+use super::{HunkGranularity, LanguageProfile, MatchStrategy};
 use crate::parser::PredicateKey;
 use std::collections::HashMap;
 
@@ -23,9 +23,47 @@ pub(super) fn create_go_profile() -> LanguageProfile {
 
     // --- Functions & Calls ---
     queries.insert(PredicateKey::Func, func_query.to_string());
+    queries.insert(
+        PredicateKey::Signature,
+        "[ (function_declaration) @match (method_declaration) @match ]".to_string(),
+    );
     queries.insert(PredicateKey::Call, "(call_expression function: [ (identifier) @match (selector_expression field: (field_identifier) @match) ])".to_string());
 
+    // --- Environment ---
+    queries.insert(
+        PredicateKey::Env,
+        r#"
+        (call_expression
+            function: (selector_expression
+                operand: (identifier) @_os
+                field: (field_identifier) @_fn)
+            arguments: (argument_list . [(interpreted_string_literal) (raw_string_literal)] @match)
+            (#eq? @_os "os")
+            (#match? @_fn "^(Getenv|LookupEnv|Setenv|Unsetenv)$")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
+    // --- Web ---
+    // `mux.HandleFunc("/users", ...)`, `http.Handle(...)` and gin/echo-style `r.GET("/users", ...)`.
+    queries.insert(
+        PredicateKey::Route,
+        r#"
+        (call_expression
+            function: (selector_expression field: (field_identifier) @_method)
+            arguments: (argument_list . [(interpreted_string_literal) (raw_string_literal)] @match)
+            (#match? @_method "^(HandleFunc|Handle|GET|POST|PUT|PATCH|DELETE|HEAD|OPTIONS|Any|Get|Post|Put|Patch|Delete)$")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
     // --- Other ---
+    queries.insert(
+        PredicateKey::Module,
+        "(package_clause (package_identifier) @match)".to_string(),
+    );
     queries.insert(
         PredicateKey::Import,
         "(import_declaration) @match".to_string(),
@@ -41,5 +79,7 @@ pub(super) fn create_go_profile() -> LanguageProfile {
         extensions: vec!["go"],
         language,
         queries,
+        import_match_strategy: MatchStrategy::Substring,
+        hunk_granularity: HunkGranularity::Body,
     }
 }
@@ -1,4 +1,4 @@
-use super::LanguageProfile;
+use super::{HunkGranularity, LanguageProfile, MatchStrategy};
 use crate::parser::PredicateKey;
 use std::collections::HashMap;
 
@@ -11,19 +11,50 @@ pub(super) fn create_java_profile() -> LanguageProfile {
     let class_query = "(class_declaration name: (identifier) @match)";
     let interface_query = "(interface_declaration name: (identifier) @match)";
     let enum_query = "(enum_declaration name: (identifier) @match)";
+    let record_query = "(record_declaration name: (identifier) @match)";
+    let annotation_query = "(annotation_type_declaration name: (identifier) @match)";
 
     queries.insert(
         PredicateKey::Def,
-        format!("[ {class_query} {interface_query} {enum_query} ]"),
+        format!("[ {class_query} {interface_query} {enum_query} {record_query} {annotation_query} ]"),
     );
     queries.insert(PredicateKey::Class, class_query.to_string());
     queries.insert(PredicateKey::Interface, interface_query.to_string());
     queries.insert(PredicateKey::Enum, enum_query.to_string());
+    queries.insert(PredicateKey::Record, record_query.to_string());
+    queries.insert(PredicateKey::Annotation, annotation_query.to_string());
+    // `sealed class Shape permits Circle, Square`, and sealed interfaces.
+    queries.insert(
+        PredicateKey::Sealed,
+        r#"[
+            (class_declaration (modifiers "sealed") name: (identifier) @match)
+            (interface_declaration (modifiers "sealed") name: (identifier) @match)
+        ]"#
+        .to_string(),
+    );
 
     // --- Functions & Calls ---
-    queries.insert(PredicateKey::Func, "[ (method_declaration name: (identifier) @match) (constructor_declaration name: (identifier) @match) ]".to_string());
+    // A lambda counts as a function under the name of the variable or field it's
+    // assigned to, e.g. `Function<String, Integer> parse = s -> ...`.
+    queries.insert(PredicateKey::Func, "[ (method_declaration name: (identifier) @match) (constructor_declaration name: (identifier) @match) (variable_declarator name: (identifier) @match value: (lambda_expression)) ]".to_string());
+    queries.insert(PredicateKey::Signature, "[ (method_declaration) @match (constructor_declaration) @match ]".to_string());
     queries.insert(PredicateKey::Call, "[ (method_invocation name: (identifier) @match) (object_creation_expression type: (type_identifier) @match) ]".to_string());
 
+    // --- Environment ---
+    queries.insert(
+        PredicateKey::Env,
+        r#"
+        (method_invocation
+            object: (identifier) @_system
+            name: (identifier) @_fn
+            arguments: (argument_list . (string_literal) @match)
+            (#eq? @_system "System")
+            (#eq? @_fn "getenv")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
     // --- Other ---
     queries.insert(
         PredicateKey::Import,
@@ -40,5 +71,7 @@ pub(super) fn create_java_profile() -> LanguageProfile {
         extensions: vec!["java"],
         language,
         queries,
+        import_match_strategy: MatchStrategy::Substring,
+        hunk_granularity: HunkGranularity::Body,
     }
 }
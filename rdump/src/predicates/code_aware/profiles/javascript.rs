@@ -1,4 +1,4 @@
-use super::LanguageProfile;
+use super::{HunkGranularity, LanguageProfile, MatchStrategy};
 use crate::parser::PredicateKey;
 use std::collections::HashMap;
 
@@ -13,6 +13,10 @@ pub(super) fn create_javascript_profile() -> LanguageProfile {
     queries.insert(PredicateKey::Def, [class_query, func_query].join("\n"));
     queries.insert(PredicateKey::Class, class_query.to_string());
     queries.insert(PredicateKey::Func, func_query.to_string());
+    queries.insert(
+        PredicateKey::Signature,
+        "[ (function_declaration) @match (method_definition) @match ]".to_string(),
+    );
 
     queries.insert(
         PredicateKey::Import,
@@ -23,6 +27,46 @@ pub(super) fn create_javascript_profile() -> LanguageProfile {
        "[ (call_expression function: [ (identifier) @match (member_expression property: (property_identifier) @match) ]) (new_expression constructor: (identifier) @match) ]".to_string()
    );
 
+    // Query for environment variable reads: `process.env.X` and `process.env["X"]`.
+    queries.insert(
+        PredicateKey::Env,
+        r#"
+        (member_expression
+            object: (member_expression
+                object: (identifier) @_process
+                property: (property_identifier) @_env)
+            property: (property_identifier) @match
+            (#eq? @_process "process")
+            (#eq? @_env "env")
+        ) @hunk
+        (subscript_expression
+            object: (member_expression
+                object: (identifier) @_process
+                property: (property_identifier) @_env)
+            index: (string) @match
+            (#eq? @_process "process")
+            (#eq? @_env "env")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
+    // Query for Express-style route registrations, e.g. `app.get('/users', handler)`.
+    queries.insert(
+        PredicateKey::Route,
+        r#"
+        (call_expression
+            function: (member_expression
+                object: (identifier) @_object
+                property: (property_identifier) @_method)
+            arguments: (arguments . [(string) (template_string)] @match)
+            (#match? @_object "(?i)(app|router|server|api)$")
+            (#match? @_method "^(get|post|put|patch|delete|all|use|head|options)$")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
     queries.insert(
         PredicateKey::Comment,
         "[(comment) @match (regex) @match]".to_string(),
@@ -67,5 +111,7 @@ pub(super) fn create_javascript_profile() -> LanguageProfile {
         extensions: vec!["js"],
         language,
         queries,
+        import_match_strategy: MatchStrategy::Substring,
+        hunk_granularity: HunkGranularity::Body,
     }
 }
@@ -10,12 +10,39 @@ mod react; // Add react module
 mod rust;
 mod typescript;
 
+/// How an `import:` value is compared with a language's import statements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchStrategy {
+    /// The value must equal an imported module or name, e.g. `import:os` matches
+    /// `import os` but not `import os.path`.
+    Exact,
+    /// The value may appear anywhere in the statement, e.g. `import:serde` matches
+    /// `use serde::Serialize;`.
+    #[default]
+    Substring,
+}
+
+/// How much of the source a semantic match reports as its hunk. Each profile sets
+/// one, and `--hunk-granularity` overrides it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum HunkGranularity {
+    /// Just the matched node, e.g. a function's name.
+    Name,
+    /// The definition a matched name belongs to, e.g. the whole function.
+    #[default]
+    Body,
+    /// The whole file.
+    File,
+}
+
 /// Defines the tree-sitter queries and metadata for a specific language.
 pub struct LanguageProfile {
     pub name: &'static str,
     pub extensions: Vec<&'static str>,
     pub(super) language: tree_sitter::Language,
     pub queries: HashMap<PredicateKey, String>,
+    pub import_match_strategy: MatchStrategy,
+    pub hunk_granularity: HunkGranularity,
 }
 
 pub(super) static LANGUAGE_PROFILES: Lazy<HashMap<&'static str, LanguageProfile>> =
@@ -1,4 +1,4 @@
-use super::LanguageProfile;
+use super::{HunkGranularity, LanguageProfile, MatchStrategy};
 use crate::parser::PredicateKey;
 use std::collections::HashMap;
 
@@ -13,6 +13,10 @@ pub(super) fn create_python_profile() -> LanguageProfile {
     queries.insert(PredicateKey::Def, [class_query, func_query].join("\n"));
     queries.insert(PredicateKey::Class, class_query.to_string());
     queries.insert(PredicateKey::Func, func_query.to_string());
+    queries.insert(
+        PredicateKey::Signature,
+        "(function_definition) @match".to_string(),
+    );
 
     // Query for `import` and `from ... import` statements.
     queries.insert(
@@ -40,13 +44,109 @@ pub(super) fn create_python_profile() -> LanguageProfile {
         .to_string(),
     );
 
+    // Query for environment variable reads: `os.environ["X"]`, `os.getenv("X")`, `os.environ.get("X")`.
+    queries.insert(
+        PredicateKey::Env,
+        r#"
+        (subscript
+            value: (attribute object: (identifier) @_os attribute: (identifier) @_environ)
+            subscript: (string) @match
+            (#eq? @_os "os")
+            (#eq? @_environ "environ")
+        ) @hunk
+        (call
+            function: (attribute object: (identifier) @_os attribute: (identifier) @_fn)
+            arguments: (argument_list . (string) @match)
+            (#eq? @_os "os")
+            (#match? @_fn "^(getenv|putenv|unsetenv)$")
+        ) @hunk
+        (call
+            function: (attribute
+                object: (attribute object: (identifier) @_os attribute: (identifier) @_environ)
+                attribute: (identifier) @_fn)
+            arguments: (argument_list . (string) @match)
+            (#eq? @_os "os")
+            (#eq? @_environ "environ")
+            (#match? @_fn "^(get|pop|setdefault)$")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
+    // Query for Flask/FastAPI-style route decorators, e.g. `@app.route("/users")`.
+    queries.insert(
+        PredicateKey::Route,
+        r#"
+        (decorated_definition
+            (decorator
+                (call
+                    function: (attribute attribute: (identifier) @_method)
+                    arguments: (argument_list . (string) @match)))
+            (#match? @_method "^(route|get|post|put|patch|delete|head|options|api_route|websocket)$")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
     queries.insert(PredicateKey::Comment, "(comment) @match".to_string());
     queries.insert(PredicateKey::Str, "(string) @match".to_string());
 
+    // Decorator names (`@property`, `@lru_cache(maxsize=None)`, `@functools.wraps(f)`),
+    // reporting the whole decorated definition.
+    queries.insert(
+        PredicateKey::Decorator,
+        r#"
+        (decorated_definition
+            (decorator [
+                (identifier) @match
+                (attribute attribute: (identifier) @match)
+                (call function: [
+                    (identifier) @match
+                    (attribute attribute: (identifier) @match)
+                ])
+            ])) @hunk
+        "#
+        .to_string(),
+    );
+
+    // A module's public interface, declared with a module-level `__all__ = [...]`.
+    queries.insert(
+        PredicateKey::Module,
+        r#"
+        (module
+            (expression_statement
+                (assignment left: (identifier) @match (#eq? @match "__all__"))) @hunk)
+        "#
+        .to_string(),
+    );
+
+    // Query for the `complexity:` metric: functions to score (`@match`) and the
+    // branch points counted within them (`@branch`).
+    queries.insert(
+        PredicateKey::Complexity,
+        r#"
+        (function_definition) @match
+        [
+            (if_statement)
+            (elif_clause)
+            (for_statement)
+            (while_statement)
+            (except_clause)
+            (case_clause)
+            (conditional_expression)
+            (boolean_operator)
+            (if_clause)
+        ] @branch
+        "#
+        .to_string(),
+    );
+
     LanguageProfile {
         name: "Python",
         extensions: vec!["py"],
         language,
         queries,
+        import_match_strategy: MatchStrategy::Exact,
+        hunk_granularity: HunkGranularity::Body,
     }
 }
@@ -1,4 +1,4 @@
-use super::LanguageProfile;
+use super::{HunkGranularity, LanguageProfile, MatchStrategy};
 use crate::parser::PredicateKey;
 use std::collections::HashMap;
 
@@ -105,5 +105,7 @@ pub(super) fn create_react_profile() -> LanguageProfile {
         extensions: vec!["jsx", "tsx"],
         language,
         queries,
+        import_match_strategy: MatchStrategy::Substring,
+        hunk_granularity: HunkGranularity::Body,
     }
 }
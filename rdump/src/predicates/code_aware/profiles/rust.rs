@@ -1,4 +1,4 @@
-use super::LanguageProfile;
+use super::{HunkGranularity, LanguageProfile, MatchStrategy};
 use crate::parser::PredicateKey;
 use std::collections::HashMap;
 
@@ -11,7 +11,19 @@ pub(super) fn create_rust_profile() -> LanguageProfile {
     let enum_query = "(enum_item name: (_) @match)";
     let trait_query = "(trait_item name: (_) @match)";
     let type_query = "(type_item name: (type_identifier) @match)";
-    let impl_query = "(impl_item type: (type_identifier) @match)";
+    // Matches `impl User`, `impl<T> Wrapper<T>`, `impl Display for User` and `impl models::User`,
+    // reporting the whole impl block.
+    let impl_query = "
+        (impl_item
+            type: [
+                (type_identifier) @match
+                (scoped_type_identifier name: (type_identifier) @match)
+                (generic_type type: [
+                    (type_identifier) @match
+                    (scoped_type_identifier name: (type_identifier) @match)
+                ])
+            ]) @hunk
+    ";
     let macro_query = "(macro_definition name: (identifier) @match)";
 
     let def_query = [struct_query, enum_query, trait_query, type_query].join("\n");
@@ -23,6 +35,19 @@ pub(super) fn create_rust_profile() -> LanguageProfile {
     queries.insert(PredicateKey::Type, type_query.to_string());
     queries.insert(PredicateKey::Impl, impl_query.to_string());
     queries.insert(PredicateKey::Macro, macro_query.to_string());
+    // Generic parameters (`<T: Serialize>`) and `where` clauses, reporting the whole item.
+    queries.insert(
+        PredicateKey::Generic,
+        "
+        (_ type_parameters: (type_parameters) @match) @hunk
+        (_ (where_clause) @match) @hunk
+        "
+        .to_string(),
+    );
+    queries.insert(
+        PredicateKey::Module,
+        "(mod_item name: (identifier) @match)".to_string(),
+    );
 
     // Query for standalone functions and methods in traits or impls.
     queries.insert(
@@ -35,6 +60,16 @@ pub(super) fn create_rust_profile() -> LanguageProfile {
         "
         .to_string(),
     );
+    // Query for whole functions; `signature:` compares the text before the body.
+    queries.insert(
+        PredicateKey::Signature,
+        "[(function_item) @match (function_signature_item) @match]".to_string(),
+    );
+    // Query for a function's return type, reporting the whole function.
+    queries.insert(
+        PredicateKey::ReturnType,
+        "(function_item return_type: (_) @match) @hunk".to_string(),
+    );
     // Query for the entire `use` declaration. We will match against its text content.
     queries.insert(
         PredicateKey::Import,
@@ -49,6 +84,67 @@ pub(super) fn create_rust_profile() -> LanguageProfile {
         .to_string(),
     );
 
+    // `call:` split by receiver: `self.connect()` is a method call, while `connect()`
+    // and `db::connect()` are free function calls.
+    queries.insert(
+        PredicateKey::MethodCall,
+        "(call_expression function: (field_expression field: (field_identifier) @match))"
+            .to_string(),
+    );
+    queries.insert(
+        PredicateKey::FreeCall,
+        r#"
+        (call_expression
+            function: [
+                (identifier) @match
+                (scoped_identifier name: (identifier) @match)
+            ])
+        "#
+        .to_string(),
+    );
+
+    // Query for environment variable reads: `std::env::var("X")`, `env!("X")`, etc.
+    queries.insert(
+        PredicateKey::Env,
+        r#"
+        (call_expression
+            function: (scoped_identifier path: (_) @_path name: (identifier) @_fn)
+            arguments: (arguments . (string_literal) @match)
+            (#match? @_path "(^|::)env$")
+            (#match? @_fn "^(var|var_os|set_var|remove_var)$")
+        ) @hunk
+        (macro_invocation
+            macro: (identifier) @_macro
+            (token_tree . (string_literal) @match)
+            (#match? @_macro "^(option_)?env$")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
+    // Query for actix/rocket-style route attributes (`#[get("/users")]`) on the
+    // handler that follows them, and axum-style `.route("/users", ...)` calls.
+    queries.insert(
+        PredicateKey::Route,
+        r#"
+        (
+            (attribute_item
+                (attribute
+                    (identifier) @_method
+                    arguments: (token_tree . (string_literal) @match)))
+            .
+            (function_item) @hunk
+            (#match? @_method "^(get|post|put|patch|delete|head|options|trace|connect|route)$")
+        )
+        (call_expression
+            function: (field_expression field: (field_identifier) @_route)
+            arguments: (arguments . (string_literal) @match)
+            (#eq? @_route "route")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
     queries.insert(
         PredicateKey::Comment,
         "[(line_comment) @match (block_comment) @match]".to_string(),
@@ -58,10 +154,42 @@ pub(super) fn create_rust_profile() -> LanguageProfile {
         "[(string_literal) @match (raw_string_literal) @match]".to_string(),
     );
 
+    // Query for `unsafe` blocks, `unsafe fn` definitions, and `unsafe impl`/`unsafe trait` items.
+    queries.insert(
+        PredicateKey::Unsafe,
+        r#"
+        (unsafe_block) @match
+        (function_item (function_modifiers "unsafe")) @match
+        (impl_item "unsafe") @match
+        (trait_item "unsafe") @match
+        "#
+        .to_string(),
+    );
+
+    // Query for the `complexity:` metric: functions to score (`@match`) and the
+    // branch points counted within them (`@branch`). A match with n arms has n - 1
+    // branch points, so every arm but the first counts.
+    queries.insert(
+        PredicateKey::Complexity,
+        r#"
+        (function_item) @match
+        [
+            (if_expression)
+            (for_expression)
+            (while_expression)
+            (binary_expression operator: ["&&" "||"])
+        ] @branch
+        (match_block (match_arm) (match_arm) @branch)
+        "#
+        .to_string(),
+    );
+
     LanguageProfile {
         name: "Rust",
         extensions: vec!["rs"],
         language,
         queries,
+        import_match_strategy: MatchStrategy::Substring,
+        hunk_granularity: HunkGranularity::Body,
     }
 }
\ No newline at end of file
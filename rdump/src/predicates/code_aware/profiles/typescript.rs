@@ -1,4 +1,4 @@
-use super::LanguageProfile;
+use super::{HunkGranularity, LanguageProfile, MatchStrategy};
 use crate::parser::PredicateKey;
 use std::collections::HashMap;
 
@@ -21,6 +21,15 @@ pub(super) fn create_typescript_profile() -> LanguageProfile {
     queries.insert(PredicateKey::Enum, enum_query.to_string());
 
     queries.insert(PredicateKey::Func, "[ (function_declaration name: (identifier) @match) (method_definition name: (property_identifier) @match) ]".to_string());
+    queries.insert(PredicateKey::Signature, "[ (function_declaration) @match (method_definition) @match ]".to_string());
+    queries.insert(
+        PredicateKey::ReturnType,
+        r#"
+        (function_declaration return_type: (type_annotation) @match) @hunk
+        (method_definition return_type: (type_annotation) @match) @hunk
+        "#
+        .to_string(),
+    );
     queries.insert(
         PredicateKey::Import,
         "(import_statement) @match".to_string(),
@@ -30,6 +39,46 @@ pub(super) fn create_typescript_profile() -> LanguageProfile {
        "[ (call_expression function: [ (identifier) @match (member_expression property: (property_identifier) @match) ]) (new_expression constructor: [ (identifier) @match (type_identifier) @match ]) ]".to_string()
    );
 
+    // Query for environment variable reads: `process.env.X` and `process.env["X"]`.
+    queries.insert(
+        PredicateKey::Env,
+        r#"
+        (member_expression
+            object: (member_expression
+                object: (identifier) @_process
+                property: (property_identifier) @_env)
+            property: (property_identifier) @match
+            (#eq? @_process "process")
+            (#eq? @_env "env")
+        ) @hunk
+        (subscript_expression
+            object: (member_expression
+                object: (identifier) @_process
+                property: (property_identifier) @_env)
+            index: (string) @match
+            (#eq? @_process "process")
+            (#eq? @_env "env")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
+    // Query for Express-style route registrations, e.g. `app.get('/users', handler)`.
+    queries.insert(
+        PredicateKey::Route,
+        r#"
+        (call_expression
+            function: (member_expression
+                object: (identifier) @_object
+                property: (property_identifier) @_method)
+            arguments: (arguments . [(string) (template_string)] @match)
+            (#match? @_object "(?i)(app|router|server|api)$")
+            (#match? @_method "^(get|post|put|patch|delete|all|use|head|options)$")
+        ) @hunk
+        "#
+        .to_string(),
+    );
+
     queries.insert(PredicateKey::Comment, "(comment) @match".to_string());
     queries.insert(
         PredicateKey::Str,
@@ -66,10 +115,38 @@ pub(super) fn create_typescript_profile() -> LanguageProfile {
     queries.insert(PredicateKey::Hook, hook_query.to_string());
     queries.insert(PredicateKey::CustomHook, custom_hook_query.to_string());
 
+    // Query for the `complexity:` metric: functions to score (`@match`) and the
+    // branch points counted within them (`@branch`).
+    queries.insert(
+        PredicateKey::Complexity,
+        r#"
+        [
+            (function_declaration)
+            (function_expression)
+            (method_definition)
+            (arrow_function)
+        ] @match
+        [
+            (if_statement)
+            (for_statement)
+            (for_in_statement)
+            (while_statement)
+            (do_statement)
+            (switch_case)
+            (catch_clause)
+            (ternary_expression)
+            (binary_expression operator: ["&&" "||" "??"])
+        ] @branch
+        "#
+        .to_string(),
+    );
+
     LanguageProfile {
         name: "TypeScript",
         extensions: vec!["ts"],
         language,
         queries,
+        import_match_strategy: MatchStrategy::Substring,
+        hunk_granularity: HunkGranularity::Body,
     }
 }
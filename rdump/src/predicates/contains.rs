@@ -2,6 +2,7 @@ use super::PredicateEvaluator;
 use crate::evaluator::{FileContext, MatchResult};
 use crate::parser::PredicateKey;
 use anyhow::Result;
+use regex::RegexBuilder;
 use tree_sitter::Range;
 
 pub(super) struct ContainsEvaluator;
@@ -14,6 +15,9 @@ impl PredicateEvaluator for ContainsEvaluator {
         value: &str,
     ) -> Result<MatchResult> {
         let content = context.get_content()?;
+        if value.contains('\n') {
+            return Ok(MatchResult::from_ranges(multiline_hunks(content, value)?));
+        }
         let mut ranges = Vec::new();
         for (i, line) in content.lines().enumerate() {
             if line.to_lowercase().contains(&value.to_lowercase()) {
@@ -31,10 +35,49 @@ impl PredicateEvaluator for ContainsEvaluator {
                 ranges.push(range);
             }
         }
-        Ok(MatchResult::Hunks(ranges))
+        Ok(MatchResult::from_ranges(ranges))
     }
 }
 
+/// Finds a value spanning several lines, case-insensitively, in the whole content
+/// rather than line by line. Each match is reported as the whole lines it covers.
+/// A newline in the value also matches a Windows `\r\n`.
+fn multiline_hunks(content: &str, value: &str) -> Result<Vec<Range>> {
+    let pattern = value
+        .split('\n')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join("\r?\n");
+    let regex = RegexBuilder::new(&pattern).case_insensitive(true).build()?;
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    // The row of a byte is the number of lines starting at or before it, less one.
+    let row = |byte: usize| line_starts.partition_point(|&start| start <= byte) - 1;
+    Ok(regex
+        .find_iter(content)
+        .map(|m| {
+            let start_row = row(m.start());
+            let end_row = row(m.end());
+            let end_byte = line_starts
+                .get(end_row + 1)
+                .map_or(content.len(), |next| next - 1);
+            Range {
+                start_byte: line_starts[start_row],
+                end_byte,
+                start_point: tree_sitter::Point {
+                    row: start_row,
+                    column: 0,
+                },
+                end_point: tree_sitter::Point {
+                    row: end_row,
+                    column: end_byte - line_starts[end_row],
+                },
+            }
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,4 +107,33 @@ mod tests {
             .unwrap()
             .is_match());
     }
+
+    #[test]
+    fn test_contains_multiline() {
+        let file = create_temp_file("a\nb\nc\nd\n    foo();\n    BAR();\ne\n");
+        let mut context = FileContext::new(file.path().to_path_buf(), PathBuf::from("/"));
+        let result = ContainsEvaluator
+            .evaluate(&mut context, &PredicateKey::Contains, "foo();\n    bar()")
+            .unwrap();
+        let MatchResult::Hunks(hunks) = result else {
+            panic!("expected hunks");
+        };
+        assert_eq!(hunks.len(), 1);
+        // Lines 5 and 6, in full.
+        assert_eq!(hunks[0].start_point.row, 4);
+        assert_eq!(hunks[0].end_point.row, 5);
+        assert_eq!(hunks[0].start_byte, 8);
+        assert_eq!(hunks[0].end_byte, 29);
+
+        let file = create_temp_file("foo\r\nbar\r\n");
+        let mut context = FileContext::new(file.path().to_path_buf(), PathBuf::from("/"));
+        assert!(ContainsEvaluator
+            .evaluate(&mut context, &PredicateKey::Contains, "foo\nbar")
+            .unwrap()
+            .is_match());
+        assert!(!ContainsEvaluator
+            .evaluate(&mut context, &PredicateKey::Contains, "bar\nfoo")
+            .unwrap()
+            .is_match());
+    }
 }
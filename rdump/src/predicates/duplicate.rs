@@ -0,0 +1,220 @@
+use super::PredicateEvaluator;
+use crate::diagnostics;
+use crate::evaluator::{FileContext, MatchResult};
+use crate::parser::PredicateKey;
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Matches files whose content is identical to at least one other candidate file.
+/// The set of duplicates is computed up front by `find_duplicates`, since a single
+/// file cannot know whether it is a duplicate on its own.
+#[derive(Default)]
+pub struct DuplicateEvaluator {
+    duplicates: Arc<HashSet<PathBuf>>,
+}
+
+impl DuplicateEvaluator {
+    pub fn new(duplicates: HashSet<PathBuf>) -> Self {
+        DuplicateEvaluator {
+            duplicates: Arc::new(duplicates),
+        }
+    }
+}
+
+impl PredicateEvaluator for DuplicateEvaluator {
+    fn evaluate(
+        &self,
+        context: &mut FileContext,
+        _key: &PredicateKey,
+        value: &str,
+    ) -> Result<MatchResult> {
+        let wanted = match value.to_lowercase().as_str() {
+            "true" | "yes" => true,
+            "false" | "no" => false,
+            _ => {
+                return Err(anyhow!(
+                    "Invalid value for duplicate predicate: '{}'. Expected 'true' or 'false'.",
+                    value
+                ))
+            }
+        };
+        let is_duplicate = self.duplicates.contains(&context.path);
+        Ok(MatchResult::Boolean(is_duplicate == wanted))
+    }
+}
+
+/// Returns the subset of `paths` whose content appears more than once.
+/// Files are first grouped by size so that only same-sized files are read and hashed,
+/// and files with the same hash are compared byte by byte. A file that can't be read
+/// is left out with a warning.
+pub fn find_duplicates(paths: &[PathBuf]) -> HashSet<PathBuf> {
+    let mut by_size: HashMap<u64, Vec<&PathBuf>> = HashMap::new();
+    for path in paths {
+        match fs::metadata(path) {
+            Ok(metadata) => by_size.entry(metadata.len()).or_default().push(path),
+            Err(e) => warn_unreadable(path, &e),
+        }
+    }
+
+    let same_size: Vec<&PathBuf> = by_size
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .collect();
+
+    let hashed: Vec<(u64, &PathBuf)> = same_size
+        .par_iter()
+        .filter_map(|path| match fs::read(path) {
+            Ok(bytes) => {
+                let mut hasher = DefaultHasher::new();
+                bytes.hash(&mut hasher);
+                Some((hasher.finish(), *path))
+            }
+            Err(e) => {
+                warn_unreadable(path, &e);
+                None
+            }
+        })
+        .collect();
+
+    let mut by_hash: HashMap<u64, Vec<&PathBuf>> = HashMap::new();
+    for (hash, path) in hashed {
+        by_hash.entry(hash).or_default().push(path);
+    }
+
+    let same_hash: Vec<Vec<&PathBuf>> = by_hash
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect();
+    same_hash
+        .into_par_iter()
+        .flat_map_iter(identical_files)
+        .cloned()
+        .collect()
+}
+
+/// The files in `group`, which all have the same hash, whose content is really the
+/// same as another's, since a 64-bit hash can collide.
+fn identical_files(group: Vec<&PathBuf>) -> Vec<&PathBuf> {
+    // Each distinct content, and the files that have it.
+    let mut contents: Vec<(Vec<u8>, Vec<&PathBuf>)> = Vec::new();
+    for path in group {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn_unreadable(path, &e);
+                continue;
+            }
+        };
+        match contents.iter_mut().find(|(content, _)| *content == bytes) {
+            Some((_, paths)) => paths.push(path),
+            None => contents.push((bytes, vec![path])),
+        }
+    }
+    contents
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .flat_map(|(_, paths)| paths)
+        .collect()
+}
+
+fn warn_unreadable(path: &Path, error: &std::io::Error) {
+    diagnostics::warn(format!(
+        "skipped {} in the duplicate check: {error}",
+        path.display()
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_duplicates() -> Result<()> {
+        let dir = tempdir()?;
+        let a = dir.path().join("a.sql");
+        let b = dir.path().join("b.sql");
+        let c = dir.path().join("c.sql");
+        let d = dir.path().join("d.sql");
+        fs::write(&a, "SELECT 1;")?;
+        fs::write(&b, "SELECT 1;")?;
+        // Same size as `a` and `b`, different content.
+        fs::write(&c, "SELECT 2;")?;
+        fs::write(&d, "SELECT * FROM users;")?;
+
+        let duplicates = find_duplicates(&[a.clone(), b.clone(), c.clone(), d.clone()]);
+        assert_eq!(duplicates.len(), 2);
+        assert!(duplicates.contains(&a));
+        assert!(duplicates.contains(&b));
+        assert!(!duplicates.contains(&c));
+        assert!(!duplicates.contains(&d));
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_hash_is_confirmed_byte_by_byte() -> Result<()> {
+        let dir = tempdir()?;
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        let c = dir.path().join("c.txt");
+        fs::write(&a, "same")?;
+        fs::write(&b, "diff")?;
+        fs::write(&c, "same")?;
+
+        // As if all three had collided on the same hash.
+        let identical = identical_files(vec![&a, &b, &c]);
+        assert_eq!(identical, [&a, &c]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unreadable_files_are_skipped() -> Result<()> {
+        let dir = tempdir()?;
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "same")?;
+        fs::write(&b, "same")?;
+        let missing = dir.path().join("missing.txt");
+
+        let duplicates = find_duplicates(&[a.clone(), missing, b.clone()]);
+        assert_eq!(duplicates, HashSet::from([a, b]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_evaluator() -> Result<()> {
+        let dir = tempdir()?;
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        let c = dir.path().join("c.txt");
+        fs::write(&a, "same")?;
+        fs::write(&b, "same")?;
+        fs::write(&c, "unique")?;
+
+        let evaluator =
+            DuplicateEvaluator::new(find_duplicates(&[a.clone(), b.clone(), c.clone()]));
+        let mut ctx_a = FileContext::new(a, dir.path().to_path_buf());
+        let mut ctx_c = FileContext::new(c, dir.path().to_path_buf());
+
+        assert!(evaluator
+            .evaluate(&mut ctx_a, &PredicateKey::Duplicate, "true")?
+            .is_match());
+        assert!(!evaluator
+            .evaluate(&mut ctx_c, &PredicateKey::Duplicate, "true")?
+            .is_match());
+        assert!(evaluator
+            .evaluate(&mut ctx_c, &PredicateKey::Duplicate, "false")?
+            .is_match());
+        assert!(evaluator
+            .evaluate(&mut ctx_a, &PredicateKey::Duplicate, "maybe")
+            .is_err());
+        Ok(())
+    }
+}
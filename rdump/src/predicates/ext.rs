@@ -16,7 +16,13 @@ impl PredicateEvaluator for ExtEvaluator {
             .extension()
             .and_then(|s| s.to_str())
             .unwrap_or("");
-        Ok(MatchResult::Boolean(file_ext.eq_ignore_ascii_case(value)))
+        // A comma-separated list (e.g. `ext:"rs,py,go"`) matches any of its extensions.
+        Ok(MatchResult::Boolean(
+            value
+                .split(',')
+                .map(str::trim)
+                .any(|ext| file_ext.eq_ignore_ascii_case(ext)),
+        ))
     }
 }
 
@@ -60,4 +66,31 @@ mod tests {
             "Dotfiles should have no extension"
         );
     }
+
+    #[test]
+    fn test_ext_evaluator_comma_separated() {
+        let evaluator = ExtEvaluator;
+        for file in ["main.rs", "script.py", "server.go"] {
+            let mut context = FileContext::new(PathBuf::from(file), PathBuf::from("/"));
+            assert!(
+                evaluator
+                    .evaluate(&mut context, &PredicateKey::Ext, "rs,py,go")
+                    .unwrap()
+                    .is_match(),
+                "{file} should match"
+            );
+        }
+
+        let mut context_js = FileContext::new(PathBuf::from("app.js"), PathBuf::from("/"));
+        assert!(!evaluator
+            .evaluate(&mut context_js, &PredicateKey::Ext, "rs,py,go")
+            .unwrap()
+            .is_match());
+
+        // Whitespace around the commas is ignored.
+        assert!(evaluator
+            .evaluate(&mut context_js, &PredicateKey::Ext, "rs, js")
+            .unwrap()
+            .is_match());
+    }
 }
@@ -1,7 +1,9 @@
 use anyhow::{anyhow, Result};
-use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono::{Days, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 use std::time::{Duration, SystemTime};
 
+/// Compares a file size against a query such as `>10kb`, `<0.5pb` or `=4096`.
+/// A query without an operator (e.g. `1000`) is an exact byte match, same as `=1000`.
 pub(super) fn parse_and_compare_size(file_size: u64, query: &str) -> Result<bool> {
     let query = query.trim();
     let (op, size_str) = if query.starts_with(['>', '<', '=']) {
@@ -10,10 +12,22 @@ pub(super) fn parse_and_compare_size(file_size: u64, query: &str) -> Result<bool
         ("=", query)
     };
 
-    let size_str = size_str.trim().to_lowercase();
+    let target_size_bytes = parse_size(size_str)?;
+
+    match op {
+        ">" => Ok(file_size > target_size_bytes),
+        "<" => Ok(file_size < target_size_bytes),
+        "=" => Ok(file_size == target_size_bytes),
+        _ => Err(anyhow!("Invalid size operator: {}", op)),
+    }
+}
+
+/// Parses a size such as `10kb`, `0.5mb` or `4096` (bytes) into bytes.
+pub fn parse_size(size: &str) -> Result<u64> {
+    let size_str = size.trim().to_lowercase();
     let (num_str, unit) = size_str.split_at(
         size_str
-            .find(|c: char| !c.is_digit(10) && c != '.')
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
             .unwrap_or(size_str.len()),
     );
 
@@ -23,16 +37,38 @@ pub(super) fn parse_and_compare_size(file_size: u64, query: &str) -> Result<bool
         "kb" | "k" => 1024.0,
         "mb" | "m" => 1024.0 * 1024.0,
         "gb" | "g" => 1024.0 * 1024.0 * 1024.0,
+        "tb" | "t" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        "pb" | "p" => 1024.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0,
         _ => return Err(anyhow!("Invalid size unit: {}", unit)),
     };
 
-    let target_size_bytes = (num * multiplier) as u64;
+    Ok((num * multiplier) as u64)
+}
+
+/// Compares a count (e.g. a function's complexity) against a query such as `>15`,
+/// `<=3` or `10`. A query without an operator is an exact match.
+pub(super) fn parse_and_compare_count(count: u64, query: &str) -> Result<bool> {
+    let query = query.trim();
+    let (op, num_str) = if query.starts_with(">=") || query.starts_with("<=") {
+        query.split_at(2)
+    } else if query.starts_with(['>', '<', '=']) {
+        query.split_at(1)
+    } else {
+        ("=", query)
+    };
+
+    let target = num_str
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| anyhow!("Invalid number: '{}'", num_str.trim()))?;
 
     match op {
-        ">" => Ok(file_size > target_size_bytes),
-        "<" => Ok(file_size < target_size_bytes),
-        "=" => Ok(file_size == target_size_bytes),
-        _ => Err(anyhow!("Invalid size operator: {}", op)),
+        ">" => Ok(count > target),
+        "<" => Ok(count < target),
+        ">=" => Ok(count >= target),
+        "<=" => Ok(count <= target),
+        "=" => Ok(count == target),
+        _ => Err(anyhow!("Invalid comparison operator: {}", op)),
     }
 }
 
@@ -45,7 +81,10 @@ pub(super) fn parse_and_compare_time(modified_time: SystemTime, query: &str) ->
     };
     let time_str = time_str.trim();
 
-    let threshold_time = if let Ok(duration) = parse_relative_time(time_str) {
+    let named_time = parse_named_time(time_str);
+    let threshold_time = if let Ok(named) = named_time {
+        named
+    } else if let Ok(duration) = parse_relative_time(time_str) {
         now.checked_sub(duration)
             .ok_or_else(|| anyhow!("Time calculation underflow"))?
     } else if let Ok(datetime) = parse_absolute_time(time_str) {
@@ -58,8 +97,9 @@ pub(super) fn parse_and_compare_time(modified_time: SystemTime, query: &str) ->
         ">" => Ok(modified_time > threshold_time),
         "<" => Ok(modified_time < threshold_time),
         "=" => {
-            // For date-only comparisons, check if the modified time is within the same day
-            if time_str.len() == 10 {
+            // For date-only comparisons (including named aliases like `today`),
+            // check if the modified time is within the same day
+            if time_str.len() == 10 || named_time.is_ok() {
                 let modified_local = chrono::DateTime::<Local>::from(modified_time);
                 let threshold_local = chrono::DateTime::<Local>::from(threshold_time);
                 Ok(modified_local.date_naive() == threshold_local.date_naive())
@@ -71,10 +111,32 @@ pub(super) fn parse_and_compare_time(modified_time: SystemTime, query: &str) ->
     }
 }
 
+/// Resolves a named alias (`today`, `yesterday`, `last-week`, `last-month`, `last-year`)
+/// to local midnight of the corresponding day.
+fn parse_named_time(time_str: &str) -> Result<SystemTime> {
+    let today = Local::now().date_naive();
+    let date = match time_str.to_lowercase().as_str() {
+        "today" => Some(today),
+        "yesterday" => today.checked_sub_days(Days::new(1)),
+        "last-week" => today.checked_sub_days(Days::new(7)),
+        "last-month" => today.checked_sub_months(Months::new(1)),
+        "last-year" => today.checked_sub_months(Months::new(12)),
+        _ => return Err(anyhow!("Unknown named time: '{}'", time_str)),
+    }
+    .ok_or_else(|| anyhow!("Time calculation underflow"))?;
+
+    let midnight = date.and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap());
+    Ok(Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .ok_or_else(|| anyhow!("Failed to convert to local time"))?
+        .into())
+}
+
 fn parse_relative_time(time_str: &str) -> Result<Duration> {
     let (num_str, unit) = time_str.split_at(
         time_str
-            .find(|c: char| !c.is_digit(10))
+            .find(|c: char| !c.is_ascii_digit())
             .unwrap_or(time_str.len()),
     );
     let num = num_str.parse::<u64>()?;
@@ -15,46 +15,63 @@ impl PredicateEvaluator for InPathEvaluator {
         _key: &PredicateKey,
         value: &str,
     ) -> Result<MatchResult> {
-        // Check for glob metacharacters to switch between logic paths.
-        if value.contains('*') || value.contains('?') || value.contains('[') || value.contains('{')
-        {
-            // --- Wildcard Logic ---
-            let glob = Glob::new(value)?.compile_matcher();
-
-            if let Some(parent) = context.path.parent() {
-                // Strip the root from the parent path to make the match relative.
-                let relative_parent = parent.strip_prefix(&context.root).unwrap_or(parent);
-                Ok(MatchResult::Boolean(glob.is_match(relative_parent)))
-            } else {
-                Ok(MatchResult::Boolean(false))
+        // A comma-separated list (e.g. `in:"src,tests"`) matches if the file is in any
+        // of the directories. Brace globs like `{src,tests}` contain commas of their own,
+        // so they are passed through whole.
+        if value.contains(',') && !value.contains('{') {
+            for dir in value.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+                if is_in_dir(context, dir)? {
+                    return Ok(MatchResult::Boolean(true));
+                }
             }
+            return Ok(MatchResult::Boolean(false));
+        }
+        Ok(MatchResult::Boolean(is_in_dir(context, value)?))
+    }
+}
+
+/// Checks whether the file is in the directory described by `value`, which is either
+/// an exact (non-recursive) path or a glob relative to the search root.
+fn is_in_dir(context: &FileContext, value: &str) -> Result<bool> {
+    // Check for glob metacharacters to switch between logic paths.
+    if value.contains('*') || value.contains('?') || value.contains('[') || value.contains('{')
+    {
+        // --- Wildcard Logic ---
+        let glob = Glob::new(value)?.compile_matcher();
+
+        if let Some(parent) = context.path.parent() {
+            // Strip the root from the parent path to make the match relative.
+            let relative_parent = parent.strip_prefix(&context.root).unwrap_or(parent);
+            Ok(glob.is_match(relative_parent))
         } else {
-            // --- Non-recursive Exact-Path Logic ---
-            let target_dir = PathBuf::from(value);
-            let absolute_target_dir = if target_dir.is_absolute() {
-                target_dir
-            } else {
-                context.root.join(target_dir)
-            };
+            Ok(false)
+        }
+    } else {
+        // --- Non-recursive Exact-Path Logic ---
+        let target_dir = PathBuf::from(value);
+        let absolute_target_dir = if target_dir.is_absolute() {
+            target_dir
+        } else {
+            context.root.join(target_dir)
+        };
 
-            if !absolute_target_dir.is_dir() {
-                return Ok(MatchResult::Boolean(false));
-            }
+        if !absolute_target_dir.is_dir() {
+            return Ok(false);
+        }
+
+        let canonical_target = match dunce::canonicalize(&absolute_target_dir) {
+            Ok(path) => path,
+            Err(_) => return Ok(false),
+        };
 
-            let canonical_target = match dunce::canonicalize(&absolute_target_dir) {
+        if let Some(file_parent) = context.path.parent() {
+            let canonical_file_parent = match dunce::canonicalize(file_parent) {
                 Ok(path) => path,
-                Err(_) => return Ok(MatchResult::Boolean(false)),
+                Err(_) => return Ok(false),
             };
-
-            if let Some(file_parent) = context.path.parent() {
-                let canonical_file_parent = match dunce::canonicalize(file_parent) {
-                    Ok(path) => path,
-                    Err(_) => return Ok(MatchResult::Boolean(false)),
-                };
-                Ok(MatchResult::Boolean(canonical_file_parent == canonical_target))
-            } else {
-                Ok(MatchResult::Boolean(false))
-            }
+            Ok(canonical_file_parent == canonical_target)
+        } else {
+            Ok(false)
         }
     }
 }
@@ -320,4 +337,60 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_in_path_evaluator_comma_separated() -> Result<()> {
+        let evaluator = InPathEvaluator;
+        let root_dir = tempdir()?;
+        let root_path = root_dir.path();
+
+        let src_dir = root_path.join("project_a").join("src");
+        let tests_dir = root_path.join("project_b").join("tests");
+        let docs_dir = root_path.join("docs");
+        fs::create_dir_all(&src_dir)?;
+        fs::create_dir_all(&tests_dir)?;
+        fs::create_dir_all(&docs_dir)?;
+
+        let main_rs = src_dir.join("main.rs");
+        fs::write(&main_rs, "")?;
+        let test_rs = tests_dir.join("test.rs");
+        fs::write(&test_rs, "")?;
+        let readme = docs_dir.join("README.md");
+        fs::write(&readme, "")?;
+
+        let mut ctx_main = FileContext::new(main_rs, root_path.to_path_buf());
+        let mut ctx_test = FileContext::new(test_rs, root_path.to_path_buf());
+        let mut ctx_readme = FileContext::new(readme, root_path.to_path_buf());
+
+        // Exact directories.
+        let value = "project_a/src, project_b/tests";
+        assert!(evaluator
+            .evaluate(&mut ctx_main, &PredicateKey::In, value)?
+            .is_match());
+        assert!(evaluator
+            .evaluate(&mut ctx_test, &PredicateKey::In, value)?
+            .is_match());
+        assert!(!evaluator
+            .evaluate(&mut ctx_readme, &PredicateKey::In, value)?
+            .is_match());
+
+        // Glob components keep their existing behavior.
+        let value = "**/src,**/tests";
+        assert!(evaluator
+            .evaluate(&mut ctx_main, &PredicateKey::In, value)?
+            .is_match());
+        assert!(evaluator
+            .evaluate(&mut ctx_test, &PredicateKey::In, value)?
+            .is_match());
+        assert!(!evaluator
+            .evaluate(&mut ctx_readme, &PredicateKey::In, value)?
+            .is_match());
+
+        // Brace alternation is still a single glob.
+        assert!(evaluator
+            .evaluate(&mut ctx_test, &PredicateKey::In, "project_{a,b}/*")?
+            .is_match());
+
+        Ok(())
+    }
 }
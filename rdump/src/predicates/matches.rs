@@ -32,7 +32,7 @@ impl PredicateEvaluator for MatchesEvaluator {
                 });
             }
         }
-        Ok(MatchResult::Hunks(ranges))
+        Ok(MatchResult::from_ranges(ranges))
     }
 }
 
@@ -1,5 +1,6 @@
 pub mod code_aware;
 pub mod contains;
+pub mod duplicate;
 pub mod ext;
 mod helpers;
 pub mod in_path;
@@ -8,16 +9,21 @@ pub mod modified;
 pub mod name;
 pub mod path;
 pub mod size;
+pub mod tag;
+
+pub use self::helpers::parse_size;
 
 use self::code_aware::CodeAwareEvaluator;
 use self::contains::ContainsEvaluator;
+use self::duplicate::DuplicateEvaluator;
 use self::ext::ExtEvaluator;
 use self::in_path::InPathEvaluator;
 use self::matches::MatchesEvaluator;
 use self::modified::ModifiedEvaluator;
 use self::name::NameEvaluator;
-use self::path::PathEvaluator;
+use self::path::{IPathEvaluator, PathEvaluator};
 use self::size::SizeEvaluator;
+use self::tag::TagEvaluator;
 use crate::evaluator::{FileContext, MatchResult};
 use crate::parser::PredicateKey;
 use anyhow::Result;
@@ -34,49 +40,113 @@ pub trait PredicateEvaluator {
     ) -> Result<MatchResult>;
 }
 
+/// Maps each predicate key to the evaluator that handles it. Library users can
+/// add their own predicates, e.g. under `PredicateKey::Other("semver".into())`,
+/// before handing the registry to `Evaluator::new`.
+#[derive(Default)]
+pub struct PredicateRegistry {
+    evaluators: HashMap<PredicateKey, Box<dyn PredicateEvaluator + Send + Sync>>,
+}
+
+impl PredicateRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `evaluator` for `key`, replacing any evaluator already registered.
+    pub fn insert<E: PredicateEvaluator + Send + Sync + 'static>(
+        &mut self,
+        key: PredicateKey,
+        evaluator: E,
+    ) {
+        self.insert_raw(key, Box::new(evaluator));
+    }
+
+    /// Like `insert`, for an evaluator that is already boxed.
+    pub fn insert_raw(
+        &mut self,
+        key: PredicateKey,
+        evaluator: Box<dyn PredicateEvaluator + Send + Sync>,
+    ) {
+        self.evaluators.insert(key, evaluator);
+    }
+
+    pub fn get(&self, key: &PredicateKey) -> Option<&(dyn PredicateEvaluator + Send + Sync)> {
+        self.evaluators.get(key).map(|evaluator| evaluator.as_ref())
+    }
+
+    pub fn contains_key(&self, key: &PredicateKey) -> bool {
+        self.evaluators.contains_key(key)
+    }
+}
+
 /// Creates a predicate registry with only the fast, metadata-based predicates.
 /// This is used for the pre-filtering pass.
-pub fn create_metadata_predicate_registry(
-) -> HashMap<PredicateKey, Box<dyn PredicateEvaluator + Send + Sync>> {
-    let mut registry: HashMap<PredicateKey, Box<dyn PredicateEvaluator + Send + Sync>> =
-        HashMap::new();
-
-    registry.insert(PredicateKey::Ext, Box::new(ExtEvaluator));
-    registry.insert(PredicateKey::Name, Box::new(NameEvaluator));
-    registry.insert(PredicateKey::Path, Box::new(PathEvaluator));
-    registry.insert(PredicateKey::In, Box::new(InPathEvaluator));
-    registry.insert(PredicateKey::Size, Box::new(SizeEvaluator));
-    registry.insert(PredicateKey::Modified, Box::new(ModifiedEvaluator));
+pub fn create_metadata_predicate_registry() -> PredicateRegistry {
+    let mut registry = PredicateRegistry::new();
+
+    registry.insert(PredicateKey::Ext, ExtEvaluator);
+    registry.insert(PredicateKey::Name, NameEvaluator);
+    // `name:` already matches case-insensitively; `iname:` is provided for symmetry with `ipath:`.
+    registry.insert(PredicateKey::IName, NameEvaluator);
+    registry.insert(PredicateKey::Path, PathEvaluator);
+    registry.insert(PredicateKey::IPath, IPathEvaluator);
+    registry.insert(PredicateKey::In, InPathEvaluator);
+    registry.insert(PredicateKey::Size, SizeEvaluator);
+    registry.insert(PredicateKey::Modified, ModifiedEvaluator);
+    // `since:` and `before:` are `modified:>` and `modified:<` under friendlier names.
+    registry.insert(PredicateKey::Since, ModifiedEvaluator);
+    registry.insert(PredicateKey::Before, ModifiedEvaluator);
 
     registry
 }
 
 /// Creates and populates the complete predicate registry.
-pub fn create_predicate_registry(
-) -> HashMap<PredicateKey, Box<dyn PredicateEvaluator + Send + Sync>> {
+pub fn create_predicate_registry() -> PredicateRegistry {
     // Start with the metadata predicates
     let mut registry = create_metadata_predicate_registry();
 
     // Add content-based predicates
-    registry.insert(PredicateKey::Contains, Box::new(ContainsEvaluator));
-    registry.insert(PredicateKey::Matches, Box::new(MatchesEvaluator));
+    registry.insert(PredicateKey::Contains, ContainsEvaluator);
+    registry.insert(PredicateKey::Matches, MatchesEvaluator);
+    // The duplicate set is only known once the candidates are collected, so
+    // `stream_search` replaces this with a populated evaluator when needed.
+    registry.insert(PredicateKey::Duplicate, DuplicateEvaluator::default());
+    // Recognizes the default markers; `stream_search` adds any configured `tags`.
+    registry.insert(PredicateKey::Tag, TagEvaluator::default());
 
     // Register the single CodeAwareEvaluator for all semantic predicate keys.
-    let code_evaluator = Box::new(CodeAwareEvaluator);
+    let code_evaluator = CodeAwareEvaluator;
     registry.insert(PredicateKey::Def, code_evaluator.clone());
     registry.insert(PredicateKey::Func, code_evaluator.clone());
+    registry.insert(PredicateKey::Signature, code_evaluator.clone());
+    registry.insert(PredicateKey::ReturnType, code_evaluator.clone());
     registry.insert(PredicateKey::Import, code_evaluator.clone());
     registry.insert(PredicateKey::Class, code_evaluator.clone());
     registry.insert(PredicateKey::Struct, code_evaluator.clone());
+    registry.insert(PredicateKey::Record, code_evaluator.clone());
+    registry.insert(PredicateKey::Sealed, code_evaluator.clone());
     registry.insert(PredicateKey::Enum, code_evaluator.clone());
     registry.insert(PredicateKey::Interface, code_evaluator.clone());
     registry.insert(PredicateKey::Trait, code_evaluator.clone());
     registry.insert(PredicateKey::Type, code_evaluator.clone());
     registry.insert(PredicateKey::Impl, code_evaluator.clone());
     registry.insert(PredicateKey::Macro, code_evaluator.clone());
+    registry.insert(PredicateKey::Module, code_evaluator.clone());
+    registry.insert(PredicateKey::Generic, code_evaluator.clone());
+    registry.insert(PredicateKey::Decorator, code_evaluator.clone());
+    registry.insert(PredicateKey::Annotation, code_evaluator.clone());
     registry.insert(PredicateKey::Comment, code_evaluator.clone());
     registry.insert(PredicateKey::Str, code_evaluator.clone());
+    registry.insert(PredicateKey::Url, code_evaluator.clone());
     registry.insert(PredicateKey::Call, code_evaluator.clone());
+    registry.insert(PredicateKey::MethodCall, code_evaluator.clone());
+    registry.insert(PredicateKey::FreeCall, code_evaluator.clone());
+    registry.insert(PredicateKey::Env, code_evaluator.clone());
+    registry.insert(PredicateKey::Route, code_evaluator.clone());
+    registry.insert(PredicateKey::Unsafe, code_evaluator.clone());
+    registry.insert(PredicateKey::Complexity, code_evaluator.clone());
+    registry.insert(PredicateKey::FuncLines, code_evaluator.clone());
     // Add React predicates
     registry.insert(PredicateKey::Component, code_evaluator.clone());
     registry.insert(PredicateKey::Element, code_evaluator.clone());
@@ -291,6 +361,36 @@ def process_data():
             .is_match());
     }
 
+    #[test]
+    fn test_import_match_strategy_per_language() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let python_path = temp_dir.path().join("script.py");
+        std::fs::write(&python_path, "import os.path\nfrom collections import OrderedDict\n")
+            .unwrap();
+        let rust_path = temp_dir.path().join("lib.rs");
+        std::fs::write(&rust_path, "use serde::Serialize;\n").unwrap();
+
+        let evaluator = CodeAwareEvaluator;
+        let is_match = |path: &std::path::PathBuf, value: &str| {
+            let mut ctx = FileContext::new(path.clone(), temp_dir.path().to_path_buf());
+            evaluator
+                .evaluate(&mut ctx, &PredicateKey::Import, value)
+                .unwrap()
+                .is_match()
+        };
+
+        // Python matches imported modules exactly.
+        assert!(!is_match(&python_path, "os"), "`import os.path` is not `import os`");
+        assert!(is_match(&python_path, "os.path"));
+        assert!(is_match(&python_path, "collections"));
+        assert!(is_match(&python_path, "collections.OrderedDict"));
+        assert!(!is_match(&python_path, "Ordered"));
+
+        // Rust matches any part of the `use` declaration.
+        assert!(is_match(&rust_path, "serde"));
+        assert!(is_match(&rust_path, "Serialize"));
+    }
+
     #[test]
     fn test_code_aware_evaluator_javascript_suite() {
         let js_code = r#"
@@ -1,30 +1,53 @@
 use super::{helpers, PredicateEvaluator};
 use crate::evaluator::{FileContext, MatchResult};
 use crate::parser::PredicateKey;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::borrow::Cow;
 
+/// Evaluates `modified:`, and its aliases `since:` (`modified:>`) and `before:`
+/// (`modified:<`).
 pub(super) struct ModifiedEvaluator;
 impl PredicateEvaluator for ModifiedEvaluator {
     fn evaluate(
         &self,
         context: &mut FileContext,
-        _key: &PredicateKey,
+        key: &PredicateKey,
         value: &str,
     ) -> Result<MatchResult> {
+        let value = match key {
+            PredicateKey::Since => with_operator(key, '>', value)?,
+            PredicateKey::Before => with_operator(key, '<', value)?,
+            _ => Cow::Borrowed(value),
+        };
         let metadata = context.path.metadata()?;
         let modified_time = metadata.modified()?;
         Ok(MatchResult::Boolean(helpers::parse_and_compare_time(
             modified_time,
-            value,
+            &value,
         )?))
     }
 }
 
+/// Prepends `op` to the value of an alias like `since:`, which may already start
+/// with it, e.g. `since:>2024-01-01`.
+fn with_operator<'a>(key: &PredicateKey, op: char, value: &'a str) -> Result<Cow<'a, str>> {
+    let value = value.strip_prefix(op).unwrap_or(value);
+    if value.starts_with(['>', '<', '=']) {
+        return Err(anyhow!(
+            "'{}:' takes a date or time without a comparison, e.g. '{}:2024-01-01'",
+            key.as_ref(),
+            key.as_ref()
+        ));
+    }
+    Ok(Cow::Owned(format!("{op}{value}")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::Write;
     use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
     use tempfile::NamedTempFile;
 
     fn create_temp_file(content: &str) -> NamedTempFile {
@@ -49,4 +72,63 @@ mod tests {
             .unwrap()
             .is_match()); // Not modified longer than 1 min ago
     }
+
+    #[test]
+    fn test_since_and_before() {
+        let file = create_temp_file("content");
+        let mut context = FileContext::new(file.path().to_path_buf(), PathBuf::from("/"));
+
+        let evaluator = ModifiedEvaluator;
+        let mut matches = |key, value| {
+            evaluator
+                .evaluate(&mut context, &key, value)
+                .unwrap()
+                .is_match()
+        };
+        assert!(matches(PredicateKey::Since, "2024-01-01"));
+        assert!(matches(PredicateKey::Since, ">2024-01-01"));
+        assert!(matches(PredicateKey::Since, "1h"));
+        assert!(!matches(PredicateKey::Before, "2024-01-01"));
+        assert!(!matches(PredicateKey::Before, "<yesterday"));
+
+        // The alias already says which way to compare.
+        let error = evaluator
+            .evaluate(&mut context, &PredicateKey::Since, "<2024-01-01")
+            .unwrap_err();
+        assert!(error.to_string().contains("'since:' takes a date"));
+    }
+
+    #[test]
+    fn test_named_time_aliases() {
+        let now = SystemTime::now();
+        let two_days_ago = now - Duration::from_secs(2 * 86400);
+
+        assert!(helpers::parse_and_compare_time(now, ">today").unwrap());
+        assert!(!helpers::parse_and_compare_time(two_days_ago, ">today").unwrap());
+        assert!(helpers::parse_and_compare_time(two_days_ago, "<yesterday").unwrap());
+        assert!(helpers::parse_and_compare_time(now, ">yesterday").unwrap());
+        assert!(helpers::parse_and_compare_time(two_days_ago, ">last-week").unwrap());
+        assert!(helpers::parse_and_compare_time(two_days_ago, ">last-month").unwrap());
+        assert!(helpers::parse_and_compare_time(two_days_ago, ">LAST-YEAR").unwrap());
+
+        // Without an operator, an alias matches anything modified on that day.
+        assert!(helpers::parse_and_compare_time(now, "today").unwrap());
+        assert!(!helpers::parse_and_compare_time(two_days_ago, "today").unwrap());
+    }
+
+    #[test]
+    fn test_modified_evaluator_named_alias() {
+        let file = create_temp_file("content");
+        let mut context = FileContext::new(file.path().to_path_buf(), PathBuf::from("/"));
+
+        let evaluator = ModifiedEvaluator;
+        assert!(evaluator
+            .evaluate(&mut context, &PredicateKey::Modified, ">today")
+            .unwrap()
+            .is_match());
+        assert!(!evaluator
+            .evaluate(&mut context, &PredicateKey::Modified, "<yesterday")
+            .unwrap()
+            .is_match());
+    }
 }
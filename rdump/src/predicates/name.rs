@@ -81,4 +81,23 @@ mod tests {
             .unwrap()
             .is_match());
     }
+
+    #[test]
+    fn test_iname_matches_like_name() {
+        let mut context =
+            FileContext::new(PathBuf::from("/home/user/README.MD"), PathBuf::from("/"));
+        let evaluator = NameEvaluator;
+        assert!(evaluator
+            .evaluate(&mut context, &PredicateKey::IName, "readme.md")
+            .unwrap()
+            .is_match());
+        assert!(evaluator
+            .evaluate(&mut context, &PredicateKey::IName, "Read*")
+            .unwrap()
+            .is_match());
+        assert!(!evaluator
+            .evaluate(&mut context, &PredicateKey::IName, "*.txt")
+            .unwrap()
+            .is_match());
+    }
 }
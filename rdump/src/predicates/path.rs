@@ -14,16 +14,37 @@ impl PredicateEvaluator for PathEvaluator {
         value: &str,
     ) -> Result<MatchResult> {
         let path_str = context.path.to_string_lossy();
+        Ok(MatchResult::Boolean(path_matches(&path_str, value)?))
+    }
+}
+
+/// A case-insensitive variant of `PathEvaluator`, for users on case-insensitive
+/// filesystems who expect `ipath:Main.rs` to match `main.rs`.
+pub(super) struct IPathEvaluator;
+
+impl PredicateEvaluator for IPathEvaluator {
+    fn evaluate(
+        &self,
+        context: &mut FileContext,
+        _key: &PredicateKey,
+        value: &str,
+    ) -> Result<MatchResult> {
+        let path_str = context.path.to_string_lossy().to_lowercase();
+        Ok(MatchResult::Boolean(path_matches(
+            &path_str,
+            &value.to_lowercase(),
+        )?))
+    }
+}
 
-        if value.contains('*') || value.contains('?') || value.contains('[') || value.contains('{')
-        {
-            // Convert glob-style pattern to a regex
-            let glob = Glob::new(value)?.compile_matcher();
-            Ok(MatchResult::Boolean(glob.is_match(path_str.as_ref())))
-        } else {
-            // Fallback to simple substring search for non-glob patterns
-            Ok(MatchResult::Boolean(path_str.contains(value)))
-        }
+fn path_matches(path_str: &str, value: &str) -> Result<bool> {
+    if value.contains('*') || value.contains('?') || value.contains('[') || value.contains('{') {
+        // Convert glob-style pattern to a regex
+        let glob = Glob::new(value)?.compile_matcher();
+        Ok(glob.is_match(path_str))
+    } else {
+        // Fallback to simple substring search for non-glob patterns
+        Ok(path_str.contains(value))
     }
 }
 
@@ -110,4 +131,41 @@ mod tests {
             .unwrap()
             .is_match());
     }
+
+    #[test]
+    fn test_ipath_evaluator_case_insensitive() {
+        let mut context = FileContext::new(
+            PathBuf::from("/home/user/Project/Src/Main.rs"),
+            PathBuf::from("/"),
+        );
+
+        // The case-sensitive `path:` does not match a differently-cased value.
+        assert!(!PathEvaluator
+            .evaluate(&mut context, &PredicateKey::Path, "project/src/main.rs")
+            .unwrap()
+            .is_match());
+        assert!(!PathEvaluator
+            .evaluate(&mut context, &PredicateKey::Path, "**/src/*.rs")
+            .unwrap()
+            .is_match());
+
+        // `ipath:` ignores case for both substrings and globs.
+        let evaluator = IPathEvaluator;
+        assert!(evaluator
+            .evaluate(&mut context, &PredicateKey::IPath, "project/src/main.rs")
+            .unwrap()
+            .is_match());
+        assert!(evaluator
+            .evaluate(&mut context, &PredicateKey::IPath, "PROJECT/SRC")
+            .unwrap()
+            .is_match());
+        assert!(evaluator
+            .evaluate(&mut context, &PredicateKey::IPath, "**/src/*.rs")
+            .unwrap()
+            .is_match());
+        assert!(!evaluator
+            .evaluate(&mut context, &PredicateKey::IPath, "**/lib/*.rs")
+            .unwrap()
+            .is_match());
+    }
 }
@@ -89,6 +89,54 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_size_evaluator_exact_bytes() -> Result<()> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("file_4kb");
+        let mut file = File::create(&file_path)?;
+        file.write_all(&[0; 4096])?;
+
+        let mut context = FileContext::new(file_path, PathBuf::from("/"));
+        let evaluator = SizeEvaluator;
+
+        assert!(evaluator
+            .evaluate(&mut context, &PredicateKey::Size, "=4096")?
+            .is_match());
+        // No operator is a synonym for `=`.
+        assert!(evaluator
+            .evaluate(&mut context, &PredicateKey::Size, "4096")?
+            .is_match());
+        assert!(evaluator
+            .evaluate(&mut context, &PredicateKey::Size, "= 4096")?
+            .is_match());
+        assert!(!evaluator
+            .evaluate(&mut context, &PredicateKey::Size, "=4095")?
+            .is_match());
+        assert!(!evaluator
+            .evaluate(&mut context, &PredicateKey::Size, "4097")?
+            .is_match());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_terabyte_and_petabyte_units() -> Result<()> {
+        let tb = 1024u64.pow(4);
+        let pb = 1024u64.pow(5);
+
+        assert!(helpers::parse_and_compare_size(2 * tb, ">1tb")?);
+        assert!(!helpers::parse_and_compare_size(tb, ">1tb")?);
+        assert!(!helpers::parse_and_compare_size(4096, ">1tb")?);
+        assert!(helpers::parse_and_compare_size(tb, "=1tb")?);
+
+        assert!(helpers::parse_and_compare_size(4096, "<0.5pb")?);
+        assert!(helpers::parse_and_compare_size(pb / 4, "<0.5pb")?);
+        assert!(!helpers::parse_and_compare_size(pb, "<0.5pb")?);
+        assert!(helpers::parse_and_compare_size(pb, "=1PB")?);
+
+        Ok(())
+    }
+
     #[test]
     fn test_size_evaluator_invalid_input() -> Result<()> {
         let dir = tempdir()?;
@@ -0,0 +1,161 @@
+use super::code_aware::find_captures;
+use super::PredicateEvaluator;
+use crate::evaluator::{FileContext, MatchResult};
+use crate::parser::PredicateKey;
+use anyhow::Result;
+use tree_sitter::{Point, Range};
+
+/// The comment markers recognized by `tag:.` out of the box.
+/// More can be added with `tags = [...]` in the config file.
+pub const DEFAULT_TAGS: &[&str] = &["TODO", "FIXME", "HACK", "XXX", "BUG", "NOTE"];
+
+/// Matches comments that *start* with a marker like `TODO:` or `FIXME(alice)`,
+/// reporting each comment together with the line of code that follows it.
+pub struct TagEvaluator {
+    tags: Vec<String>,
+}
+
+impl Default for TagEvaluator {
+    fn default() -> Self {
+        TagEvaluator {
+            tags: DEFAULT_TAGS.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+}
+
+impl TagEvaluator {
+    /// Creates an evaluator that recognizes the default markers plus `extra_tags`.
+    pub fn with_extra_tags(extra_tags: &[String]) -> Self {
+        let mut evaluator = TagEvaluator::default();
+        for tag in extra_tags {
+            let tag = tag.to_uppercase();
+            if !evaluator.tags.contains(&tag) {
+                evaluator.tags.push(tag);
+            }
+        }
+        evaluator
+    }
+}
+
+impl PredicateEvaluator for TagEvaluator {
+    fn evaluate(
+        &self,
+        context: &mut FileContext,
+        _key: &PredicateKey,
+        value: &str,
+    ) -> Result<MatchResult> {
+        let comments = match find_captures(context, &PredicateKey::Comment)? {
+            Some(comments) => comments,
+            None => return Ok(MatchResult::Boolean(false)),
+        };
+        let content = context.get_content()?;
+
+        let wanted = value.to_uppercase();
+        let mut ranges = Vec::new();
+        for (range, text) in comments {
+            let Some(marker) = leading_marker(&text) else {
+                continue;
+            };
+            let is_match = if value == "." {
+                self.tags.iter().any(|t| t == marker)
+            } else {
+                marker == wanted
+            };
+            if is_match {
+                ranges.push(extend_to_next_line(range, content));
+            }
+        }
+        Ok(MatchResult::from_ranges(ranges))
+    }
+}
+
+/// Returns the upper-case marker word a comment starts with, if any.
+/// `// TODO: x`, `# FIXME(bob)` and `/* HACK */` all qualify; `// not a todo` does not.
+fn leading_marker(comment: &str) -> Option<&str> {
+    let body = comment.trim_start_matches(|c: char| {
+        c.is_whitespace() || matches!(c, '/' | '*' | '#' | '!' | '-' | '<')
+    });
+    let end = body
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(body.len());
+    let word = &body[..end];
+    let is_marker = !word.is_empty()
+        && word.chars().any(|c| c.is_ascii_uppercase())
+        && !word.chars().any(|c| c.is_ascii_lowercase());
+    // A marker is its own word, e.g. `TODO:` or `TODO ...`, not `TODOs`.
+    let terminated = body[end..]
+        .chars()
+        .next()
+        .is_none_or(|c| c.is_whitespace() || matches!(c, ':' | '(' | '[' | '-' | '!' | '*'));
+    (is_marker && terminated).then_some(word)
+}
+
+/// Extends a comment's range to the end of the line after it, so the hunk shows
+/// the code the marker is about.
+fn extend_to_next_line(range: Range, content: &str) -> Range {
+    let rest = &content[range.end_byte..];
+    // Skip the remainder of the comment's own line, then take the next line.
+    let next_line_end = rest
+        .find('\n')
+        .map(|first| {
+            let after = &rest[first + 1..];
+            first + 1 + after.find('\n').unwrap_or(after.len())
+        })
+        .unwrap_or(rest.len());
+    let end_byte = range.end_byte + next_line_end;
+    let row = content[..end_byte].matches('\n').count();
+    let column = end_byte - content[..end_byte].rfind('\n').map_or(0, |i| i + 1);
+    Range {
+        start_byte: range.start_byte,
+        end_byte,
+        start_point: range.start_point,
+        end_point: Point { row, column },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_leading_marker() {
+        assert_eq!(leading_marker("// TODO: refactor"), Some("TODO"));
+        assert_eq!(leading_marker("# FIXME(alice) broken"), Some("FIXME"));
+        assert_eq!(leading_marker("/* HACK */"), Some("HACK"));
+        assert_eq!(leading_marker("/// NOTE"), Some("NOTE"));
+        assert_eq!(leading_marker("// we should fixme later"), None);
+        assert_eq!(leading_marker("// Todo: mixed case"), None);
+        assert_eq!(leading_marker("// TODOs are tracked elsewhere"), None);
+    }
+
+    #[test]
+    fn test_tag_evaluator() -> Result<()> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("lib.rs");
+        fs::write(
+            &file_path,
+            "// FIXME: off by one\nfn a() {}\n// Mentions fixme mid-sentence\nfn b() {}\n// PERF: slow\nfn c() {}\n",
+        )?;
+        let mut context = FileContext::new(file_path, PathBuf::from("/"));
+
+        let result = TagEvaluator::default().evaluate(&mut context, &PredicateKey::Tag, "fixme")?;
+        let MatchResult::Hunks(hunks) = result else {
+            panic!("expected hunks");
+        };
+        assert_eq!(hunks.len(), 1);
+        // The hunk covers the comment and the line after it.
+        assert_eq!(hunks[0].start_point.row, 0);
+        assert_eq!(hunks[0].end_point.row, 1);
+
+        // `PERF` is only a wildcard match once it is configured.
+        let default_any = TagEvaluator::default().evaluate(&mut context, &PredicateKey::Tag, ".")?;
+        assert!(matches!(default_any, MatchResult::Hunks(h) if h.len() == 1));
+        let configured_any = TagEvaluator::with_extra_tags(&["perf".to_string()])
+            .evaluate(&mut context, &PredicateKey::Tag, ".")?;
+        assert!(matches!(configured_any, MatchResult::Hunks(h) if h.len() == 2));
+        Ok(())
+    }
+}
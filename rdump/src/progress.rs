@@ -0,0 +1,144 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How often the progress line is redrawn.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The most characters of the current file's path shown on the progress line.
+const MAX_PATH_WIDTH: usize = 40;
+
+/// Counts scanned and matched files during a search and, when enabled, shows the
+/// counts, out of the total once it's known, and the file being evaluated on a single,
+/// continually redrawn stderr line.
+#[derive(Default)]
+pub struct Progress {
+    enabled: bool,
+    /// The line is only shown for searches of at least this many files.
+    min_total: usize,
+    scanned: AtomicUsize,
+    matched: AtomicUsize,
+    /// The number of files to evaluate, or 0 until the walk is done.
+    total: AtomicUsize,
+    current: Mutex<String>,
+    done: AtomicBool,
+    /// The width of the progress line currently on screen, or 0 if it's been cleared.
+    /// Held while drawing, and while other output is written, so the two never mix.
+    line_width: Mutex<usize>,
+}
+
+impl Progress {
+    /// A progress line that, when enabled, only appears once the search turns out to
+    /// have at least `min_total` files, so quick searches don't flicker.
+    pub fn new(enabled: bool, min_total: usize) -> Self {
+        Progress {
+            enabled,
+            min_total,
+            ..Default::default()
+        }
+    }
+
+    /// Records how many files the search will evaluate.
+    pub fn set_total(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Records the file being evaluated, to show on the line. Does nothing when
+    /// progress is disabled.
+    pub fn file_started(&self, path: &Path) {
+        if self.enabled {
+            *self.current.lock().unwrap() = path.display().to_string();
+        }
+    }
+
+    /// Records a file that has been evaluated, and whether it matched.
+    pub fn file_scanned(&self, matched: bool) {
+        self.scanned.fetch_add(1, Ordering::Relaxed);
+        if matched {
+            self.matched.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Redraws the progress line until `finish` is called, then clears it.
+    /// Meant to run on its own thread; returns immediately when progress is disabled.
+    pub fn report(&self) {
+        if !self.enabled {
+            return;
+        }
+        loop {
+            self.draw();
+            if self.done.load(Ordering::Acquire) {
+                break;
+            }
+            thread::sleep(REDRAW_INTERVAL);
+        }
+        self.suspend(|| ());
+    }
+
+    /// Stops `report`.
+    pub fn finish(&self) {
+        self.done.store(true, Ordering::Release);
+    }
+
+    /// Clears the progress line and runs `write`, e.g. to print a result, without the
+    /// line being redrawn in the middle of it. It reappears on the next redraw.
+    pub fn suspend<T>(&self, write: impl FnOnce() -> T) -> T {
+        let mut line_width = self.line_width.lock().unwrap();
+        if *line_width > 0 {
+            let _ = write!(io::stderr(), "\r{}\r", " ".repeat(*line_width));
+            *line_width = 0;
+        }
+        write()
+    }
+
+    fn draw(&self) {
+        let total = self.total.load(Ordering::Relaxed);
+        if total < self.min_total {
+            return;
+        }
+        let mut line_width = self.line_width.lock().unwrap();
+        let scanned = self.scanned.load(Ordering::Relaxed);
+        let matched = self.matched.load(Ordering::Relaxed);
+        let line = if total == 0 {
+            format!("Scanned {scanned} files, {matched} matched...")
+        } else {
+            let current = self.current.lock().unwrap();
+            format!(
+                "Scanned {scanned}/{total} files, {matched} matched... {}",
+                path_tail(&current)
+            )
+        };
+        // Pad over any longer line left from the previous draw.
+        let padding = line_width.saturating_sub(line.len());
+        let _ = write!(io::stderr(), "\r{line}{}", " ".repeat(padding));
+        let _ = io::stderr().flush();
+        *line_width = line.len() + padding;
+    }
+}
+
+/// The end of `path`, at most `MAX_PATH_WIDTH` characters, so the line doesn't wrap.
+fn path_tail(path: &str) -> String {
+    let length = path.chars().count();
+    if length <= MAX_PATH_WIDTH {
+        return path.to_string();
+    }
+    let tail: String = path.chars().skip(length - MAX_PATH_WIDTH + 1).collect();
+    format!("…{tail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_tail() {
+        assert_eq!(path_tail("./src/main.rs"), "./src/main.rs");
+        let long = format!("./{}/main.rs", "deeply/nested".repeat(5));
+        let tail = path_tail(&long);
+        assert_eq!(tail.chars().count(), MAX_PATH_WIDTH);
+        assert!(tail.starts_with('…') && tail.ends_with("nested/main.rs"));
+    }
+}
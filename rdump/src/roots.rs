@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+
+/// The root `path` was found under: the longest of `roots` it starts with, so a
+/// root nested inside another still claims its own files.
+pub fn root_of<'a>(roots: &'a [PathBuf], path: &Path) -> &'a Path {
+    roots
+        .iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+        .or(roots.first())
+        .map_or(Path::new(""), PathBuf::as_path)
+}
+
+/// `path` relative to the root it was found under, e.g. `src/main.rs`.
+pub fn relative_to_root<'a>(roots: &[PathBuf], path: &'a Path) -> &'a Path {
+    path.strip_prefix(root_of(roots, path)).unwrap_or(path)
+}
+
+/// Like `relative_to_root`, but when there are several roots the root's own name is
+/// kept, e.g. `repo-a/src/main.rs`, so files from different roots can't collide.
+pub fn unique_relative_path<'a>(roots: &[PathBuf], path: &'a Path) -> &'a Path {
+    let root = root_of(roots, path);
+    let base = match root.parent() {
+        Some(parent) if roots.len() > 1 => parent,
+        _ => root,
+    };
+    path.strip_prefix(base).unwrap_or(path)
+}
+
+/// `roots` without those inside another root, or repeating an earlier one, so that
+/// overlapping roots such as `repo-a` and `./repo-a/src` are walked only once. Roots
+/// are compared by their canonical paths; one that can't be resolved is kept as is.
+pub fn distinct_roots(roots: &[PathBuf]) -> Vec<&PathBuf> {
+    let resolved: Vec<PathBuf> = roots
+        .iter()
+        .map(|root| dunce::canonicalize(root).unwrap_or_else(|_| root.clone()))
+        .collect();
+    roots
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| {
+            !resolved.iter().enumerate().any(|(j, other)| {
+                j != i && resolved[i].starts_with(other) && (resolved[i] != *other || j < i)
+            })
+        })
+        .map(|(_, root)| root)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_of() {
+        let roots = vec![
+            PathBuf::from("../repo-a"),
+            PathBuf::from("../repo-b"),
+            PathBuf::from("../repo-b/vendor"),
+        ];
+        let path = Path::new("../repo-b/src/main.rs");
+        assert_eq!(root_of(&roots, path), Path::new("../repo-b"));
+        assert_eq!(relative_to_root(&roots, path), Path::new("src/main.rs"));
+        assert_eq!(unique_relative_path(&roots, path), Path::new("repo-b/src/main.rs"));
+
+        let vendored = Path::new("../repo-b/vendor/lib.rs");
+        assert_eq!(root_of(&roots, vendored), Path::new("../repo-b/vendor"));
+        assert_eq!(relative_to_root(&roots, vendored), Path::new("lib.rs"));
+    }
+
+    #[test]
+    fn test_single_root() {
+        let roots = vec![PathBuf::from(".")];
+        let path = Path::new("./src/main.rs");
+        assert_eq!(relative_to_root(&roots, path), Path::new("src/main.rs"));
+        assert_eq!(unique_relative_path(&roots, path), Path::new("src/main.rs"));
+        assert_eq!(root_of(&[], path), Path::new(""));
+    }
+
+    #[test]
+    fn test_distinct_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo-a");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        let roots = vec![
+            repo.join("./src"),
+            repo.clone(),
+            repo.join("src/../"),
+            dir.path().join("missing"),
+        ];
+        assert_eq!(distinct_roots(&roots), [&roots[1], &roots[3]]);
+    }
+}
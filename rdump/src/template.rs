@@ -0,0 +1,149 @@
+use anyhow::{anyhow, Result};
+
+/// A value that can be substituted into an output template with `{name}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    Path,
+    RelPath,
+    Size,
+    Modified,
+    Lines,
+    Content,
+    Hunks,
+    LineStart,
+    LineEnd,
+}
+
+impl Placeholder {
+    const ALL: [(&'static str, Placeholder); 9] = [
+        ("path", Placeholder::Path),
+        ("relpath", Placeholder::RelPath),
+        ("size", Placeholder::Size),
+        ("modified", Placeholder::Modified),
+        ("lines", Placeholder::Lines),
+        ("content", Placeholder::Content),
+        ("hunks", Placeholder::Hunks),
+        ("line_start", Placeholder::LineStart),
+        ("line_end", Placeholder::LineEnd),
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|(n, _)| *n == name).map(|(_, p)| *p)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+/// A parsed `--template` string, e.g. `- [{relpath}]({relpath}) ({size})`.
+/// `{{` and `}}` stand for literal braces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// Parses `template`, rejecting unknown placeholders and unbalanced braces.
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        return Err(anyhow!(
+                            "Unclosed '{{{}' in template. Use '{{{{' for a literal brace.",
+                            name
+                        ));
+                    }
+                    let placeholder = Placeholder::from_name(&name).ok_or_else(|| {
+                        let available: Vec<String> = Placeholder::ALL
+                            .iter()
+                            .map(|(n, _)| format!("{{{n}}}"))
+                            .collect();
+                        anyhow!(
+                            "Unknown placeholder '{{{}}}' in template. Available placeholders: {}",
+                            name,
+                            available.join(", ")
+                        )
+                    })?;
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Placeholder(placeholder));
+                }
+                '}' => {
+                    return Err(anyhow!(
+                        "Unmatched '}}' in template. Use '}}}}' for a literal brace."
+                    ))
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+
+    /// Renders the template, asking `value` for each placeholder it uses.
+    pub fn render(&self, mut value: impl FnMut(Placeholder) -> String) -> String {
+        let mut output = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => output.push_str(text),
+                Part::Placeholder(placeholder) => output.push_str(&value(*placeholder)),
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_render() {
+        let template = Template::parse("- [{relpath}]({relpath}) ({size}) {{literal}}").unwrap();
+        let rendered = template.render(|p| match p {
+            Placeholder::RelPath => "docs/a.md".to_string(),
+            Placeholder::Size => "1.2K".to_string(),
+            _ => unreachable!(),
+        });
+        assert_eq!(rendered, "- [docs/a.md](docs/a.md) (1.2K) {literal}");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Template::parse("{path} {nope}").unwrap_err().to_string();
+        assert!(err.starts_with("Unknown placeholder '{nope}'"), "{err}");
+        assert!(err.contains("{line_start}"));
+        assert!(Template::parse("{path")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Unclosed '{path'"));
+        assert!(Template::parse("a } b").is_err());
+    }
+}
@@ -0,0 +1,44 @@
+/// Estimates how many tokens an LLM tokenizer (cl100k-style BPE) would split `text`
+/// into, without shipping a vocabulary. Words cost about one token per four
+/// characters, each punctuation character costs one, and so does each line break
+/// with its indentation. Good for budgeting, not for billing.
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut word_len: usize = 0;
+    let mut in_line_break = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            word_len += 1;
+            in_line_break = false;
+            continue;
+        }
+        tokens += word_len.div_ceil(4);
+        word_len = 0;
+        if c == '\n' {
+            if !in_line_break {
+                tokens += 1;
+                in_line_break = true;
+            }
+        } else if !c.is_whitespace() {
+            tokens += 1;
+            in_line_break = false;
+        }
+    }
+    tokens + word_len.div_ceil(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("hello world"), 4);
+        // `fn`, `main`, `(`, `)`, `{`, `}`
+        assert_eq!(estimate_tokens("fn main() {}"), 6);
+        // A blank line and its indentation cost a single token.
+        assert_eq!(estimate_tokens("a\n\n    b"), 3);
+        assert!(estimate_tokens(&"word ".repeat(1000)) >= 1000);
+    }
+}
@@ -0,0 +1,389 @@
+//! The `--interactive` result browser: the matching files on the left, and a
+//! highlighted preview of the selected file's hunks on the right.
+
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::crossterm::ExecutableCommand;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::path::PathBuf;
+
+use crate::commands::search::{open_in_editor, set_clipboard_text};
+use crate::evaluator::{read_source, Hunk};
+use crate::formatter::{self, OutputOptions};
+use crate::roots::relative_to_root;
+
+const HELP: &str = "↑/↓ select  PgUp/PgDn scroll  w whole file  e edit  y copy  q quit";
+
+/// Lines moved by PgUp and PgDn.
+const SCROLL_STEP: u16 = 10;
+
+/// Opens the browser on `matching_files` and returns when the user quits. `editor`
+/// is what `e` opens the selected file with, if there is one.
+pub fn browse(
+    matching_files: Vec<(PathBuf, Vec<Hunk>)>,
+    options: OutputOptions,
+    editor: Option<String>,
+) -> Result<()> {
+    let mut browser = Browser::new(matching_files, options, editor);
+    let mut terminal = ratatui::init();
+    let result = browser.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// What a key press asks the browser to do, beyond moving around.
+#[derive(Debug, PartialEq)]
+enum Action {
+    None,
+    Edit,
+    Copy,
+    Quit,
+}
+
+/// The lines shown for the selected file.
+struct Preview {
+    lines: Vec<Line<'static>>,
+    /// The same lines without highlighting or line numbers, for `y`.
+    text: String,
+}
+
+struct Browser {
+    files: Vec<(PathBuf, Vec<Hunk>)>,
+    options: OutputOptions,
+    editor: Option<String>,
+    list: ListState,
+    /// Whether the preview shows the whole file rather than just the hunks.
+    whole_file: bool,
+    scroll: u16,
+    /// The preview of the selected file, built when first drawn.
+    preview: Option<Preview>,
+    /// Shown in place of the key help until the next key press.
+    status: Option<String>,
+}
+
+impl Browser {
+    fn new(
+        files: Vec<(PathBuf, Vec<Hunk>)>,
+        options: OutputOptions,
+        editor: Option<String>,
+    ) -> Self {
+        Browser {
+            files,
+            options,
+            editor,
+            list: ListState::default().with_selected(Some(0)),
+            whole_file: false,
+            scroll: 0,
+            preview: None,
+            status: None,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match self.handle_key(key.code) {
+                Action::None => {}
+                Action::Edit => self.edit(terminal)?,
+                Action::Copy => self.copy(),
+                Action::Quit => return Ok(()),
+            }
+        }
+    }
+
+    fn selected(&self) -> usize {
+        self.list.selected().unwrap_or(0)
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> Action {
+        self.status = None;
+        match code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.select((self.selected() + 1).min(self.files.len().saturating_sub(1)))
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected().saturating_sub(1)),
+            KeyCode::PageDown | KeyCode::Char(' ') => {
+                let last_line = self.preview.as_ref().map_or(0, |p| p.lines.len()) as u16;
+                self.scroll = (self.scroll + SCROLL_STEP).min(last_line.saturating_sub(1));
+            }
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(SCROLL_STEP),
+            KeyCode::Char('w') => {
+                self.whole_file = !self.whole_file;
+                self.scroll = 0;
+                self.preview = None;
+            }
+            KeyCode::Char('e') => return Action::Edit,
+            KeyCode::Char('y') => return Action::Copy,
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn select(&mut self, index: usize) {
+        if index != self.selected() {
+            self.list.select(Some(index));
+            self.scroll = 0;
+            self.preview = None;
+        }
+    }
+
+    fn preview(&mut self) -> &Preview {
+        let (path, hunks) = &self.files[self.selected()];
+        let whole_file = self.whole_file;
+        let options = &self.options;
+        self.preview.get_or_insert_with(|| {
+            build_preview(path, hunks, whole_file, options).unwrap_or_else(|e| Preview {
+                lines: vec![Line::from(format!("Error: {e:#}"))],
+                text: String::new(),
+            })
+        })
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main);
+
+        let items = self.files.iter().map(|(path, hunks)| {
+            let path = relative_to_root(&self.options.root, path).display();
+            match hunks.len() {
+                0 => path.to_string(),
+                n => format!("{path} ({n})"),
+            }
+        });
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(" {} files ", self.files.len())))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        let title = if self.whole_file {
+            " whole file "
+        } else {
+            " hunks "
+        };
+        let scroll = self.scroll;
+        let preview = Paragraph::new(self.preview().lines.clone())
+            .block(Block::bordered().title(title))
+            .scroll((scroll, 0));
+        frame.render_widget(preview, preview_area);
+
+        let footer_text = self.status.as_deref().unwrap_or(HELP);
+        frame.render_widget(
+            Paragraph::new(footer_text).style(Style::default().add_modifier(Modifier::DIM)),
+            footer,
+        );
+    }
+
+    /// Opens the selected file in the editor, handing it the terminal until it exits.
+    fn edit(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(editor) = self.editor.clone() else {
+            self.status =
+                Some("No editor to open. Pass --editor=<CMD>, or set $VISUAL or $EDITOR.".into());
+            return Ok(());
+        };
+        let file = self.files[self.selected()].clone();
+        terminal::disable_raw_mode()?;
+        io::stdout().execute(LeaveAlternateScreen)?;
+        let opened = open_in_editor(&editor, &[file], false);
+        io::stdout().execute(EnterAlternateScreen)?;
+        terminal::enable_raw_mode()?;
+        terminal.clear()?;
+        if let Err(e) = opened {
+            self.status = Some(format!("{e:#}"));
+        }
+        Ok(())
+    }
+
+    /// Copies what the preview shows to the clipboard.
+    fn copy(&mut self) {
+        let text = self.preview().text.clone();
+        self.status = Some(match set_clipboard_text(&text) {
+            Ok(()) => format!("Copied {} lines", text.lines().count()),
+            Err(e) => format!("{e:#}"),
+        });
+    }
+}
+
+/// The highlighted, numbered lines of `path` around its hunks, with `...` between
+/// blocks, or all of them with `whole_file` or for a whole-file match.
+fn build_preview(
+    path: &PathBuf,
+    hunks: &[Hunk],
+    whole_file: bool,
+    options: &OutputOptions,
+) -> Result<Preview> {
+    let content = read_source(path)?;
+    let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let theme = formatter::find_theme(&options.theme)?;
+    let highlighted = formatter::highlight_lines(&content, extension, theme)?;
+    let lines: Vec<&str> = content.lines().collect();
+    let blocks = if whole_file || hunks.is_empty() {
+        std::iter::once(0..highlighted.len()).collect()
+    } else {
+        formatter::get_contextual_line_ranges(
+            hunks,
+            &lines,
+            options.before_context,
+            options.after_context,
+            options.merge_gap,
+        )
+    };
+
+    let mut preview = Preview {
+        lines: Vec::new(),
+        text: String::new(),
+    };
+    for (i, block) in blocks.into_iter().enumerate() {
+        if i > 0 {
+            preview
+                .lines
+                .push(Line::from("...").style(Style::default().add_modifier(Modifier::DIM)));
+            preview.text.push_str("...\n");
+        }
+        for row in block {
+            let mut spans = vec![Span::styled(
+                format!("{: >5} | ", row + 1),
+                Style::default().fg(Color::DarkGray),
+            )];
+            for (style, text) in &highlighted[row] {
+                let color = style.foreground;
+                spans.push(Span::styled(
+                    text.trim_end_matches(['\r', '\n']).to_string(),
+                    Style::default().fg(Color::Rgb(color.r, color.g, color.b)),
+                ));
+                preview.text.push_str(text);
+            }
+            if !preview.text.ends_with('\n') {
+                preview.text.push('\n');
+            }
+            preview.lines.push(Line::from(spans));
+        }
+    }
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Format;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::fs;
+    use tempfile::tempdir;
+    use tree_sitter::{Point, Range};
+
+    fn options() -> OutputOptions {
+        OutputOptions {
+            with_line_numbers: true,
+            before_context: 1,
+            after_context: 1,
+            query: "func:target".to_string(),
+            root: vec![],
+            ..OutputOptions::new(Format::Hunks)
+        }
+    }
+
+    fn line_hunk(content: &str, row: usize) -> Hunk {
+        let start_byte: usize = content.lines().take(row).map(|l| l.len() + 1).sum();
+        let end_byte = start_byte + content.lines().nth(row).unwrap().len();
+        Range {
+            start_byte,
+            end_byte,
+            start_point: Point { row, column: 0 },
+            end_point: Point {
+                row,
+                column: end_byte - start_byte,
+            },
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_build_preview() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let content = (1..=10)
+            .map(|i| format!("// line {i}\n"))
+            .collect::<String>();
+        fs::write(&path, &content).unwrap();
+        let hunks = vec![line_hunk(&content, 2), line_hunk(&content, 7)];
+
+        let preview = build_preview(&path, &hunks, false, &options()).unwrap();
+        assert_eq!(
+            preview.text,
+            "// line 2\n// line 3\n// line 4\n...\n// line 7\n// line 8\n// line 9\n"
+        );
+        assert_eq!(preview.lines.len(), 7);
+        assert_eq!(preview.lines[0].spans[0].content, "    2 | ");
+
+        let whole = build_preview(&path, &hunks, true, &options()).unwrap();
+        assert_eq!(whole.text, content);
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let dir = tempdir().unwrap();
+        let files: Vec<_> = ["a.rs", "b.rs"]
+            .iter()
+            .map(|name| {
+                let path = dir.path().join(name);
+                fs::write(&path, "fn target() {}\n").unwrap();
+                (path, vec![])
+            })
+            .collect();
+        let mut browser = Browser::new(files, options(), None);
+
+        assert_eq!(browser.handle_key(KeyCode::Down), Action::None);
+        assert_eq!(browser.selected(), 1);
+        browser.handle_key(KeyCode::Char('j'));
+        assert_eq!(browser.selected(), 1);
+        browser.handle_key(KeyCode::Up);
+        browser.handle_key(KeyCode::Up);
+        assert_eq!(browser.selected(), 0);
+
+        browser.handle_key(KeyCode::Char('w'));
+        assert!(browser.whole_file);
+        assert_eq!(browser.handle_key(KeyCode::Char('e')), Action::Edit);
+        assert_eq!(browser.handle_key(KeyCode::Char('y')), Action::Copy);
+        assert_eq!(browser.handle_key(KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_draw() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        let content = "use std::fs;\n\nfn target() {}\n";
+        fs::write(&path, content).unwrap();
+        let mut options = options();
+        options.root = vec![dir.path().to_path_buf()];
+        let mut browser = Browser::new(vec![(path, vec![line_hunk(content, 2)])], options, None);
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+        terminal.draw(|frame| browser.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("main.rs (1)"), "{screen}");
+        assert!(screen.contains("3 | fn target() {}"), "{screen}");
+        assert!(screen.contains("q quit"), "{screen}");
+    }
+}
@@ -0,0 +1,76 @@
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+
+/// The built-in file types for `--type`, each a group of extensions.
+const BUILTIN_TYPES: &[(&str, &[&str])] = &[
+    ("javascript", &["js", "mjs", "cjs"]),
+    ("python", &["py", "pyx", "pxd"]),
+    ("rust", &["rs"]),
+    ("typescript", &["ts", "tsx"]),
+    ("web", &["html", "css", "js", "ts"]),
+];
+
+/// Every known file type by name: the built-in ones, plus those from the `[types]`
+/// config section, which replace a built-in type of the same name.
+pub fn all_types(config: &Config) -> BTreeMap<String, Vec<String>> {
+    let mut types: BTreeMap<String, Vec<String>> = BUILTIN_TYPES
+        .iter()
+        .map(|(name, extensions)| {
+            let extensions = extensions.iter().map(|ext| ext.to_string()).collect();
+            (name.to_string(), extensions)
+        })
+        .collect();
+    types.extend(config.types.clone());
+    types
+}
+
+/// The query matching files of any of the named types, e.g. `ext:"rs,py,pyx,pxd"`
+/// for `rust` and `python`.
+pub fn type_query(names: &[String], config: &Config) -> Result<String> {
+    let types = all_types(config);
+    let mut extensions: Vec<&str> = Vec::new();
+    for name in names {
+        let type_extensions = types.get(name).ok_or_else(|| {
+            anyhow!("Unknown file type '{name}'. Run `rdump type list` to see available types.")
+        })?;
+        for ext in type_extensions {
+            let ext = ext.trim_start_matches('.');
+            if !extensions.contains(&ext) {
+                extensions.push(ext);
+            }
+        }
+    }
+    Ok(format!("ext:\"{}\"", extensions.join(",")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_query() {
+        let config = Config::default();
+        assert_eq!(type_query(&["rust".into()], &config).unwrap(), "ext:\"rs\"");
+        assert_eq!(
+            type_query(&["javascript".into(), "web".into()], &config).unwrap(),
+            "ext:\"js,mjs,cjs,html,css,ts\""
+        );
+        assert!(type_query(&["cobol".into()], &config).is_err());
+    }
+
+    #[test]
+    fn test_custom_types_override_builtin_ones() {
+        let mut config = Config::default();
+        config
+            .types
+            .insert("rust".into(), vec!["rs".into(), "ron".into()]);
+        config.types.insert("docs".into(), vec![".md".into()]);
+        assert_eq!(
+            type_query(&["rust".into()], &config).unwrap(),
+            "ext:\"rs,ron\""
+        );
+        assert_eq!(type_query(&["docs".into()], &config).unwrap(), "ext:\"md\"");
+        assert_eq!(all_types(&config).len(), BUILTIN_TYPES.len() + 1);
+    }
+}
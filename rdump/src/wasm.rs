@@ -0,0 +1,103 @@
+//! JavaScript bindings for running queries against in-memory files, e.g. in a web
+//! editor. Build with `cargo build --lib --target wasm32-unknown-unknown --features wasm`.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+use wasm_bindgen::prelude::*;
+
+use crate::evaluator::{Evaluator, FileContext, MatchResult};
+use crate::parser::parse_query;
+use crate::predicates::create_predicate_registry;
+
+/// Parses `query` and returns its syntax tree as JSON, e.g.
+/// `{"predicate":["func","main"]}`.
+#[wasm_bindgen]
+pub fn parse_query_js(query: &str) -> Result<JsValue, JsError> {
+    let json = parse_query_json(query).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(JsValue::from_str(&json))
+}
+
+/// Evaluates `query` against a file with the given path and content, and returns the
+/// result as JSON: `{"matched":true,"hunks":[...]}`. Predicates that read file
+/// metadata, such as `size:` and `modified:`, aren't available.
+#[wasm_bindgen]
+pub fn evaluate_content_js(query: &str, path: &str, content: &str) -> Result<JsValue, JsError> {
+    let json =
+        evaluate_content_json(query, path, content).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(JsValue::from_str(&json))
+}
+
+fn parse_query_json(query: &str) -> Result<String> {
+    Ok(serde_json::to_string(&parse_query(query)?)?)
+}
+
+/// A match, as reported to JavaScript. `hunks` is empty for whole-file matches.
+#[derive(Serialize)]
+struct JsMatch {
+    matched: bool,
+    hunks: Vec<JsHunk>,
+}
+
+/// A matched code block, with 1-based inclusive line numbers and byte offsets.
+#[derive(Serialize)]
+struct JsHunk {
+    start_line: usize,
+    end_line: usize,
+    start_byte: usize,
+    end_byte: usize,
+    text: String,
+}
+
+fn evaluate_content_json(query: &str, path: &str, content: &str) -> Result<String> {
+    let evaluator = Evaluator::new(parse_query(query)?, create_predicate_registry());
+    let mut context = FileContext::from_content(PathBuf::from(path), content.to_string());
+    let result = match evaluator.evaluate(&mut context)? {
+        MatchResult::Boolean(matched) => JsMatch {
+            matched,
+            hunks: vec![],
+        },
+        MatchResult::Hunks(ranges) => JsMatch {
+            matched: !ranges.is_empty(),
+            hunks: ranges
+                .iter()
+                .map(|range| JsHunk {
+                    start_line: range.start_point.row + 1,
+                    end_line: range.end_point.row + 1,
+                    start_byte: range.start_byte,
+                    end_byte: range.end_byte,
+                    text: content
+                        .get(range.start_byte..range.end_byte)
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+                .collect(),
+        },
+    };
+    Ok(serde_json::to_string(&result)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_json() {
+        assert_eq!(
+            parse_query_json("func:main & !ext:py").unwrap(),
+            r#"{"logical_op":["and",{"predicate":["func","main"]},{"not":{"predicate":["ext","py"]}}]}"#
+        );
+        assert!(parse_query_json("func:main &").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_content_json() {
+        let json = evaluate_content_json("func:main", "src/main.rs", "fn main() {}\n").unwrap();
+        assert_eq!(
+            json,
+            r#"{"matched":true,"hunks":[{"start_line":1,"end_line":1,"start_byte":0,"end_byte":12,"text":"fn main() {}"}]}"#
+        );
+        let json = evaluate_content_json("ext:py", "src/main.rs", "").unwrap();
+        assert_eq!(json, r#"{"matched":false,"hunks":[]}"#);
+    }
+}
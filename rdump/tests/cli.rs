@@ -7,6 +7,12 @@ use std::io::Write;
 use std::process::Command; // Lets us run other programs
 use tempfile::tempdir; // Create temporary directories for testing
 
+mod common;
+use common::{
+    search_paths, setup_files_from_project, setup_project_with, setup_repos_project,
+    setup_sample_project, setup_sort_project, setup_types_project,
+};
+
 // --- Helper Functions ---
 
 /// A helper to set up a temporary directory with a predictable file structure for tests.
@@ -214,6 +220,41 @@ fn test_file_discovery_flags() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_min_depth_skips_shallow_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path();
+    fs::create_dir_all(root.join("src/nested"))?;
+    fs::write(root.join("top.txt"), "")?;
+    fs::write(root.join("src/inner.txt"), "")?;
+    fs::write(root.join("src/nested/deep.txt"), "")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--format=paths", "--min-depth", "2", "--max-depth", "2"])
+        .arg("ext:txt")
+        .assert()
+        .success()
+        .stdout("./src/inner.txt\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--format=paths", "--min-depth", "1", "--max-depth", "1"])
+        .arg("ext:txt")
+        .assert()
+        .success()
+        .stdout("./top.txt\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--format=paths", "--min-depth", "2", "ext:txt"])
+        .assert()
+        .success()
+        .stdout("./src/inner.txt\n./src/nested/deep.txt\n");
+
+    Ok(())
+}
+
 #[test]
 fn test_output_formatting_flags() -> Result<(), Box<dyn std::error::Error>> {
     let (_dir, root) = setup_advanced_test_dir();
@@ -366,6 +407,80 @@ fn test_preset_lifecycle() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_preset_export_and_import() -> Result<(), Box<dyn std::error::Error>> {
+    let (dir, fake_home, project_dir) = setup_preset_test_env();
+    let rdump = |home: &std::path::Path| {
+        let mut cmd = Command::cargo_bin("rdump").unwrap();
+        cmd.env("RDUMP_TEST_CONFIG_DIR", home).current_dir(&project_dir);
+        cmd
+    };
+    fs::create_dir_all(fake_home.join("rdump"))?;
+    fs::write(
+        fake_home.join("rdump/config.toml"),
+        "[presets]\nrust = \"ext:rs\"\ndocs = \"ext:md\"\n",
+    )?;
+
+    // Export one preset from the first config, in both formats.
+    let exported = rdump(&fake_home)
+        .args(["preset", "export", "--names", "rust"])
+        .output()?;
+    assert!(exported.status.success());
+    assert_eq!(String::from_utf8(exported.stdout.clone())?, "[presets]\nrust = \"ext:rs\"\n");
+    let export_path = dir.path().join("shared.toml");
+    fs::write(&export_path, &exported.stdout)?;
+    let json = rdump(&fake_home)
+        .args(["preset", "export", "--format", "json"])
+        .output()?;
+    let json_path = dir.path().join("shared.json");
+    fs::write(&json_path, &json.stdout)?;
+
+    // Import it into a fresh config, where it works in a search.
+    let fresh_home = dir.path().join("fresh");
+    rdump(&fresh_home)
+        .args(["preset", "import"])
+        .arg(&export_path)
+        .assert()
+        .success();
+    rdump(&fresh_home)
+        .args(["preset", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust : ext:rs"))
+        .stdout(predicate::str::contains("docs").not());
+    rdump(&fresh_home)
+        .args(["search", "--format=paths", "--preset", "rust"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("main.rs"))
+        .stdout(predicate::str::contains("main.toml").not());
+
+    // A preset with a different query is a conflict unless --overwrite is given.
+    rdump(&fresh_home)
+        .args(["preset", "add", "rust", "ext:rs & !name:main.rs"])
+        .assert()
+        .success();
+    rdump(&fresh_home)
+        .args(["preset", "import"])
+        .arg(&json_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Pass --overwrite"));
+    rdump(&fresh_home)
+        .args(["preset", "import", "--overwrite"])
+        .arg(&json_path)
+        .assert()
+        .success();
+    rdump(&fresh_home)
+        .args(["preset", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust : ext:rs\n"))
+        .stdout(predicate::str::contains("docs : ext:md"));
+
+    Ok(())
+}
+
 #[test]
 fn test_search_and_preset_interaction() -> Result<(), Box<dyn std::error::Error>> {
     let (_dir, fake_home, project_dir) = setup_preset_test_env();
@@ -456,6 +571,138 @@ app = "ext:toml""#;
     Ok(())
 }
 
+#[test]
+fn test_local_preset_extends_global() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, fake_home, project_dir) = setup_preset_test_env();
+    fs::write(project_dir.join("lib.rs"), "pub fn helper() {}")?;
+
+    let config_dir = fake_home.join("rdump");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(config_dir.join("config.toml"), "[presets]\nrust = \"ext:rs\"\n")?;
+    fs::write(
+        project_dir.join(".rdump.toml"),
+        "[presets]\nentry = { extends = \"rust\", query = \"func:main\" }\n",
+    )?;
+
+    Command::cargo_bin("rdump")?
+        .env("RDUMP_TEST_CONFIG_DIR", &fake_home)
+        .current_dir(&project_dir)
+        .args(["search", "--format=paths", "-p", "entry"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("main.rs"))
+        .stdout(predicate::str::contains("lib.rs").not())
+        .stdout(predicate::str::contains("main.toml").not());
+
+    Command::cargo_bin("rdump")?
+        .env("RDUMP_TEST_CONFIG_DIR", &fake_home)
+        .current_dir(&project_dir)
+        .args(["preset", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(ext:rs) & (func:main)"));
+
+    Ok(())
+}
+
+#[test]
+fn test_preset_inheritance_cycle_is_an_error() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, fake_home, project_dir) = setup_preset_test_env();
+    fs::write(
+        project_dir.join(".rdump.toml"),
+        r#"[presets]
+a = { extends = "b", query = "ext:rs" }
+b = { extends = "a", query = "ext:toml" }
+"#,
+    )?;
+
+    Command::cargo_bin("rdump")?
+        .env("RDUMP_TEST_CONFIG_DIR", &fake_home)
+        .current_dir(&project_dir)
+        .args(["search", "-p", "a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Preset inheritance cycle"));
+
+    Ok(())
+}
+
+#[test]
+fn test_config_defaults_apply_unless_overridden() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, fake_home, project_dir) = setup_preset_test_env();
+    fs::write(
+        project_dir.join(".rdump.toml"),
+        "[defaults]\nformat = \"paths\"\n",
+    )?;
+
+    Command::cargo_bin("rdump")?
+        .env("RDUMP_TEST_CONFIG_DIR", &fake_home)
+        .current_dir(&project_dir)
+        .args(["search", "ext:rs"])
+        .assert()
+        .success()
+        .stdout("./main.rs\n");
+
+    // A flag on the command line wins over the project default.
+    Command::cargo_bin("rdump")?
+        .env("RDUMP_TEST_CONFIG_DIR", &fake_home)
+        .current_dir(&project_dir)
+        .args(["search", "--format=cat", "ext:rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fn main() {}"))
+        .stdout(predicate::str::contains("./main.rs").not());
+
+    // Even a flag at its built-in default, and a boolean turned back off.
+    fs::write(
+        project_dir.join(".rdump.toml"),
+        "[defaults]\nformat = \"paths\"\nhidden = true\n",
+    )?;
+    fs::write(project_dir.join(".hidden.rs"), "fn hidden() {}\n")?;
+    Command::cargo_bin("rdump")?
+        .env("RDUMP_TEST_CONFIG_DIR", &fake_home)
+        .current_dir(&project_dir)
+        .args(["search", "--format=hunks", "--hidden=false", "ext:rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("File: ./main.rs"))
+        .stdout(predicate::str::contains(".hidden.rs").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_config_init_writes_starter_file() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, fake_home, project_dir) = setup_preset_test_env();
+
+    Command::cargo_bin("rdump")?
+        .env("RDUMP_TEST_CONFIG_DIR", &fake_home)
+        .current_dir(&project_dir)
+        .args(["config", "init"])
+        .assert()
+        .success();
+    let text = fs::read_to_string(project_dir.join(".rdump.toml"))?;
+    assert!(text.contains("[defaults]\n# format = \"markdown\""));
+
+    // The commented-out file changes nothing, and isn't overwritten by accident.
+    Command::cargo_bin("rdump")?
+        .env("RDUMP_TEST_CONFIG_DIR", &fake_home)
+        .current_dir(&project_dir)
+        .args(["search", "--format=paths", "ext:rs"])
+        .assert()
+        .success()
+        .stdout("./main.rs\n");
+    Command::cargo_bin("rdump")?
+        .env("RDUMP_TEST_CONFIG_DIR", &fake_home)
+        .current_dir(&project_dir)
+        .args(["config", "init"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("use --force"));
+
+    Ok(())
+}
+
 #[test]
 fn test_preset_error_handling() -> Result<(), Box<dyn std::error::Error>> {
     let (_dir, fake_home, _project_dir) = setup_preset_test_env();
@@ -573,4 +820,877 @@ fn test_search_implicit_and_with_negation_fails() -> Result<(), Box<dyn std::err
         .failure()
         .stderr(predicate::str::contains("missing logical operator"));
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn test_environment_variables_drive_search() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let project = dir.path().join("project");
+    fs::create_dir(&project)?;
+    fs::write(project.join("main.rs"), "fn main() {}")?;
+    fs::write(project.join("notes.md"), "# notes")?;
+
+    // The query, root and format all come from the environment.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .env("RDUMP_QUERY", "ext:rs")
+        .env("RDUMP_ROOT", &project)
+        .env("RDUMP_FORMAT", "paths")
+        .env("RDUMP_COLOR", "never")
+        .arg("search")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("main.rs"))
+        .stdout(predicate::str::contains("notes.md").not())
+        .stdout(predicate::str::contains("fn main").not());
+
+    // Command-line arguments take precedence.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .env("RDUMP_QUERY", "ext:rs")
+        .env("RDUMP_ROOT", &project)
+        .env("RDUMP_FORMAT", "paths")
+        .args(["search", "--format=cat", "ext:md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# notes"))
+        .stdout(predicate::str::contains("main.rs").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_rdump_no_ignore_environment_variable() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join(".gitignore"), "ignored.rs\n")?;
+    fs::create_dir(dir.path().join(".git"))?;
+    fs::write(dir.path().join("ignored.rs"), "fn hidden() {}")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "ext:rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ignored.rs").not());
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .env("RDUMP_NO_IGNORE", "true")
+        .args(["search", "--format=paths", "ext:rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ignored.rs"));
+
+    Ok(())
+}
+
+#[test]
+fn test_progress_flags() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("a.rs"), "fn a() {}")?;
+    fs::write(dir.path().join("b.rs"), "fn b() {}")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--no-progress", "--format=paths", "func:a"])
+        .assert()
+        .success()
+        .stdout("./a.rs\n")
+        .stderr(predicate::str::contains("\r").not());
+
+    // The progress line goes to stderr and is cleared when the search ends.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--progress", "--format=paths", "func:a"])
+        .assert()
+        .success()
+        .stdout("./a.rs\n")
+        .stderr(predicate::str::contains("\rScanned "))
+        .stderr(predicate::str::ends_with("\r"));
+
+    Ok(())
+}
+
+// Whether the pager is used at all needs a terminal, which `test_pager_command`
+// covers; this checks how its command is parsed.
+#[test]
+fn test_pager_leaves_the_query_alone() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("a.rs"), "fn a() {}")?;
+    fs::write(dir.path().join("b.py"), "def b(): pass")?;
+
+    // The query after a bare --pager isn't taken for the pager command.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "--pager", "ext:rs"])
+        .assert()
+        .success()
+        .stdout("./a.rs\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "--pager=less -R", "ext:py"])
+        .assert()
+        .success()
+        .stdout("./b.py\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_dry_run_editor_prints_commands() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("a.rs"), "// a\n\nfn a() {}\n")?;
+    fs::write(dir.path().join("b.rs"), "fn b() {}\n")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .env_remove("VISUAL")
+        .env("EDITOR", "vim")
+        .args(["search", "--dry-run-editor", "--format=paths", "func:a | func:b"])
+        .assert()
+        .success()
+        .stdout("./a.rs\n./b.rs\nvim +3 ./a.rs\nvim +1 ./b.rs\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .env("VISUAL", "nvim")
+        .args(["search", "--editor=code", "--dry-run-editor", "--format=paths"])
+        .arg("func:a | func:b")
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("code --goto ./a.rs:3 ./b.rs:1\n"));
+
+    // The query after a bare --editor isn't taken for the editor command.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .env("VISUAL", "nvim")
+        .args(["search", "--dry-run-editor", "--format=paths", "--editor", "func:b"])
+        .assert()
+        .success()
+        .stdout("./b.rs\nnvim +1 ./b.rs\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .env_remove("VISUAL")
+        .env_remove("EDITOR")
+        .args(["search", "--dry-run-editor", "func:a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No editor to open"));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_exclude() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::create_dir(dir.path().join("tests"))?;
+    fs::write(dir.path().join("api.rs"), "fn handler() {}\n")?;
+    fs::write(dir.path().join("api_mock.rs"), "fn handler() {}\n")?;
+    fs::write(dir.path().join("tests/api.rs"), "fn handler() {}\n")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "func:handler"])
+        .args(["-x", "in:tests | name:*_mock.rs"])
+        .assert()
+        .success()
+        .stdout("./api.rs\n");
+
+    // Repeated excludes each remove their own files.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "func:handler"])
+        .args(["--exclude", "in:tests", "--exclude", "name:*_mock.rs"])
+        .assert()
+        .success()
+        .stdout("./api.rs\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "func:handler", "-x", "in:tests &"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to parse the --exclude query 'in:tests &'",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_quiet() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("a.rs"), "fn a() {}\n")?;
+    fs::write(dir.path().join("b.rs"), "fn b() {}\n")?;
+    fs::write(dir.path().join("c.rs"), "fn c() {}\n".repeat(100))?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=hunks", "--color=never", "func:a | func:b"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("File: "))
+        .stdout(predicate::str::contains("---"));
+
+    // Only the hunks, and nothing on stderr, not even the --max-tokens summary.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=hunks", "--color=never", "--quiet"])
+        .args(["--max-tokens", "50", "ext:rs"])
+        .assert()
+        .success()
+        .stdout("fn a() {}\nfn b() {}\n")
+        .stderr("");
+
+    // --verbose undoes an earlier -q.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--color=never", "-q", "--verbose", "--max-tokens", "50", "ext:rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("File: "))
+        .stderr(predicate::str::contains("Skipped 1 file(s)"));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_invert_match() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("a.rs"), "unsafe fn unsafe_fn() {}\n")?;
+    fs::write(dir.path().join("b.rs"), "fn safe_fn() {}\n")?;
+    fs::write(dir.path().join("c.py"), "def safe_fn(): pass\n")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "--invert-match", "func:unsafe_fn & ext:rs"])
+        .assert()
+        .success()
+        .stdout("./b.rs\n./c.py\n");
+
+    // --type isn't inverted.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "-v", "func:unsafe_fn", "--type=rust"])
+        .assert()
+        .success()
+        .stdout("./b.rs\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--invert", "--format=paths", "func:safe_fn"])
+        .assert()
+        .success()
+        .stdout("./a.rs\n");
+
+    // Formats that show what matched inside a file have nothing to show.
+    for format in ["symbols", "hunks", "markdown", "grep", "sarif", "cat"] {
+        Command::cargo_bin("rdump")?
+            .current_dir(dir.path())
+            .args(["search", "--invert", "--format", format, "func:safe_fn"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--invert-match only finds whole"))
+            .stderr(predicate::str::contains("Use one of: paths, paths0, find"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_search_whole_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let content = "use std::io;\n\nfn route() {}\n\nfn route_two() {}\n";
+    fs::write(dir.path().join("routes.rs"), content)?;
+    fs::write(dir.path().join("other.rs"), "fn other() {}\n")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=hunks", "func:route"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fn route() {}"))
+        .stdout(predicate::str::contains("use std::io").not());
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=hunks", "--whole-file", "func:route"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(content))
+        .stdout(predicate::str::contains("other.rs").not());
+
+    // A whole-file match counts once.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--count", "--whole-file", "func:route"])
+        .assert()
+        .success()
+        .stdout("./routes.rs: 1\ntotal: 1\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--whole-file", "--format=quickfix", "func:route"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--whole-file only finds whole files"));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_max_filesize() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("small.log"), "needle\n")?;
+    fs::write(dir.path().join("big.log"), format!("needle\n{}", "x".repeat(4096)))?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "--max-filesize", "1kb", "contains:needle"])
+        .assert()
+        .success()
+        .stdout("./small.log\n")
+        .stderr(predicate::str::contains(
+            "skipped 1 file(s) larger than --max-filesize",
+        ));
+
+    // A dry run leaves out the same files.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--dry-run", "--max-filesize", "1kb"])
+        .assert()
+        .success()
+        .stdout("./small.log\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--max-filesize", "1zb", "contains:needle"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid size unit"));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_count() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("a.rs"), "let x = a.unwrap();\nlet y = b.unwrap();\n")?;
+    fs::write(dir.path().join("b.rs"), "let z = c.unwrap();\n")?;
+    fs::write(dir.path().join("c.rs"), "let w = d?;\n")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--count", "contains:unwrap"])
+        .assert()
+        .success()
+        .stdout("./a.rs: 2\n./b.rs: 1\ntotal: 3\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_search_stats() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("a.rs"), "fn main() {}\n")?;
+    fs::write(dir.path().join("b.rs"), "fn helper() {}\n")?;
+    fs::write(dir.path().join("notes.txt"), "main\n")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "--stats", "ext:rs & func:main"])
+        .assert()
+        .success()
+        .stdout("./a.rs\n")
+        .stderr(predicate::str::contains("Files walked:     3\n"))
+        .stderr(predicate::str::contains("Files evaluated:  2\n"))
+        .stderr(predicate::str::contains("Files matched:    1\n"))
+        .stderr(predicate::str::contains("Hunks matched:    1\n"))
+        .stderr(predicate::str::contains("Bytes read:       28\n"))
+        .stderr(predicate::str::contains("(walk "));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_include_and_exclude_globs() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(dir.path().join("api.rs"), "fn handler() {}\n")?;
+    fs::write(dir.path().join("api.test.rs"), "fn handler() {}\n")?;
+    fs::write(dir.path().join("api.py"), "def handler(): pass\n")?;
+
+    // The query matches the test file too, but --exclude leaves it out.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "func:handler", "--exclude", "*.test.rs"])
+        .assert()
+        .success()
+        .stdout("./api.py\n./api.rs\n");
+
+    // --include on its own is the query.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "--include", "*.rs", "-x", "*.test.rs"])
+        .assert()
+        .success()
+        .stdout("./api.rs\n");
+
+    // Several includes must all match.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "func:handler"])
+        .args(["--include", "api.*", "--include", "*.py"])
+        .assert()
+        .success()
+        .stdout("./api.py\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_search_sort_keys() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_sort_project();
+    let sorted = |args: &[&str]| search_paths(dir.path(), &[args, &["contains:main"]].concat());
+
+    assert_eq!(sorted(&[]), "./a.rs\n./b.py\n./c.rs\n");
+    assert_eq!(sorted(&["--sort", "size"]), "./b.py\n./c.rs\n./a.rs\n");
+    assert_eq!(sorted(&["--sort", "modified"]), "./b.py\n./a.rs\n./c.rs\n");
+    assert_eq!(sorted(&["--sort", "matches"]), "./b.py\n./c.rs\n./a.rs\n");
+    assert_eq!(sorted(&["--sort", "ext"]), "./b.py\n./a.rs\n./c.rs\n");
+
+    assert_eq!(sorted(&["--reverse"]), "./c.rs\n./b.py\n./a.rs\n");
+    assert_eq!(
+        sorted(&["--sort", "modified", "--reverse"]),
+        "./c.rs\n./a.rs\n./b.py\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_search_sort_size_with_token_budget() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_sort_project();
+    // Only the two smallest files fit: 3 and 8 tokens, but not 12 more.
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=cat", "--sort=size", "--max-tokens=12"])
+        .arg("contains:main")
+        .assert()
+        .success()
+        .stdout("# main\n// main\n// main\n");
+    Ok(())
+}
+
+#[test]
+fn test_search_sort_modified_with_find_format() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_sort_project();
+    let output = Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=find", "--sort=modified", "--reverse"])
+        .arg("contains:main")
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    let names: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.rsplit('/').next().unwrap())
+        .collect();
+    assert_eq!(names, ["c.rs", "a.rs", "b.py"]);
+    Ok(())
+}
+
+#[test]
+fn test_search_limit_and_offset() -> Result<(), Box<dyn std::error::Error>> {
+    // Ten files that import `tokio`, each smaller than the last, and one that doesn't.
+    let dir = setup_project_with(
+        (0..10)
+            .map(|i| {
+                let content = "use tokio::runtime::Runtime;\n".repeat(10 - i);
+                (format!("file_{i}.rs"), content)
+            })
+            .chain([("plain.rs".to_string(), "use std::fs;\n".to_string())]),
+    );
+    let limited = |args: &[&str]| search_paths(dir.path(), &[args, &["import:tokio"]].concat());
+
+    assert_eq!(
+        limited(&["--limit", "3"]),
+        "./file_0.rs\n./file_1.rs\n./file_2.rs\n"
+    );
+    assert_eq!(
+        limited(&["--limit", "2", "--offset", "3"]),
+        "./file_3.rs\n./file_4.rs\n"
+    );
+    assert_eq!(limited(&["--offset", "9"]), "./file_9.rs\n");
+    assert_eq!(limited(&["--limit", "0"]), "");
+
+    // The limit applies after sorting.
+    assert_eq!(
+        limited(&["--sort", "size", "--limit", "2"]),
+        "./file_9.rs\n./file_8.rs\n"
+    );
+    assert_eq!(limited(&["--limit", "1", "--reverse"]), "./file_9.rs\n");
+
+    // Unsorted, it stops after the first files found, whichever they are.
+    let unsorted = limited(&["--unsorted", "--limit", "4"]);
+    assert_eq!(unsorted.lines().count(), 4);
+    assert!(unsorted.lines().all(|line| line.starts_with("./file_")));
+    Ok(())
+}
+
+#[test]
+fn test_search_sample_returns_n_matching_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_sample_project();
+    let paths = search_paths(dir.path(), &["--sample", "5", "import:tokio"]);
+    assert_eq!(paths.lines().count(), 5);
+    assert!(paths.lines().all(|p| p.contains("worker_")));
+    Ok(())
+}
+
+#[test]
+fn test_search_sample_with_seed_is_reproducible() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_sample_project();
+    let seeded = ["--sample", "5", "--seed", "7", "import:tokio"];
+    let first = search_paths(dir.path(), &seeded);
+    assert_eq!(first.lines().count(), 5);
+    assert_eq!(first, search_paths(dir.path(), &seeded));
+    Ok(())
+}
+
+#[test]
+fn test_search_sample_with_seed_ignores_unsorted() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_sample_project();
+    let seeded = ["--sample", "5", "--seed", "7", "import:tokio"];
+    let unsorted = search_paths(dir.path(), &[&["--unsorted"], &seeded[..]].concat());
+    assert_eq!(search_paths(dir.path(), &seeded), unsorted);
+    Ok(())
+}
+
+#[test]
+fn test_search_sample_larger_than_results() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_sample_project();
+    let paths = search_paths(dir.path(), &["--sample", "50", "import:tokio"]);
+    assert_eq!(paths.lines().count(), 20);
+    Ok(())
+}
+
+#[test]
+fn test_search_exit_code() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, root) = setup_test_dir();
+    for format in [&[][..], &["--count"], &["--find"], &["--format=json"]] {
+        Command::cargo_bin("rdump")?
+            .current_dir(&root)
+            .arg("search")
+            .args(format)
+            .args(["--exit-code", "func:main"])
+            .assert()
+            .code(0);
+
+        Command::cargo_bin("rdump")?
+            .current_dir(&root)
+            .arg("search")
+            .args(format)
+            .args(["--exit-code", "func:missing"])
+            .assert()
+            .code(1)
+            .stderr(predicate::str::is_empty());
+    }
+
+    Command::cargo_bin("rdump")?
+        .current_dir(&root)
+        .args(["search", "--exit-code", "nosuchkey:value"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Error:"));
+
+    // Without --exit-code, finding nothing is still a success.
+    Command::cargo_bin("rdump")?
+        .current_dir(&root)
+        .args(["search", "func:missing"])
+        .assert()
+        .success();
+    Command::cargo_bin("rdump")?
+        .current_dir(&root)
+        .args(["search", "--count", "func:missing"])
+        .assert()
+        .success();
+    Ok(())
+}
+
+#[test]
+fn test_search_exit_code_counts_printed_files() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, root) = setup_test_dir();
+    for format in [&[][..], &["--count"], &["--format=json"]] {
+        Command::cargo_bin("rdump")?
+            .current_dir(&root)
+            .arg("search")
+            .args(format)
+            .args(["--exit-code", "--offset", "5", "func:main"])
+            .assert()
+            .code(1);
+    }
+    Command::cargo_bin("rdump")?
+        .current_dir(&root)
+        .args([
+            "search",
+            "--format=paths",
+            "--exit-code",
+            "--limit=1",
+            "func:main",
+        ])
+        .assert()
+        .code(0)
+        .stdout("./main.rs\n");
+    Ok(())
+}
+
+#[test]
+fn test_search_silent() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, root) = setup_test_dir();
+    Command::cargo_bin("rdump")?
+        .current_dir(&root)
+        .args(["search", "--silent", "func:main"])
+        .assert()
+        .code(0)
+        .stdout("")
+        .stderr("");
+    Command::cargo_bin("rdump")?
+        .current_dir(&root)
+        .args(["search", "-s", "func:missing"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("");
+    Command::cargo_bin("rdump")?
+        .current_dir(&root)
+        .args(["search", "-s", "nosuchkey:value"])
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[test]
+fn test_search_silent_stops_at_the_first_match() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_project_with((0..300).map(|i| (format!("{i:03}.txt"), "needle\n")));
+    let output = Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args([
+            "search",
+            "--silent",
+            "--stats",
+            "--jobs=1",
+            "contains:needle",
+        ])
+        .output()?;
+    assert_eq!(output.status.code(), Some(0));
+
+    let stats = String::from_utf8(output.stderr)?;
+    let evaluated: usize = stats
+        .lines()
+        .find_map(|line| line.strip_prefix("Files evaluated:"))
+        .unwrap()
+        .trim()
+        .parse()?;
+    assert!(evaluated < 300, "{stats}");
+    Ok(())
+}
+
+#[test]
+fn test_search_type() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_types_project();
+    let by_type = search_paths(dir.path(), &["--type=rust"]);
+    assert_eq!(by_type, search_paths(dir.path(), &["ext:rs"]));
+    assert_eq!(by_type, "./lib.rs\n./main.rs\n");
+
+    // With a query, the type narrows it.
+    assert_eq!(
+        search_paths(dir.path(), &["-t", "python", "contains:main"]),
+        "./app.py\n./fast.pyx\n"
+    );
+    // Repeated types match any of them.
+    assert_eq!(
+        search_paths(dir.path(), &["-t", "rust", "-t", "python", "contains:main"]),
+        "./app.py\n./fast.pyx\n./main.rs\n"
+    );
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--type=cobol"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown file type 'cobol'"));
+    Ok(())
+}
+
+#[test]
+fn test_search_custom_type_from_config() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_types_project();
+    fs::write(
+        dir.path().join(".rdump.toml"),
+        "[types]\ndocs = [\"md\", \"rst\"]\n",
+    )?;
+    assert_eq!(search_paths(dir.path(), &["--type=docs"]), "./README.md\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["type", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("docs         md, rst"))
+        .stdout(predicate::str::contains("rust         rs"));
+    Ok(())
+}
+
+#[test]
+fn test_search_files_from() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_files_from_project();
+    assert_cmd::Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "--files-from", "-", "ext:rs"])
+        .write_stdin("src/c.rs\nsrc/a.rs\nsrc/missing.rs\n\n")
+        .assert()
+        .success()
+        .stdout("./src/a.rs\n./src/c.rs\n")
+        .stderr(predicate::str::contains(
+            "Warning: skipping ./src/missing.rs: not a file",
+        ));
+
+    assert_cmd::Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "--files-from0", "-", "ext:rs"])
+        .write_stdin("src/b.rs\0src/c.rs\0")
+        .assert()
+        .success()
+        .stdout("./src/b.rs\n./src/c.rs\n");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_search_files_from_non_utf8_paths() -> Result<(), Box<dyn std::error::Error>> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = setup_files_from_project();
+    let name = OsStr::from_bytes(b"caf\xe9.rs");
+    fs::write(dir.path().join(name), "fn test_cafe() {}\n")?;
+    assert_cmd::Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args([
+            "search",
+            "--format=paths",
+            "--files-from",
+            "-",
+            "func:test_cafe",
+        ])
+        .write_stdin(&b"caf\xe9.rs\n"[..])
+        .assert()
+        .success()
+        .stdout("./caf\u{FFFD}.rs\n")
+        .stderr("");
+    Ok(())
+}
+
+#[test]
+fn test_search_files_from_file_resolves_against_root() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_files_from_project();
+    let list = dir.path().join("list.txt");
+    fs::write(&list, "a.rs\r\nb.rs\r\n")?;
+    let root = dir.path().join("src");
+    Command::cargo_bin("rdump")?
+        .args(["search", "--format=paths", "func:test_b"])
+        .arg("--root")
+        .arg(&root)
+        .arg("--files-from")
+        .arg(&list)
+        .assert()
+        .success()
+        .stdout(format!("{}\n", root.join("b.rs").display()));
+    Ok(())
+}
+
+#[test]
+fn test_search_multiple_roots() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_repos_project();
+    let root = dir.path();
+    // `in:src` is relative to the root each file was found under.
+    assert_eq!(
+        search_paths(
+            root,
+            &["-r", "repo-a", "--root", "repo-b", "func:handler & in:src"]
+        ),
+        "repo-a/src/lib.rs\nrepo-b/src/lib.rs\n"
+    );
+
+    // Overlapping roots don't list a file twice.
+    assert_eq!(
+        search_paths(root, &["-r", "repo-a", "-r", "repo-a/src", "func:handler"]),
+        "repo-a/src/lib.rs\nrepo-a/tests/lib.rs\n"
+    );
+    // Even when they're spelled differently.
+    assert_eq!(
+        search_paths(
+            root,
+            &["-r", "./repo-a/src", "-r", "repo-a", "func:handler"]
+        ),
+        "repo-a/src/lib.rs\nrepo-a/tests/lib.rs\n"
+    );
+
+    assert_eq!(
+        search_paths(
+            root,
+            &[
+                "-r",
+                "repo-a",
+                "-r",
+                "repo-b",
+                "--template",
+                "{relpath}",
+                "in:tests"
+            ]
+        ),
+        "tests/lib.rs\ntests/lib.rs\n"
+    );
+
+    let expected = dunce::canonicalize(root.join("repo-b/src/lib.rs"))?;
+    assert_eq!(
+        search_paths(root, &["-r", "repo-b", "--absolute-paths", "in:src"]),
+        format!("{}\n", expected.display())
+    );
+    Ok(())
+}
+
+#[test]
+fn test_search_workspace_from_the_project_root() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = setup_repos_project();
+    let repo = dir.path().join("repo-a");
+    fs::write(repo.join("Cargo.toml"), "[package]\n")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(repo.join("src"))
+        .args([
+            "search",
+            "--format=paths",
+            "--workspace",
+            "--verbose",
+            "func:handler",
+        ])
+        .assert()
+        .success()
+        .stdout("../src/lib.rs\n../tests/lib.rs\n")
+        .stderr(predicate::str::contains("Using workspace root .."));
+
+    // An explicit --root wins.
+    Command::cargo_bin("rdump")?
+        .current_dir(repo.join("src"))
+        .args([
+            "search",
+            "--format=paths",
+            "--workspace",
+            "-r",
+            "../tests",
+            "func:handler",
+        ])
+        .assert()
+        .stdout("../tests/lib.rs\n");
+    Ok(())
+}
@@ -1,7 +1,11 @@
 #![allow(dead_code)] // a-llow dead code for this common helper module
 
+use assert_cmd::prelude::*;
 use std::fs;
 use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
 use tempfile::tempdir;
 use tempfile::TempDir;
 
@@ -258,4 +262,714 @@ export default function useAuth() {
         .unwrap();
 
     dir
-}
\ No newline at end of file
+}
+
+/// A helper to set up a temporary directory holding `files`, given as (relative path,
+/// content) pairs. Parent directories are created as needed.
+pub fn setup_project_with<P, C>(files: impl IntoIterator<Item = (P, C)>) -> TempDir
+where
+    P: AsRef<Path>,
+    C: AsRef<[u8]>,
+{
+    let dir = tempdir().unwrap();
+    for (path, content) in files {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+/// A helper to set up a project for the semantic predicates the sample project above
+/// doesn't cover: `module:`, `generic:`, `return_type:`, `decorator:`, the call kinds,
+/// and Go interfaces.
+pub fn setup_semantic_project() -> TempDir {
+    setup_project_with([
+        // --- Module declarations ---
+        (
+            "modules/lib.rs",
+            r#"
+mod parser;
+pub mod formatter;
+
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn it_adds() {}
+}
+"#,
+        ),
+        ("modules/main.rs", "mod parser;\n\nfn main() {}\n"),
+        (
+            "modules/shapes.py",
+            r#"
+__all__ = ["Circle", "Square"]
+
+class Circle:
+    pass
+
+def helper():
+    __all__ = []
+"#,
+        ),
+        ("modules/util.py", "def helper():\n    pass\n"),
+        (
+            "modules/geometry/__init__.py",
+            "from .shapes import Circle\n",
+        ),
+        ("modules/server.go", "package server\n\nfunc Start() {}\n"),
+        ("modules/main.go", "package main\n\nfunc main() {}\n"),
+        // --- Generics ---
+        (
+            "generics.rs",
+            r#"
+pub struct MyConfig<T>
+where
+    T: Serialize + Clone,
+{
+    inner: T,
+}
+
+pub struct Plain {
+    value: u32,
+}
+
+pub fn process<T: Debug>(item: T) {
+    println!("{:?}", item);
+}
+
+pub fn handle(item: u32) {}
+"#,
+        ),
+        ("plain_config.rs", "pub struct MyConfig { a: u8 }\n"),
+        // --- Return types ---
+        (
+            "returns.rs",
+            r#"
+fn load() -> Result<(), Error> {
+    Ok(())
+}
+
+fn read_name() -> Result<String, anyhow::Error> {
+    Ok(String::new())
+}
+
+fn find(id: u32) -> Option<User> {
+    None
+}
+
+fn log_result(result: Result<(), Error>) {}
+"#,
+        ),
+        (
+            "api.ts",
+            r#"
+async function fetchUser(id: number): Promise<User> {
+    return await get(id);
+}
+
+function formatUser(user: User): string {
+    return user.name;
+}
+
+class Client {
+    send(body: string): Promise<void> {
+        return post(body);
+    }
+}
+"#,
+        ),
+        // --- Calls ---
+        (
+            "client.rs",
+            r#"
+fn connect() -> bool {
+    true
+}
+
+struct Client;
+
+impl Client {
+    fn connect(&self) -> bool {
+        false
+    }
+
+    fn reconnect(&self) -> bool {
+        self.connect()
+    }
+}
+
+fn start() -> bool {
+    connect()
+}
+
+fn start_pooled() -> bool {
+    db::connect()
+}
+"#,
+        ),
+        // --- Decorators ---
+        (
+            "decorators.py",
+            r#"
+import functools
+
+class Circle:
+    def __init__(self, radius):
+        self.radius = radius
+
+    @property
+    def area(self):
+        return 3.14 * self.radius ** 2
+
+    def scale(self, factor):
+        return Circle(self.radius * factor)
+
+@functools.lru_cache(maxsize=None)
+def compute(n):
+    return n * 2
+
+@lru_cache
+def other(n):
+    return n
+
+def plain():
+    return "property"
+"#,
+        ),
+        // --- Go interfaces ---
+        (
+            "greet.go",
+            r#"
+package greet
+
+type Greeter interface {
+	Greet(name string) string
+}
+
+type Person struct {
+	Name string
+}
+
+func (p Person) Greet(name string) string {
+	return "hi " + name
+}
+"#,
+        ),
+    ])
+}
+
+/// A helper to set up a project with HTTP route handlers in each language `route:`
+/// supports, next to strings and calls that only look like routes.
+pub fn setup_route_project() -> TempDir {
+    setup_project_with([
+        (
+            "app.py",
+            r#"
+from flask import Flask
+app = Flask(__name__)
+
+@app.route("/users")
+def list_users():
+    return []
+
+@app.get("/health")
+def health():
+    return "ok"
+
+def helper():
+    return "/users"
+"#,
+        ),
+        (
+            "server.js",
+            r#"
+const express = require('express');
+const app = express();
+
+app.get('/users/:id', (req, res) => {
+    res.send(req.params.id);
+});
+
+app.post('/orders', createOrder);
+
+const cache = new Map();
+cache.get('/users');
+"#,
+        ),
+        (
+            "handlers.rs",
+            r#"
+#[get("/users")]
+async fn list_users() -> String {
+    String::new()
+}
+
+fn not_a_route() {
+    let _path = "/users";
+}
+
+fn router() {
+    Router::new().route("/orders", get(list_orders));
+}
+"#,
+        ),
+        (
+            "main.go",
+            r#"
+package main
+
+import "net/http"
+
+func main() {
+	mux := http.NewServeMux()
+	mux.HandleFunc("/users", usersHandler)
+	fmt.Println("/users")
+}
+"#,
+        ),
+    ])
+}
+
+/// A helper to set up a project reading environment variables in each language
+/// `env:` supports.
+pub fn setup_env_project() -> TempDir {
+    setup_project_with([
+        (
+            "config.rs",
+            r#"
+fn database_url() -> String {
+    std::env::var("DATABASE_URL").unwrap()
+}
+
+fn port() -> &'static str {
+    env!("PORT")
+}
+
+fn log_level() -> String {
+    env::var("LOG_LEVEL").unwrap_or_default()
+}
+"#,
+        ),
+        (
+            "settings.py",
+            r#"
+import os
+
+DATABASE_URL = os.environ["DATABASE_URL"]
+DEBUG = os.getenv("DEBUG")
+SECRET = os.environ.get("SECRET_KEY")
+"#,
+        ),
+        (
+            "server.js",
+            r#"
+const url = process.env.DATABASE_URL;
+const port = process.env["PORT"];
+const other = config.env.DATABASE_URL;
+"#,
+        ),
+        (
+            "Config.java",
+            r#"
+public class Config {
+    String url = System.getenv("DATABASE_URL");
+}
+"#,
+        ),
+        (
+            "main.go",
+            r#"
+package main
+
+import "os"
+
+func main() {
+	url := os.Getenv("DATABASE_URL")
+	cache, _ := os.LookupEnv(`REDIS_URL`)
+	_, _ = url, cache
+}
+"#,
+        ),
+    ])
+}
+
+/// A helper to set up a project with URLs, and strings and comments that only
+/// mention them, for `url:`.
+pub fn setup_url_project() -> TempDir {
+    setup_project_with([
+        (
+            "client.py",
+            r#"
+# See https://docs.example.com for details
+API = "https://api.internal.corp/v1/users"
+PUBLIC = "https://example.com"
+NAME = "not a url"
+"#,
+        ),
+        (
+            "client.ts",
+            r#"
+const endpoint = `http://localhost:8080/health`;
+const label = "internal.corp";
+"#,
+        ),
+    ])
+}
+
+/// A helper to set up a project with duplicated SQL fixtures, for `duplicate:`.
+pub fn setup_duplicate_project() -> TempDir {
+    setup_project_with([
+        ("fixtures/a/seed.sql", "INSERT INTO users VALUES (1);"),
+        ("fixtures/b/seed.sql", "INSERT INTO users VALUES (1);"),
+        ("fixtures/b/other.sql", "INSERT INTO users VALUES (2);"),
+        // Same content as the SQL fixtures, but excluded by `ext:sql`.
+        ("notes.txt", "INSERT INTO users VALUES (2);"),
+    ])
+}
+
+/// A helper to set up a project with a branchless and a branchy function in each
+/// language `complexity:` supports, and a Java file it doesn't.
+pub fn setup_complexity_project() -> TempDir {
+    setup_project_with([
+        (
+            "lib.rs",
+            r#"
+fn simple() -> i32 {
+    42
+}
+
+fn gnarly(x: i32, y: i32) -> i32 {
+    if x > 0 && y > 0 {
+        for i in 0..x {
+            while i < y {
+                return i;
+            }
+        }
+    }
+    match x {
+        0 => 1,
+        _ => 2,
+    }
+}
+"#,
+        ),
+        (
+            "app.py",
+            r#"
+def simple():
+    return 1
+
+def gnarly(x, y):
+    if x and y:
+        return 1
+    elif x or y:
+        return 2
+    for i in range(x):
+        while i:
+            i -= 1
+    return 0
+"#,
+        ),
+        (
+            "util.ts",
+            r#"
+function simple(): number {
+    return 1;
+}
+
+function gnarly(x: number, y: number): number {
+    if (x > 0 || y > 0) {
+        for (let i = 0; i < x; i++) {
+            if (i === y) {
+                return i;
+            }
+        }
+    }
+    return x ? 1 : 2;
+}
+"#,
+        ),
+        ("Main.java", "class Main { void run() { if (a) {} } }"),
+    ])
+}
+
+/// A helper to set up a project with one-line and ten-line functions, for
+/// `funclines:`.
+pub fn setup_funclines_project() -> TempDir {
+    let long_body = "    let x = 1;\n".repeat(10);
+    setup_project_with([
+        (
+            "lib.rs",
+            format!("fn wrapper() -> i32 {{ 1 }}\n\nfn process() {{\n{long_body}}}\n\nfn other_long() {{\n{long_body}}}\n"),
+        ),
+        (
+            "app.py",
+            "def tiny():\n    return 1\n\ndef big():\n    a = 1\n    b = 2\n    c = 3\n    d = 4\n    return a + b + c + d\n".to_string(),
+        ),
+    ])
+}
+
+/// A helper to set up a Rust file with an unsafe fn, impl and block next to safe
+/// code, for `unsafe:`.
+pub fn setup_unsafe_project() -> TempDir {
+    setup_project_with([(
+        "lib.rs",
+        r#"
+pub unsafe fn reinterpret(x: f32) -> u32 {
+    std::mem::transmute(x)
+}
+
+struct Handle(*mut u8);
+unsafe impl Send for Handle {}
+
+fn read_first(ptr: *const u8) -> u8 {
+    // SAFETY: caller guarantees `ptr` is valid.
+    unsafe { ptr.read() }
+}
+
+fn safe_code() -> u8 {
+    42
+}
+"#,
+    )])
+}
+
+/// A helper to set up a project with tag comments, and a comment that only mentions
+/// one, for `tag:`.
+pub fn setup_tag_project() -> TempDir {
+    setup_project_with([
+        (
+            "lib.rs",
+            r#"// FIXME: handle overflow
+fn add(a: u8, b: u8) -> u8 { a + b }
+
+// This comment merely mentions fixme in passing.
+fn mention() {}
+
+// PERF: allocates on every call
+fn slow() -> Vec<u8> { Vec::new() }
+"#,
+        ),
+        (
+            "app.py",
+            "# TODO(alice): cache this\ndef fetch():\n    pass\n",
+        ),
+    ])
+}
+
+/// A helper to set up a project with functions whose signatures only differ from
+/// their bodies or their neighbours', for `signature:`.
+pub fn setup_signature_project() -> TempDir {
+    setup_project_with([
+        (
+            "lib.rs",
+            r#"
+impl Server {
+    pub fn new(config: &Config) -> Self {
+        Server { port: config.port }
+    }
+
+    pub fn new_default() -> Self {
+        // Mentions fn new(config: &Config) only in a comment.
+        Server { port: 80 }
+    }
+}
+"#,
+        ),
+        (
+            "views.py",
+            r#"
+def handle(request,
+           user=None):
+    return respond(request)
+
+def handle_later(task):
+    return task
+"#,
+        ),
+    ])
+}
+
+/// A helper to set up a project with Rust files at two depths and a README, for
+/// `--output-dir`.
+pub fn setup_output_dir_project() -> TempDir {
+    setup_project_with([
+        ("src/main.rs", "fn main() {}\n"),
+        ("src/nested/lib.rs", "pub fn lib() {}\n"),
+        ("README.md", "# readme\n"),
+    ])
+}
+
+/// A helper to set up twenty files that import `tokio` and twenty that don't, for
+/// `--sample`.
+pub fn setup_sample_project() -> TempDir {
+    setup_project_with((0..20).flat_map(|i| {
+        [
+            (
+                format!("worker_{i:02}.rs"),
+                "use tokio::runtime::Runtime;\n",
+            ),
+            // Files that never match, to make sure the sample only draws from matches.
+            (format!("plain_{i:02}.rs"), "use std::fs;\n"),
+        ]
+    }))
+}
+
+/// A helper to set up a Rust file with inherent, trait and generic impl blocks, for
+/// `impl:`.
+pub fn setup_impl_project() -> TempDir {
+    setup_project_with([(
+        "models.rs",
+        r#"
+pub struct User { name: String }
+pub struct Order { id: u64 }
+pub struct Wrapper<T>(T);
+
+impl User {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl std::fmt::Display for User {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Order {
+    pub fn total(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<T> Wrapper<T> {
+    pub fn inner(&self) -> &T {
+        &self.0
+    }
+}
+
+impl Default for crate::models::User {
+    fn default() -> Self {
+        Self { name: String::new() }
+    }
+}
+"#,
+    )])
+}
+
+/// A helper to set up files whose path, size, modification time and match count
+/// orders all differ, for `--sort`.
+pub fn setup_sort_project() -> TempDir {
+    let dir = setup_project_with([
+        ("a.rs", "// main\n".repeat(3)),
+        ("b.py", "# main\n".to_string()),
+        ("c.rs", "// main\n".repeat(2)),
+    ]);
+    for (name, age_days) in [("a.rs", 2), ("b.py", 3), ("c.rs", 1)] {
+        let modified = SystemTime::now() - Duration::from_secs(age_days * 24 * 60 * 60);
+        fs::File::options()
+            .write(true)
+            .open(dir.path().join(name))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+    dir
+}
+
+/// A helper to set up a few files of different types, most of them mentioning
+/// `main`, for `--type`.
+pub fn setup_types_project() -> TempDir {
+    setup_project_with([
+        ("main.rs", "fn main() {}\n"),
+        ("lib.rs", "pub fn helper() {}\n"),
+        ("app.py", "def main(): pass\n"),
+        ("fast.pyx", "def main(): pass\n"),
+        ("README.md", "# main\n"),
+    ])
+}
+
+/// A helper to set up three files under `src`, each with one test function, for
+/// `--files-from`.
+pub fn setup_files_from_project() -> TempDir {
+    setup_project_with([
+        ("src/a.rs", "fn test_a() {}\n"),
+        ("src/b.rs", "fn test_b() {}\n"),
+        ("src/c.rs", "fn test_c() {}\n"),
+    ])
+}
+
+/// A helper to set up two sibling repositories, each with a `src` and a `tests`
+/// directory, for searching several roots.
+pub fn setup_repos_project() -> TempDir {
+    setup_project_with(
+        ["repo-a/src", "repo-a/tests", "repo-b/src", "repo-b/tests"]
+            .map(|sub| (format!("{sub}/lib.rs"), "fn handler() {}\n")),
+    )
+}
+
+/// A helper to set up a `project` directory with two matching Rust files, a README and
+/// a binary file, for `--archive`.
+pub fn setup_archive_project() -> TempDir {
+    let dir = setup_project_with([
+        ("project/src/main.rs", "fn main() {\n    connect();\n}\n"),
+        ("project/src/db.rs", "pub fn connect() {}\n"),
+        ("project/README.md", "nothing to see\n"),
+    ]);
+    // Binary content must be stored byte for byte.
+    fs::write(
+        dir.path().join("project/src/blob.bin"),
+        [0u8, 159, 146, 150, 255],
+    )
+    .unwrap();
+    dir
+}
+
+/// A helper to set up a Java file with Java 16+ constructs: records, sealed classes,
+/// annotation types and lambdas.
+pub fn setup_modern_java_project() -> TempDir {
+    setup_project_with([(
+        "Shapes.java",
+        r#"
+package com.example.shapes;
+
+import java.util.List;
+import java.util.function.Supplier;
+import java.util.stream.Stream;
+
+public record Point(int x, int y) {}
+
+public sealed interface Shape permits Circle, Square {}
+
+public @interface Audited {
+    String value();
+}
+
+public abstract class AbstractFactory {
+    private final List<String> names = List.of("a", "b");
+    private final Supplier<Stream<String>> stream = () -> names.stream();
+
+    public abstract Shape create();
+}
+"#,
+    )])
+}
+
+/// Runs `rdump search --format=paths` with `args` in `dir`, and returns what it printed.
+pub fn search_paths(dir: &Path, args: &[&str]) -> String {
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir)
+        .args(["search", "--format=paths"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
@@ -0,0 +1,51 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn test_completions_include_subcommands() {
+    let dir = tempdir().unwrap();
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        Command::cargo_bin("rdump")
+            .unwrap()
+            .current_dir(dir.path())
+            .args(["completions", shell])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("search"))
+            .stdout(predicate::str::contains("lang"))
+            .stdout(predicate::str::contains("preset"))
+            .stdout(predicate::str::contains("completions"));
+    }
+}
+
+#[test]
+fn test_completions_include_format_values_and_local_presets() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join(".rdump.toml"),
+        "[presets]\nmy-todos = \"tag:todo\"\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("json-extended"))
+        .stdout(predicate::str::contains("my-todos"));
+}
+
+#[test]
+fn test_completions_rejects_unknown_shell() {
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .args(["completions", "tcsh"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'tcsh'"));
+}
@@ -0,0 +1,73 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+/// A fake home with a global config, and a project with a local one.
+fn setup_configs(global: &str, local: &str) -> (tempfile::TempDir, Command) {
+    let dir = tempdir().unwrap();
+    let config_dir = dir.path().join("home/rdump");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(config_dir.join("config.toml"), global).unwrap();
+    let project = dir.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join(".rdump.toml"), local).unwrap();
+
+    let mut cmd = Command::cargo_bin("rdump").unwrap();
+    cmd.env("RDUMP_TEST_CONFIG_DIR", dir.path().join("home"))
+        .current_dir(&project)
+        .args(["config", "migrate"]);
+    (dir, cmd)
+}
+
+#[test]
+fn test_migrate_adds_version_to_unversioned_configs() {
+    let global = "# shared presets\n[presets]\nrust = \"ext:rs\"\n";
+    let (dir, mut cmd) = setup_configs(global, "tags = [\"PERF\"]\n");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("+version = 1\n # shared presets\n"))
+        .stdout(predicate::str::contains("+version = 1\n tags = [\"PERF\"]\n"))
+        .stdout(predicate::str::contains("Migrated"));
+
+    let migrated = fs::read_to_string(dir.path().join("home/rdump/config.toml")).unwrap();
+    assert_eq!(migrated, format!("version = 1\n{global}"));
+    let local = fs::read_to_string(dir.path().join("project/.rdump.toml")).unwrap();
+    assert_eq!(local, "version = 1\ntags = [\"PERF\"]\n");
+
+    // The presets still load.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .env("RDUMP_TEST_CONFIG_DIR", dir.path().join("home"))
+        .current_dir(dir.path().join("project"))
+        .args(["preset", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust : ext:rs"));
+}
+
+#[test]
+fn test_migrate_leaves_version_1_configs_alone() {
+    let global = "version = 1\n[presets]\nrust = \"ext:rs\"\n";
+    let (dir, mut cmd) = setup_configs(global, "version = 1\n");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("already at version 1"))
+        .stdout(predicate::str::contains("+").not());
+    let unchanged = fs::read_to_string(dir.path().join("home/rdump/config.toml")).unwrap();
+    assert_eq!(unchanged, global);
+}
+
+#[test]
+fn test_migrate_dry_run_writes_nothing() {
+    let global = "[presets]\nrust = \"ext:rs\"\n";
+    let (dir, mut cmd) = setup_configs(global, "version = 1\n");
+    cmd.arg("--dry-run")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+version = 1\n"))
+        .stdout(predicate::str::contains("was not changed"));
+    let unchanged = fs::read_to_string(dir.path().join("home/rdump/config.toml")).unwrap();
+    assert_eq!(unchanged, global);
+}
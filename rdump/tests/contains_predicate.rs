@@ -64,3 +64,18 @@ fn test_contains_with_other_predicates() {
         .stdout(predicate::str::contains("main.go").not());
 }
 
+
+#[test]
+fn test_contains_multiline() {
+    let dir = setup_test_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["search", "--line-numbers", r#"contains:"main() {\n    // this is""#])
+        .assert()
+        .success()
+        // The hunk is both lines, in full.
+        .stdout(predicate::str::contains("pub fn main() {"))
+        .stdout(predicate::str::contains("// This is the main function"))
+        .stdout(predicate::str::contains("User::new").not());
+}
@@ -0,0 +1,77 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn test_explain_prints_tree_and_plan() {
+    let dir = tempdir().unwrap();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["explain", "ext:rs & func:main"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Query: ext:rs & func:main"))
+        .stdout(predicate::str::contains("├── ext:rs  [metadata]"))
+        .stdout(predicate::str::contains("└── func:main  [semantic]"))
+        .stdout(predicate::str::contains("Metadata pre-filter: ext:rs"));
+}
+
+#[test]
+fn test_explain_splices_presets() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join(".rdump.toml"),
+        "[presets]\nrust = \"ext:rs\"\n",
+    )
+    .unwrap();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["explain", "--preset", "rust", "func:main"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Query: ((ext:rs)) & (func:main)"))
+        .stdout(predicate::str::contains("preset rust: ext:rs"));
+}
+
+#[test]
+fn test_explain_traces_a_file() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("main.rs"),
+        "fn helper() {}\n\nfn main() {\n    helper();\n}\n",
+    )
+    .unwrap();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["explain", "--file", "main.rs", "ext:py & func:main"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Evaluating main.rs:"))
+        .stdout(predicate::str::contains("  & → no match; right side skipped"))
+        .stdout(predicate::str::contains("    ext:py → no match"))
+        .stdout(predicate::str::contains("Result: no match"));
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["explain", "--file", "main.rs", "ext:rs & (func:main | func:helper)"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("      func:main → 1 hunk at line 3"))
+        .stdout(predicate::str::contains("Result: 2 hunks at lines 1, 3"));
+}
+
+#[test]
+fn test_explain_rejects_unknown_predicates() {
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .args(["explain", "nosuch:thing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown predicate: 'nosuch'"));
+}
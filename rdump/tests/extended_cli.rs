@@ -7,6 +7,9 @@ use std::io::Write;
 use std::process::Command;
 use tempfile::tempdir;
 
+mod common;
+use common::setup_duplicate_project;
+
 /// Helper to create a directory with a few files for testing complex queries.
 fn setup_query_test_dir() -> (tempfile::TempDir, std::path::PathBuf) {
     let dir = tempdir().unwrap();
@@ -128,4 +131,119 @@ fn test_complex_predicate_combination() -> Result<(), Box<dyn std::error::Error>
         .stdout(predicate::str::contains("utils.rs").not());
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_copy_fails_gracefully_without_a_display() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.rs"), "fn main() {}").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .arg("search")
+        .arg("--copy")
+        .arg("ext:rs")
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("Could not access the system clipboard"));
+
+    // The background process that holds the copied text fails the same way.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .env("RDUMP_CLIPBOARD_HOLDER", "1")
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .stdin(std::process::Stdio::null())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Could not access the system clipboard"));
+}
+
+#[test]
+fn test_interactive_needs_a_terminal() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.rs"), "fn main() {}").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["search", "--interactive", "ext:rs"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("--interactive needs a terminal"));
+}
+
+#[test]
+fn test_print0_separates_paths_with_nul() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path();
+    fs::create_dir(root.join("my docs"))?;
+    fs::write(root.join("my docs/release notes.md"), "# Notes")?;
+    fs::write(root.join("plain.md"), "# Plain")?;
+
+    let mut cmd = Command::cargo_bin("rdump")?;
+    cmd.current_dir(root).args(["search", "--print0", "ext:md"]);
+    cmd.assert()
+        .success()
+        .stdout("./my docs/release notes.md\0./plain.md\0");
+
+    let mut cmd = Command::cargo_bin("rdump")?;
+    cmd.current_dir(root)
+        .args(["search", "--format=paths0", "name:'release notes.md'"]);
+    cmd.assert().success().stdout("./my docs/release notes.md\0");
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_predicate_finds_identical_files() {
+    let dir = setup_duplicate_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("duplicate:true & ext:sql")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("a/seed.sql"))
+        .stdout(predicate::str::contains("b/seed.sql"))
+        // `other.sql` only duplicates a file the `ext:sql` filter removed.
+        .stdout(predicate::str::contains("other.sql").not())
+        .stdout(predicate::str::contains("notes.txt").not());
+}
+
+#[test]
+fn test_duplicate_predicate_false_finds_unique_files() {
+    let dir = setup_duplicate_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("duplicate:false & ext:sql")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("other.sql"))
+        .stdout(predicate::str::contains("seed.sql").not());
+}
+
+#[test]
+fn test_duplicate_predicate_invalid_value() {
+    let dir = setup_duplicate_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("duplicate:sometimes")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid value for duplicate predicate",
+        ));
+}
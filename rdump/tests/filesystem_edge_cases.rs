@@ -48,9 +48,173 @@ fn test_search_handles_invalid_utf8_file_gracefully() {
     // This query forces the tool to read the file content.
     cmd.arg("search").arg("contains:any");
 
-    // The tool should not panic. It should report an error and exit.
+    // The file is read lossily, and the search goes on with a warning.
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("1 file(s) weren't valid UTF-8"));
+
+    // --strict-encoding makes it an error, as before.
+    let mut cmd = Command::cargo_bin("rdump").unwrap();
+    cmd.current_dir(root);
+    cmd.args(["search", "--strict-encoding", "contains:any"]);
     cmd.assert()
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr(predicate::str::contains("Error evaluating file"));
+        .stderr(predicate::str::contains("Error evaluating file"))
+        .stderr(predicate::str::contains("is not valid UTF-8"));
+}
+
+#[test]
+fn test_search_skips_binary_files() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("app"), b"\x7fELF\x02\x01\x01\x00\x00needle\xff\xfe").unwrap();
+    fs::write(root.join("notes.txt"), "needle\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=cat", "contains:needle"])
+        .assert()
+        .success()
+        .stdout("needle\n")
+        .stderr(predicate::str::contains("skipped 1 binary file(s)"));
+
+    // Not even a negated query shows a binary file.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=paths", "!contains:haystack"])
+        .assert()
+        .success()
+        .stdout("./notes.txt\n");
+}
+
+#[test]
+fn test_output_leaves_out_binary_content_matched_by_metadata() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("x.bin"), b"a\0b").unwrap();
+    fs::write(root.join("y.bin"), "text\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=markdown", "ext:bin"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("File: ./x.bin"))
+        .stdout(predicate::str::contains("File: ./y.bin"))
+        .stdout(predicate::str::contains("text\n"))
+        .stderr(predicate::str::contains(
+            "Warning: leaving out the content of ./x.bin: it is a binary file",
+        ));
+
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=json", "ext:bin"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let files: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(files[0]["path"], "./x.bin");
+    assert_eq!(files[0]["content"], "");
+    assert_eq!(files[1]["content"], "text\n");
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("leaving out the content of ./x.bin: it is a binary file"));
+}
+
+#[test]
+fn test_search_matches_latin1_files() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    // "José" in Latin-1.
+    fs::write(root.join("names.txt"), b"author = Jos\xe9\nversion = 2\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format", "paths", "contains:version"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("names.txt"));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_search_follows_symlinked_directories_with_follow() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("packages/core")).unwrap();
+    fs::write(root.join("packages/core/lib.rs"), "fn core() {}\n").unwrap();
+    fs::create_dir(root.join("app")).unwrap();
+    symlink(root.join("packages/core"), root.join("app/core")).unwrap();
+    symlink(root.join("packages/core"), root.join("app/core-again")).unwrap();
+    // A loop back to the top must not hang the search.
+    symlink(root, root.join("packages/loop")).unwrap();
+
+    let search = |follow: bool| {
+        let mut cmd = Command::cargo_bin("rdump").unwrap();
+        cmd.current_dir(root).args(["search", "--format=paths", "func:core"]);
+        if follow {
+            cmd.arg("--follow");
+        }
+        cmd.output().unwrap()
+    };
+
+    let output = search(false);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "./packages/core/lib.rs\n");
+
+    // The same file, reachable three ways, is only listed once.
+    let output = search(true);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "./app/core/lib.rs\n");
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("File system loop found"));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_find_format_marks_followed_symlinks() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("target.txt"), "content").unwrap();
+    symlink(root.join("target.txt"), root.join("link.txt")).unwrap();
+
+    for sort in ["--sort=path", "--sort=size"] {
+        let output = Command::cargo_bin("rdump")
+            .unwrap()
+            .current_dir(root)
+            .args(["search", "--find", "--follow", sort, "contains:content"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let line = stdout.lines().find(|line| line.ends_with("link.txt")).unwrap();
+        assert!(line.starts_with('l'), "{stdout}");
+    }
+}
+
+#[test]
+fn test_search_matches_files_with_a_bom() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("main.rs"), "\u{FEFF}fn main() {\n    println!(\"hi\");\n}\n").unwrap();
+
+    for query in ["contains:'fn main()'", "func:main"] {
+        Command::cargo_bin("rdump")
+            .unwrap()
+            .current_dir(root)
+            .args(["search", "--format", "hunks", query])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("main.rs"))
+            .stdout(predicate::str::contains("fn main() {"))
+            .stdout(predicate::str::contains('\u{FEFF}').not());
+    }
 }
@@ -1,9 +1,15 @@
 use assert_cmd::prelude::*;
+use flate2::read::GzDecoder;
 use predicates::prelude::*;
-use std::fs;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
 use std::process::Command;
 use tempfile::tempdir;
 
+mod common;
+use common::{setup_archive_project, setup_output_dir_project, setup_project_with};
+
 #[test]
 fn test_formatter_merges_overlapping_hunks() {
     let dir = tempdir().unwrap();
@@ -37,3 +43,854 @@ fn test_formatter_merges_overlapping_hunks() {
         .stdout(predicate::str::contains("line 5"))
         .stdout(predicate::str::contains("...").not());
 }
+
+#[test]
+fn test_json_extended_format_reports_hunk_lines() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("lib.rs"),
+        "fn first() {}\n\nfn second() {}\n\nfn third() {}\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=json-extended")
+        .arg("func:second | func:third")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let file = &json.as_array().unwrap()[0];
+    assert_eq!(file["language"], "Rust");
+    let start_lines: Vec<u64> = file["hunks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["start_line"].as_u64().unwrap())
+        .collect();
+    assert_eq!(start_lines, vec![3, 5]);
+    assert_eq!(file["hunks"][0]["text"], "fn second() {}");
+}
+
+#[test]
+fn test_json_format_reports_hunks_unless_full_content() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("lib.rs"),
+        "fn first() {}\n\nfn second() {}\n\nfn third() {}\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=json", "-C", "1", "func:second"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let file = &json[0];
+    assert!(file.get("content").is_none());
+    assert_eq!(file["hunks"][0]["start_line"], 2);
+    assert_eq!(file["hunks"][0]["end_line"], 4);
+    assert_eq!(file["hunks"][0]["text"], "\nfn second() {}\n");
+
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=json", "--json-full-content"])
+        .arg("func:second")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json[0].get("hunks").is_none());
+    assert_eq!(
+        json[0]["content"],
+        "fn first() {}\n\nfn second() {}\n\nfn third() {}\n"
+    );
+}
+
+#[test]
+fn test_hunks_format_shows_enclosing_scope() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("user.rs"),
+        "struct User;\n\nimpl User {\n    fn new() -> Self {\n        User\n    }\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("cart.py"),
+        "class Cart:\n    def total(self):\n        return compute(self.items)\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("func:new")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("// In: impl User::new()"));
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("call:compute")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("// In: class Cart::total()"));
+
+    // Top-level code and whole-file matches get no scope line.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("struct:User | ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("// In:").not());
+}
+
+#[test]
+fn test_merge_adjacent_hunks_flag() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let content: String = (1..=15)
+        .map(|i| {
+            if i == 5 || i == 12 {
+                format!("line {i} (match)\n")
+            } else {
+                format!("line {i}\n")
+            }
+        })
+        .collect();
+    fs::write(root.join("test.txt"), content).unwrap();
+
+    // Without merging, the two hunks are printed separately.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("contains:match")
+        .arg("--merge-adjacent-hunks")
+        .arg("0")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("..."))
+        .stdout(predicate::str::contains("line 8\n").not());
+
+    // With a large enough gap, they become one block including the lines between.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("contains:match")
+        .arg("--merge-adjacent-hunks")
+        .arg("10")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("line 5 (match)\nline 6\n"))
+        .stdout(predicate::str::contains("line 8\n"))
+        .stdout(predicate::str::contains("line 12 (match)"))
+        .stdout(predicate::str::contains("...").not())
+        .stdout(predicate::str::contains("line 13").not());
+}
+
+#[test]
+fn test_ndjson_format_unsorted_emits_one_line_per_file() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    for name in ["a.rs", "b.rs", "c.rs"] {
+        fs::write(root.join(name), "fn main() {}\n").unwrap();
+    }
+
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=ndjson")
+        .arg("--unsorted")
+        .arg("func:main")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut paths: Vec<String> = stdout
+        .lines()
+        .map(|line| {
+            let file: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(file["hunks"][0]["start_line"], 1);
+            file["path"].as_str().unwrap().to_string()
+        })
+        .collect();
+    paths.sort();
+    assert_eq!(paths.len(), 3);
+    assert!(paths[0].ends_with("a.rs"));
+    assert!(paths[2].ends_with("c.rs"));
+}
+
+#[test]
+fn test_streamed_output_stays_in_path_order() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    // Enough files that rayon evaluates them out of order.
+    for i in 0..50 {
+        fs::write(root.join(format!("file_{i:02}.rs")), "fn main() {}\n").unwrap();
+    }
+
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=csv")
+        .arg("func:main")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some("path,start_line,end_line,size,modified,match_count")
+    );
+    let paths: Vec<&str> = lines.map(|l| l.split(',').next().unwrap()).collect();
+    assert_eq!(paths.len(), 50);
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert_eq!(paths, sorted);
+}
+
+#[test]
+fn test_html_format_writes_report_file() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("main.rs"), "fn main() {}\n").unwrap();
+    let report = root.join("report.html");
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=html")
+        .arg("--theme=InspiredGitHub")
+        .arg("-o")
+        .arg(&report)
+        .arg("func:main")
+        .assert()
+        .success();
+
+    let html = fs::read_to_string(&report).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("main.rs</a></li>"));
+    assert!(html.contains(r#"<span id="file-1-L1">"#));
+}
+
+#[test]
+fn test_unknown_theme_is_rejected() {
+    let dir = tempdir().unwrap();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--theme=no-such-theme")
+        .arg("ext:rs")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown theme 'no-such-theme'"));
+}
+
+#[test]
+fn test_grep_format_prints_line_and_column() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("lib.rs"), "fn first() {}\n\nfn second() {}\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=grep")
+        .arg("func:second")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs:3:1:fn second() {}\n"))
+        .stdout(predicate::str::contains("first").not());
+}
+
+#[test]
+fn test_quickfix_format_has_one_entry_per_hunk() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("lib.rs"), "fn first() {}\n\nfn second() {}\n").unwrap();
+    fs::write(root.join("notes.txt"), "nothing to see").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=quickfix", "func:. | ext:txt"])
+        .assert()
+        .success()
+        .stdout(
+            "./lib.rs:1:1: fn first() {}\n\
+             ./lib.rs:3:1: fn second() {}\n\
+             ./notes.txt:1:1: func:. | ext:txt\n",
+        );
+}
+
+#[test]
+fn test_sarif_format_reports_query_as_rule() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("app.py"),
+        "import os\n\nx = '😀'; eval(user_input)\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=sarif")
+        .arg("call:eval & ext:py")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(result["ruleId"], "call:eval & ext:py");
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "app.py");
+    assert_eq!(location["region"]["startLine"], 3);
+    // Columns count UTF-16 code units: the emoji is 4 bytes, but 2 units.
+    assert_eq!(location["region"]["startColumn"], 11);
+}
+
+#[test]
+fn test_symbols_format_with_wildcard_def() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("models.rs"),
+        "pub struct User;\n\npub enum Role { Admin }\n\npub trait Greet {}\n",
+    )
+    .unwrap();
+    fs::write(root.join("app.py"), "class Cart:\n    pass\n\ndef checkout():\n    pass\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=symbols")
+        .arg("def:*")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("User\t./models.rs\t1\tstruct\n"))
+        .stdout(predicate::str::contains("Role\t./models.rs\t3\tenum\n"))
+        .stdout(predicate::str::contains("Greet\t./models.rs\t5\ttrait\n"))
+        .stdout(predicate::str::contains("Cart\t./app.py\t1\tclass\n"))
+        .stdout(predicate::str::contains("checkout\t./app.py\t4\tfunction\n"));
+}
+
+#[test]
+fn test_tree_format_prunes_unmatched_directories() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("src/utils")).unwrap();
+    fs::create_dir_all(root.join("docs")).unwrap();
+    fs::write(root.join("src/main.rs"), "use lodash;\nuse lodash;\n").unwrap();
+    fs::write(root.join("src/utils/math.rs"), "use lodash;\n").unwrap();
+    fs::write(root.join("src/lib.rs"), "fn unrelated() {}\n").unwrap();
+    fs::write(root.join("docs/guide.md"), "nothing here\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=tree")
+        .arg("contains:lodash")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            ".\n\
+             └── src\n    \
+                 ├── main.rs (24B, 2 hunks)\n    \
+                 └── utils\n        \
+                     └── math.rs (12B, 1 hunk)\n\
+             \n\
+             2 directories, 2 files\n",
+        ));
+}
+
+#[test]
+fn test_stats_format_summarizes_matches() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(root.join("scripts")).unwrap();
+    fs::write(root.join("src/main.rs"), "// TODO: one\n// TODO: two\nfn main() {}\n").unwrap();
+    fs::write(root.join("src/lib.rs"), "// TODO: three\n").unwrap();
+    fs::write(root.join("scripts/build.py"), "# TODO: four\n").unwrap();
+    fs::write(root.join("README.md"), "no markers\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=stats")
+        .arg("comment:TODO")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Files:       3\n"))
+        .stdout(predicate::str::contains("Total hunks: 4\n"))
+        .stdout(predicate::str::contains("  rs      2 files"))
+        .stdout(predicate::str::contains("  py      1 file "))
+        .stdout(predicate::str::contains("  src          2 files"))
+        .stdout(predicate::str::contains("  scripts      1 file "))
+        .stdout(predicate::str::contains("Largest files:\n       39B  ./src/main.rs\n"))
+        // No file content is printed.
+        .stdout(predicate::str::contains("fn main").not())
+        .stdout(predicate::str::contains("README").not());
+    // Directories are relative to the root, even when it's given as an absolute path.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .args(["search", "--format=stats", "--root"])
+        .arg(root)
+        .arg("comment:TODO")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  src          2 files"))
+        .stdout(predicate::str::contains("  scripts      1 file "));
+}
+
+#[test]
+fn test_template_builds_an_index() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("docs")).unwrap();
+    fs::write(root.join("docs/intro.md"), "# Intro\n").unwrap();
+    fs::write(root.join("notes.md"), "notes\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("ext:md")
+        .args(["--template", "- [{relpath}]({relpath}) ({size}, {lines} lines)"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "- [docs/intro.md](docs/intro.md) (8B, 1 lines)\n- [notes.md](notes.md) (6B, 1 lines)\n",
+        ));
+}
+
+#[test]
+fn test_template_renders_each_hunk_with_line_numbers() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("lib.rs"),
+        "fn first() {\n    work();\n}\n\nfn second() {\n    rest();\n    work();\n}\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--line-numbers")
+        .arg("-A")
+        .arg("1")
+        .arg("--template")
+        .arg("{path}:{line_start}-{line_end} of {hunks}\n{content}")
+        .arg("contains:work")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "./lib.rs:2-3 of 2\n    2 |     work();\n    3 | }\n\
+             ./lib.rs:7-8 of 2\n    7 |     work();\n    8 | }\n",
+        ));
+}
+
+#[test]
+fn test_template_unknown_placeholder_is_an_upfront_error() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.md"), "a").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("ext:md")
+        .arg("--template")
+        .arg("{path} {owner}")
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("Unknown placeholder '{owner}'"));
+}
+
+#[test]
+fn test_tokens_format_and_max_tokens_budget() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    // 100, 10 and 1 estimated tokens respectively.
+    fs::write(root.join("big.txt"), "word ".repeat(100)).unwrap();
+    fs::write(root.join("medium.txt"), "word ".repeat(10)).unwrap();
+    fs::write(root.join("small.txt"), "word").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=tokens", "ext:txt"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "100  ./big.txt\n 10  ./medium.txt\n  1  ./small.txt\n111  total\n",
+        ));
+
+    // `big.txt` comes first in path order but doesn't fit, so it is skipped.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=cat", "--max-tokens", "50", "ext:txt"])
+        .assert()
+        .success()
+        .stdout(format!("{}\nword\n", "word ".repeat(10)))
+        .stderr(predicate::str::contains(
+            "Skipped 1 file(s) to stay within --max-tokens 50",
+        ));
+
+    // The budget covers what is printed, so a header and fences cost tokens too.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=markdown", "--max-tokens=30", "ext:txt"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("small.txt"))
+        .stdout(predicate::str::contains("medium.txt").not());
+}
+
+#[test]
+fn test_links_format_prints_permalinks() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(root.join("src/lib.rs"), "fn first() {}\n\nfn second() {}\n").unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=rdump", "-c", "user.email=r@example.com"])
+            .args(args)
+            .current_dir(root)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?}");
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+    git(&["remote", "add", "origin", "git@github.com:org/repo.git"]);
+    let head = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(root)
+        .output()
+        .unwrap();
+    let head = String::from_utf8(head.stdout).unwrap().trim().to_string();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root.join("src"))
+        .args(["search", "--format=links", "func:second"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "https://github.com/org/repo/blob/{head}/src/lib.rs#L3\n"
+        ));
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=links", "ext:rs"])
+        .args(["--link-base", "https://git.example.com/org/repo/"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "https://git.example.com/org/repo/blob/{head}/src/lib.rs\n"
+        ));
+}
+
+#[test]
+fn test_markdown_toc_links_to_file_headings() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(root.join("src/lib.rs"), "fn first() {}\n\nfn second() {}\n").unwrap();
+    fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=markdown", "--toc", "func:."])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with(
+        "## Contents\n\n\
+         - [./src/lib.rs](#file-srclibrs) (2 hunks, 30B)\n\
+         - [./src/main.rs](#file-srcmainrs) (1 hunk, 13B)\n"
+    ));
+    assert!(stdout.contains("\n## File: ./src/lib.rs\n"));
+    assert!(stdout.contains("\n## File: ./src/main.rs\n"));
+    // Without --toc, the plain file headers are kept.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=markdown", "func:."])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("File: ./src/lib.rs\n---\n"));
+}
+
+#[test]
+fn test_max_hunks_per_file_caps_output_but_not_counts() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("lib.rs"),
+        "fn a() { log(); }\nfn b() { log(); }\nfn c() { log(); }\nfn d() { log(); }\n",
+    )
+    .unwrap();
+    let rdump = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("rdump").unwrap();
+        cmd.current_dir(root)
+            .args(["search", "--color=never", "--max-hunks-per-file=2"])
+            .args(args)
+            .arg("call:log");
+        cmd
+    };
+
+    rdump(&["--format=grep"])
+        .assert()
+        .success()
+        .stdout(
+            "./lib.rs:1:10:fn a() { log(); }\n\
+             ./lib.rs:2:10:fn b() { log(); }\n\
+             ... (+2 more matches)\n",
+        );
+    rdump(&["--format=hunks"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fn b()"))
+        .stdout(predicate::str::contains("fn c()").not())
+        .stdout(predicate::str::contains("... (+2 more matches)\n"));
+
+    let output = rdump(&["--format=json-extended"]).output().unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["hunks"].as_array().unwrap().len(), 2);
+    assert_eq!(json[0]["more_matches"], 2);
+
+    let output = rdump(&["--format=ndjson"]).output().unwrap();
+    let line: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(line["hunks"].as_array().unwrap().len(), 2);
+    assert_eq!(line["more_matches"], 2);
+
+    rdump(&["--format=quickfix"])
+        .assert()
+        .success()
+        .stdout(
+            "./lib.rs:1:10: fn a() { log(); }\n\
+             ./lib.rs:2:10: fn b() { log(); }\n",
+        );
+
+    rdump(&["--format=count"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("total: 4"));
+}
+
+#[test]
+fn test_rich_format_falls_back_to_markdown_off_a_terminal() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("main.rs"), "fn main() {}\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=rich", "--rich-max-width=40", "ext:rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("```rs\nfn main() {}\n```"))
+        .stdout(predicate::str::contains('┌').not());
+}
+
+fn write_archive(dir: &tempfile::TempDir, archive_name: &str, query: &str) {
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([
+            "search",
+            "--root",
+            "project",
+            "--archive",
+            archive_name,
+            query,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_archive_tar_gz_keeps_relative_paths() {
+    let dir = setup_archive_project();
+    // `ext:bin` comes first so the binary file is never searched as text.
+    write_archive(
+        &dir,
+        "bundle.tar.gz",
+        "ext:bin | (ext:rs & contains:connect)",
+    );
+
+    let file = File::open(dir.path().join("bundle.tar.gz")).unwrap();
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let entries: BTreeMap<String, Vec<u8>> = archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().to_string();
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).unwrap();
+            (name, bytes)
+        })
+        .collect();
+    let names: Vec<&str> = entries.keys().map(|k| k.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["INDEX.md", "src/blob.bin", "src/db.rs", "src/main.rs"]
+    );
+    assert_eq!(entries["src/main.rs"], b"fn main() {\n    connect();\n}\n");
+    assert_eq!(entries["src/blob.bin"], [0u8, 159, 146, 150, 255]);
+
+    let index = String::from_utf8(entries["INDEX.md"].clone()).unwrap();
+    assert!(index.contains("Query: `ext:bin | (ext:rs & contains:connect)`"));
+    assert!(index.contains("- `src/main.rs` (lines 2-2)"));
+    assert!(!index.contains("README.md"));
+}
+
+#[test]
+fn test_archive_zip() {
+    let dir = setup_archive_project();
+    write_archive(&dir, "bundle.zip", "ext:rs & contains:connect");
+
+    let mut zip = zip::ZipArchive::new(File::open(dir.path().join("bundle.zip")).unwrap()).unwrap();
+    let mut names: Vec<String> = zip.file_names().map(String::from).collect();
+    names.sort();
+    assert_eq!(names, vec!["INDEX.md", "src/db.rs", "src/main.rs"]);
+
+    let mut content = String::new();
+    zip.by_name("src/db.rs")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "pub fn connect() {}\n");
+}
+
+#[test]
+fn test_archive_rejects_unknown_extension() {
+    let dir = setup_archive_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["search", "--archive", "bundle.rar", "contains:connect"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported archive 'bundle.rar'"));
+}
+
+#[test]
+fn test_output_dir_writes_one_file_per_match() {
+    let dir = setup_output_dir_project();
+    let out = tempdir().unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("ext:rs")
+        .arg("--format=markdown")
+        .arg("--output-dir")
+        .arg(out.path())
+        .assert()
+        .success()
+        .stdout("");
+
+    let main_md = out.path().join("src/main.rs.md");
+    let lib_md = out.path().join("src/nested/lib.rs.md");
+    assert!(main_md.exists());
+    assert!(lib_md.exists());
+    assert!(!out.path().join("README.md.md").exists());
+
+    let main_content = fs::read_to_string(main_md).unwrap();
+    assert!(main_content.contains("```rs\nfn main() {}\n```"));
+    assert!(!main_content.contains("pub fn lib()"));
+    assert!(fs::read_to_string(lib_md)
+        .unwrap()
+        .contains("pub fn lib() {}"));
+}
+
+#[test]
+fn test_output_dir_extension_follows_format() {
+    let dir = setup_output_dir_project();
+    let out = tempdir().unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("ext:rs")
+        .arg("--format=json")
+        .arg("--output-dir")
+        .arg(out.path())
+        .assert()
+        .success();
+
+    let json = fs::read_to_string(out.path().join("src/main.rs.json")).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.as_array().unwrap().len(), 1);
+    assert_eq!(parsed[0]["content"], "fn main() {}\n");
+}
+
+#[test]
+fn test_output_dir_conflicts_with_output() {
+    let dir = setup_output_dir_project();
+    let out = tempdir().unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("ext:rs")
+        .arg("--output")
+        .arg(out.path().join("all.txt"))
+        .arg("--output-dir")
+        .arg(out.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
@@ -3,7 +3,7 @@ use predicates::prelude::*;
 use std::process::Command;
 
 mod common;
-use common::setup_test_project;
+use common::{setup_env_project, setup_route_project, setup_semantic_project, setup_test_project};
 
 #[test]
 fn test_struct_predicate_go() {
@@ -60,3 +60,123 @@ fn test_struct_not_found_go() {
         .success()
         .stdout(predicate::str::is_empty());
 }
+
+#[test]
+fn test_interface_predicate_go() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("interface:Greeter")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("type Greeter interface"))
+        .stdout(predicate::str::contains("type Person struct").not());
+
+    // An interface is not a struct.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("struct:Greeter")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_struct_is_not_an_interface_go() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("struct:Person")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("type Person struct"))
+        .stdout(predicate::str::contains("type Greeter interface").not());
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("interface:Person")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_def_predicate_matches_both_go() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=symbols")
+        .arg("def:Greeter | def:Person")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Greeter\t./greet.go\t4\tinterface"))
+        .stdout(predicate::str::contains("Person\t./greet.go\t8\tstruct"));
+}
+
+#[test]
+fn test_module_predicate_go_package() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("module:server")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("server.go"))
+        .stdout(predicate::str::contains("main.go").not());
+}
+
+#[test]
+fn test_route_predicate_go() {
+    let dir = setup_route_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("route:/users & ext:go")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "mux.HandleFunc(\"/users\", usersHandler)",
+        ))
+        // Calls on anything but a router are not routes.
+        .stdout(predicate::str::contains("fmt.Println").not());
+}
+
+#[test]
+fn test_env_predicate_go() {
+    let dir = setup_env_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("env:DATABASE_URL & ext:go")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("os.Getenv(\"DATABASE_URL\")"));
+}
+
+#[test]
+fn test_env_predicate_go_raw_string() {
+    let dir = setup_env_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("env:REDIS_URL")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("os.LookupEnv(`REDIS_URL`)"));
+}
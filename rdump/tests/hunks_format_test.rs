@@ -43,3 +43,151 @@ fn test_hunks_format() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_hunks_format_zero_before_and_after_context() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, root) = setup_hunks_test_dir();
+
+    let mut cmd = Command::cargo_bin("rdump")?;
+    cmd.current_dir(&root);
+    cmd.arg("search")
+        .arg("contains:'line 3'")
+        .arg("-A")
+        .arg("0")
+        .arg("-B")
+        .arg("0");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("line 3"))
+        .stdout(predicate::str::contains("line 2").not())
+        .stdout(predicate::str::contains("line 4").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_hunks_format_after_context() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, root) = setup_hunks_test_dir();
+
+    let mut cmd = Command::cargo_bin("rdump")?;
+    cmd.current_dir(&root);
+    cmd.arg("search").arg("contains:'line 1'").arg("-A").arg("3");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("line 1\nline 2\nline 3\nline 4"))
+        .stdout(predicate::str::contains("line 5").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_hunks_format_before_context() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, root) = setup_hunks_test_dir();
+
+    let mut cmd = Command::cargo_bin("rdump")?;
+    cmd.current_dir(&root);
+    cmd.arg("search").arg("contains:'line 5'").arg("-B").arg("2");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("line 3\nline 4\nline 5"))
+        .stdout(predicate::str::contains("line 2").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_hunks_format_after_context_overrides_context() -> Result<(), Box<dyn std::error::Error>> {
+    let (_dir, root) = setup_hunks_test_dir();
+
+    let mut cmd = Command::cargo_bin("rdump")?;
+    cmd.current_dir(&root);
+    cmd.arg("search")
+        .arg("contains:'line 3'")
+        .arg("-C")
+        .arg("1")
+        .arg("-A")
+        .arg("0");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("line 2\nline 3"))
+        .stdout(predicate::str::contains("line 4").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_hunks_format_reports_whole_function() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(
+        dir.path().join("lib.rs"),
+        "use std::io;\n\nfn run() {\n    let x = 1;\n    println!(\"{x}\");\n}\n\nfn other() {}\n",
+    )?;
+
+    let mut cmd = Command::cargo_bin("rdump")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "--format=hunks", "-C", "0", "func:run"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("fn run() {"))
+        .stdout(predicate::str::contains("let x = 1;"))
+        .stdout(predicate::str::contains("println!"))
+        .stdout(predicate::str::contains("use std::io").not())
+        .stdout(predicate::str::contains("fn other").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_json_extended_hunk_spans_function_body() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(
+        dir.path().join("tool.py"),
+        "import os\n\ndef run():\n    path = os.getcwd()\n    return path\n",
+    )?;
+
+    let output = Command::cargo_bin("rdump")?
+        .current_dir(dir.path())
+        .args(["search", "--format=json-extended", "func:run"])
+        .output()?;
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let hunk = &json[0]["hunks"][0];
+    assert_eq!(hunk["start_line"], 3);
+    assert_eq!(hunk["end_line"], 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_hunk_granularity_overrides_profile() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    fs::write(
+        dir.path().join("tool.py"),
+        "import os\n\ndef run():\n    return os.getcwd()\n",
+    )?;
+
+    let hunk = |granularity: &str| -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("rdump")?
+            .current_dir(dir.path())
+            .args(["search", "--format=json-extended", "func:run"])
+            .arg(format!("--hunk-granularity={granularity}"))
+            .output()?;
+        assert!(output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        Ok(json[0]["hunks"][0].clone())
+    };
+
+    assert_eq!(hunk("name")?["text"], "run");
+    assert_eq!(hunk("body")?["start_line"], 3);
+    let file = hunk("file")?;
+    assert_eq!(file["start_line"], 1);
+    assert!(file["text"].as_str().unwrap().starts_with("import os"));
+
+    Ok(())
+}
@@ -107,3 +107,164 @@ fn test_rdumpignore_unignore_overrides_default_ignores() -> Result<(), Box<dyn s
 
     Ok(())
 }
+
+#[test]
+fn test_dry_run_lists_non_ignored_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path();
+    fs::create_dir(root.join(".git"))?;
+    fs::write(root.join(".gitignore"), "*.log\nbuild/\n")?;
+    fs::write(root.join("main.rs"), "fn main() {}")?;
+    fs::write(root.join("debug.log"), "log")?;
+    fs::create_dir_all(root.join("build"))?;
+    fs::write(root.join("build/out.rs"), "fn out() {}")?;
+    fs::create_dir_all(root.join("src"))?;
+    fs::write(root.join("src/lib.rs"), "pub fn lib() {}")?;
+
+    // No query is needed, and none is evaluated.
+    let output = Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--dry-run"])
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    let paths: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.trim_start_matches("./"))
+        .collect();
+    assert_eq!(paths, ["main.rs", "src/lib.rs"]);
+
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--dry-run-count", "func:main"])
+        .assert()
+        .success()
+        .stdout("2\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_dry_run_report_applies_the_metadata_filter() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path();
+    fs::create_dir_all(root.join("src"))?;
+    fs::write(root.join("src/big.rs"), "x".repeat(2048))?;
+    fs::write(root.join("src/small.rs"), "fn main() {}")?;
+    fs::write(root.join("notes.md"), "# notes")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--dry-run-report", "ext:rs & func:main"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Files walked:       3\n"))
+        .stdout(predicate::str::contains("Files to evaluate:  2\n"))
+        .stdout(predicate::str::contains("Bytes to read:      2.0K\n"))
+        .stdout(predicate::str::contains("\nLargest files:\n      2.0K  ./src/big.rs\n"))
+        .stdout(predicate::str::contains("notes.md").not());
+
+    // A metadata-only query reads nothing; no query evaluates every file.
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--dry-run-report", "ext:md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Files to evaluate:  1\n"))
+        .stdout(predicate::str::contains("0 (the query needs only metadata)"));
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--dry-run-report"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Files to evaluate:  3\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_no_default_ignore_keeps_gitignore() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path();
+
+    // Initialize a git repository so .gitignore is respected
+    StdCommand::new("git")
+        .arg("init")
+        .current_dir(root)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+
+    fs::create_dir_all(root.join("node_modules/left-pad"))?;
+    fs::write(root.join("node_modules/left-pad/index.js"), "module.exports = pad;")?;
+    fs::write(root.join("app.js"), "const pad = require('left-pad');")?;
+    fs::write(root.join("secret.js"), "const pad = 1;")?;
+    fs::write(root.join(".gitignore"), "secret.js\n")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--format=paths", "contains:pad"])
+        .assert()
+        .success()
+        .stdout("./app.js\n");
+
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--format=paths", "--no-default-ignore", "contains:pad"])
+        .assert()
+        .success()
+        .stdout("./app.js\n./node_modules/left-pad/index.js\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_ignore_file_and_no_rdumpignore() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path();
+
+    fs::write(root.join("app.log"), "log content")?;
+    fs::write(root.join("app.txt"), "text content")?;
+    fs::write(root.join("custom.ignore"), "*.log\n")?;
+    fs::write(root.join(".rdumpignore"), "*.txt\n")?;
+
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--format=paths", "contains:content"])
+        .args(["--ignore-file", "custom.ignore"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--format=paths", "contains:content"])
+        .args(["--ignore-file", "custom.ignore", "--no-rdumpignore"])
+        .assert()
+        .success()
+        .stdout("./app.txt\n");
+
+    // --no-ignore turns off .rdumpignore, but not a file named on the command line.
+    Command::cargo_bin("rdump")?
+        .current_dir(root)
+        .args(["search", "--format=paths", "contains:content"])
+        .args(["--ignore-file", "custom.ignore", "--no-ignore"])
+        .assert()
+        .success()
+        .stdout("./app.txt\n");
+
+    for extra in [&[][..], &["--no-ignore"]] {
+        Command::cargo_bin("rdump")?
+            .current_dir(root)
+            .args(["search", "--ignore-file", "missing.ignore"])
+            .args(extra)
+            .arg("contains:content")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "ignore file 'missing.ignore' does not exist",
+            ));
+    }
+
+    Ok(())
+}
@@ -3,7 +3,9 @@ use predicates::prelude::*;
 use std::process::Command;
 
 mod common;
-use common::setup_test_project;
+use common::{
+    setup_complexity_project, setup_env_project, setup_modern_java_project, setup_test_project,
+};
 
 #[test]
 fn test_class_predicate_java() {
@@ -69,4 +71,66 @@ fn test_class_not_found_java() {
         .assert()
         .success()
         .stdout(predicate::str::is_empty());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_modern_java_predicates() {
+    let dir = setup_modern_java_project();
+    for (query, expected) in [
+        ("record:Point", "public record Point(int x, int y) {}"),
+        ("import:java.util", "import java.util.List;"),
+        ("func:stream", "stream = () -> names.stream();"),
+        ("class:AbstractFactory", "public abstract class AbstractFactory {"),
+        ("sealed:Shape", "public sealed interface Shape permits Circle, Square {}"),
+        ("annotation:Audited", "public @interface Audited {"),
+        ("def:Point & def:Audited", "Shapes.java"),
+    ] {
+        Command::cargo_bin("rdump")
+            .unwrap()
+            .current_dir(dir.path())
+            .args(["search", query])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(expected));
+    }
+}
+
+#[test]
+fn test_sealed_needs_the_modifier() {
+    let dir = setup_modern_java_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["search", "sealed:AbstractFactory"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_env_predicate_java() {
+    let dir = setup_env_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("env:DATABASE_URL & ext:java")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("System.getenv(\"DATABASE_URL\")"));
+}
+
+#[test]
+fn test_complexity_predicate_never_matches_java() {
+    let dir = setup_complexity_project();
+    // `complexity:` doesn't support Java, so it never matches there.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("complexity:>0 & ext:java")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
@@ -0,0 +1,99 @@
+use assert_cmd::prelude::*;
+use std::fs;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tempfile::{tempdir, TempDir};
+
+/// Creates `count` Rust files, every third of which calls `connect`.
+fn setup_corpus(count: usize) -> TempDir {
+    let dir = tempdir().unwrap();
+    for i in 0..count {
+        let body = if i % 3 == 0 { "connect();" } else { "disconnect();" };
+        let mut content = String::new();
+        for j in 0..50 {
+            content.push_str(&format!(
+                "fn f{j}(x: u32) -> u32 {{\n    if x > {j} {{ {body} }}\n    x\n}}\n"
+            ));
+        }
+        fs::write(dir.path().join(format!("file_{i:04}.rs")), content).unwrap();
+    }
+    dir
+}
+
+fn run_search(dir: &TempDir, extra_args: &[&str]) -> (Vec<u8>, Duration) {
+    let start = Instant::now();
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "call:connect"])
+        .args(extra_args)
+        .output()
+        .unwrap();
+    let elapsed = start.elapsed();
+    assert!(output.status.success(), "{output:?}");
+    (output.stdout, elapsed)
+}
+
+#[test]
+fn test_jobs_1_matches_parallel_results() {
+    let dir = setup_corpus(60);
+    let (parallel, _) = run_search(&dir, &[]);
+    let (serial, _) = run_search(&dir, &["--jobs", "1"]);
+    assert_eq!(serial, parallel);
+    assert_eq!(String::from_utf8(serial).unwrap().lines().count(), 20);
+
+    let (short, _) = run_search(&dir, &["-j", "2"]);
+    assert_eq!(short, parallel);
+
+    let (threads, _) = run_search(&dir, &["--threads", "1"]);
+    assert_eq!(threads, parallel);
+}
+
+#[test]
+fn test_jobs_applies_to_sorting_and_stats() {
+    let dir = setup_corpus(30);
+    let (parallel, _) = run_search(&dir, &["--sort=size", "--find"]);
+    let (serial, _) = run_search(&dir, &["--sort=size", "--find", "--jobs", "1"]);
+    assert_eq!(serial, parallel);
+
+    let stats = |extra_args: &[&str]| {
+        Command::cargo_bin("rdump")
+            .unwrap()
+            .current_dir(dir.path())
+            .args(["stats", "--format=json"])
+            .args(extra_args)
+            .output()
+            .unwrap()
+    };
+    let (parallel, serial) = (stats(&[]), stats(&["-j", "1"]));
+    assert!(serial.status.success(), "{serial:?}");
+    assert_eq!(serial.stdout, parallel.stdout);
+}
+
+#[test]
+fn test_jobs_0_is_rejected() {
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .args(["search", "--jobs", "0", "ext:rs"])
+        .assert()
+        .failure();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .args(["search", "--threads", "0", "ext:rs"])
+        .assert()
+        .failure();
+}
+
+// Timing-dependent, and needs at least four CPUs to show a difference.
+#[test]
+#[ignore]
+fn test_jobs_1_is_slower_than_jobs_4() {
+    let dir = setup_corpus(400);
+    let (serial_output, serial) = run_search(&dir, &["--jobs", "1"]);
+    let (parallel_output, parallel) = run_search(&dir, &["--jobs", "4"]);
+    assert_eq!(serial_output, parallel_output);
+    assert!(
+        serial > parallel,
+        "--jobs 1 took {serial:?}, --jobs 4 took {parallel:?}"
+    );
+}
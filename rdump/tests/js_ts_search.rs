@@ -3,7 +3,10 @@ use predicates::prelude::*;
 use std::process::Command;
 
 mod common;
-use common::setup_test_project;
+use common::{
+    setup_complexity_project, setup_env_project, setup_project_with, setup_route_project,
+    setup_semantic_project, setup_test_project, setup_url_project,
+};
 
 #[test]
 fn test_def_finds_javascript_class() {
@@ -143,4 +146,143 @@ fn test_def_not_found_js_ts() {
         .assert()
         .success()
         .stdout(predicate::str::is_empty());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_return_type_predicate_typescript() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("return_type:Promise & ext:ts")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "async function fetchUser(id: number): Promise<User> {",
+        ))
+        .stdout(predicate::str::contains(
+            "send(body: string): Promise<void> {",
+        ))
+        .stdout(predicate::str::contains("function formatUser").not());
+}
+
+#[test]
+fn test_route_predicate_javascript() {
+    let dir = setup_route_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("route:/users & ext:js")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("app.get('/users/:id'"))
+        // Calls on anything but a router are not routes.
+        .stdout(predicate::str::contains("cache.get").not())
+        .stdout(predicate::str::contains("app.post").not());
+}
+
+#[test]
+fn test_env_predicate_javascript() {
+    let dir = setup_env_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("env:DATABASE_URL & ext:js")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("process.env.DATABASE_URL"))
+        .stdout(predicate::str::contains("config.env.DATABASE_URL").not());
+}
+
+#[test]
+fn test_env_predicate_javascript_subscript() {
+    let dir = setup_env_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("env:PORT & ext:js")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("process.env[\"PORT\"]"))
+        .stdout(predicate::str::contains("DATABASE_URL").not());
+}
+
+#[test]
+fn test_url_predicate_template_string() {
+    let dir = setup_url_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("url:. & ext:ts")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("http://localhost:8080/health"))
+        .stdout(predicate::str::contains("const label").not());
+}
+
+#[test]
+fn test_url_predicate_scheme() {
+    let dir = setup_url_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("url:http: & !ext:py")
+        .arg("--format=paths")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("client.ts"))
+        .stdout(predicate::str::contains("client.py").not());
+}
+
+#[test]
+fn test_complexity_predicate_typescript() {
+    let dir = setup_complexity_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("complexity:>4 & ext:ts")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "function gnarly(x: number, y: number)",
+        ))
+        .stdout(predicate::str::contains("simple").not());
+}
+
+#[test]
+fn test_complexity_predicate_scores_function_expressions() {
+    let dir = setup_project_with([(
+        "handlers.ts",
+        r#"
+const onEvent = function (x: number): number {
+    if (x > 0) {
+        return 1;
+    }
+    return x ? 2 : 3;
+};
+
+export function wrap(): number {
+    return 0;
+}
+"#,
+    )]);
+    // The function expression is scored on its own, not left to the enclosing file.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("complexity:3")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "const onEvent = function (x: number): number {",
+        ))
+        .stdout(predicate::str::contains("export function wrap").not());
+}
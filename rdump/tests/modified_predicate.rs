@@ -71,3 +71,47 @@ fn test_invalid_date_format() {
         .failure()
         .stderr(predicate::str::contains("Invalid date format"));
 }
+
+#[test]
+fn test_since_and_before() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    for name in ["old.rs", "new.rs", "new.txt"] {
+        File::create(root.join(name)).unwrap();
+    }
+    let old = chrono::NaiveDate::from_ymd_opt(2023, 6, 1)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+        .and_local_timezone(chrono::Local)
+        .unwrap();
+    File::options()
+        .write(true)
+        .open(root.join("old.rs"))
+        .unwrap()
+        .set_modified(old.into())
+        .unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=paths", "since:2024-01-01 & ext:rs"])
+        .assert()
+        .success()
+        .stdout("./new.rs\n");
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=paths", "before:>2024-01-01"])
+        .assert()
+        .failure();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .args(["search", "--format=paths", "before:2024-01-01"])
+        .assert()
+        .success()
+        .stdout("./old.rs\n");
+}
@@ -3,7 +3,11 @@ use predicates::prelude::*;
 use std::process::Command;
 
 mod common;
-use common::setup_test_project;
+use common::{
+    setup_complexity_project, setup_env_project, setup_funclines_project, setup_route_project,
+    setup_semantic_project, setup_signature_project, setup_tag_project, setup_test_project,
+    setup_url_project,
+};
 
 #[test]
 fn test_def_finds_python_class() {
@@ -101,4 +105,282 @@ fn test_def_not_found_python() {
         .assert()
         .success()
         .stdout(predicate::str::is_empty());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_import_matches_python_modules_exactly() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("plain.py"), "import os\n").unwrap();
+    std::fs::write(dir.path().join("nested.py"), "import os.path\n").unwrap();
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "import:os"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("plain.py"))
+        .stdout(predicate::str::contains("nested.py").not());
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["search", "--format=paths", "import:os.path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("nested.py"))
+        .stdout(predicate::str::contains("plain.py").not());
+}
+
+#[test]
+fn test_module_predicate_python_all() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("module:__all__ & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("shapes.py"))
+        .stdout(predicate::str::contains(
+            r#"__all__ = ["Circle", "Square"]"#,
+        ))
+        // A local variable named `__all__` is not a module declaration.
+        .stdout(predicate::str::contains("__all__ = []").not())
+        .stdout(predicate::str::contains("util.py").not());
+}
+
+#[test]
+fn test_module_predicate_python_package_init() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("module:geometry")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("__init__.py"))
+        .stdout(predicate::str::contains("shapes.py").not());
+}
+
+#[test]
+fn test_decorator_predicate_finds_only_decorated_methods() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("decorator:property")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("@property"))
+        .stdout(predicate::str::contains("def area(self):"))
+        .stdout(predicate::str::contains("return 3.14 * self.radius ** 2"))
+        .stdout(predicate::str::contains("def scale").not())
+        .stdout(predicate::str::contains("def plain").not())
+        .stdout(predicate::str::contains("def __init__").not());
+}
+
+#[test]
+fn test_decorator_predicate_matches_name_not_arguments() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("decorator:lru_cache & func:compute")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "@functools.lru_cache(maxsize=None)",
+        ))
+        .stdout(predicate::str::contains("def compute(n):"));
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("decorator:maxsize")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_route_predicate_python() {
+    let dir = setup_route_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("route:/users & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("def list_users():"))
+        // Plain string literals are not routes.
+        .stdout(predicate::str::contains("def health").not())
+        .stdout(predicate::str::contains("def helper").not());
+}
+
+#[test]
+fn test_route_predicate_wildcard() {
+    let dir = setup_route_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("route:. & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("def list_users():"))
+        .stdout(predicate::str::contains("def health():"))
+        .stdout(predicate::str::contains("def helper").not());
+}
+
+#[test]
+fn test_env_predicate_python() {
+    let dir = setup_env_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("env:DATABASE_URL & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("os.environ[\"DATABASE_URL\"]"))
+        .stdout(predicate::str::contains("DEBUG").not());
+}
+
+#[test]
+fn test_env_predicate_python_get() {
+    let dir = setup_env_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("env:SECRET_KEY")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("os.environ.get(\"SECRET_KEY\")"));
+}
+
+#[test]
+fn test_url_predicate_filters_by_substring() {
+    let dir = setup_url_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("url:internal.corp")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "https://api.internal.corp/v1/users",
+        ))
+        .stdout(predicate::str::contains("https://example.com").not())
+        // A plain string mentioning the host is not a URL.
+        .stdout(predicate::str::contains("client.ts").not());
+}
+
+#[test]
+fn test_url_predicate_wildcard_ignores_comments() {
+    let dir = setup_url_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("url:. & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "https://api.internal.corp/v1/users",
+        ))
+        .stdout(predicate::str::contains("https://example.com"))
+        .stdout(predicate::str::contains("docs.example.com").not())
+        .stdout(predicate::str::contains("not a url").not());
+}
+
+#[test]
+fn test_complexity_predicate_python() {
+    let dir = setup_complexity_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("complexity:>4 & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("def gnarly(x, y):"))
+        .stdout(predicate::str::contains("simple").not());
+}
+
+#[test]
+fn test_complexity_predicate_exact() {
+    let dir = setup_complexity_project();
+    // `simple` has no branches, so its complexity is exactly 1.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("complexity:1 & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("def simple():"))
+        .stdout(predicate::str::contains("def gnarly").not());
+}
+
+#[test]
+fn test_funclines_predicate_python() {
+    let dir = setup_funclines_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("funclines:>5 & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("def big():"))
+        .stdout(predicate::str::contains("tiny").not());
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("funclines:<3 & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("def tiny():"))
+        .stdout(predicate::str::contains("def big").not());
+}
+
+#[test]
+fn test_tag_predicate_python() {
+    let dir = setup_tag_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("tag:. & ext:py")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# TODO(alice): cache this"))
+        .stdout(predicate::str::contains("def fetch():"));
+}
+
+#[test]
+fn test_signature_predicate_multiline_python() {
+    let dir = setup_signature_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("signature:'def handle(request, user=None)'")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("return respond(request)"))
+        .stdout(predicate::str::contains("handle_later").not());
+}
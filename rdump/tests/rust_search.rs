@@ -1,9 +1,14 @@
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
+use std::fs;
 use std::process::Command;
 
 mod common;
-use common::setup_test_project;
+use common::{
+    setup_complexity_project, setup_env_project, setup_funclines_project, setup_project_with,
+    setup_route_project, setup_semantic_project, setup_signature_project, setup_tag_project,
+    setup_test_project, setup_unsafe_project,
+};
 
 #[test]
 fn test_def_finds_struct_in_correct_file() {
@@ -266,3 +271,435 @@ fn test_func_not_found() {
         .success()
         .stdout(predicate::str::is_empty());
 }
+
+#[test]
+fn test_module_predicate_rust_mod_declarations() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("module:tests & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs"))
+        .stdout(predicate::str::contains("main.rs").not());
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("module:parser")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs"))
+        .stdout(predicate::str::contains("main.rs"));
+}
+
+#[test]
+fn test_generic_predicate_matches_where_clause_bound() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("generic:Serialize & struct:MyConfig")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("generics.rs"))
+        .stdout(predicate::str::contains("T: Serialize + Clone,"))
+        .stdout(predicate::str::contains("inner: T,"))
+        .stdout(predicate::str::contains("plain_config.rs").not());
+}
+
+#[test]
+fn test_generic_predicate_matches_type_parameters() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("generic:Debug")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "pub fn process<T: Debug>(item: T) {",
+        ))
+        .stdout(predicate::str::contains("MyConfig").not())
+        .stdout(predicate::str::contains("fn handle").not());
+
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("generic:T & func:process")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("generics.rs"));
+}
+
+#[test]
+fn test_return_type_predicate_rust() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("return_type:Result & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fn load() -> Result<(), Error> {"))
+        .stdout(predicate::str::contains(
+            "fn read_name() -> Result<String, anyhow::Error> {",
+        ))
+        .stdout(predicate::str::contains("fn find").not())
+        // A `Result` parameter is not a return type.
+        .stdout(predicate::str::contains("fn log_result").not());
+}
+
+#[test]
+fn test_method_call_predicate_skips_free_calls() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=grep")
+        .arg("method_call:connect")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("self.connect()"))
+        .stdout(predicate::str::contains("    connect()").not())
+        .stdout(predicate::str::contains("db::connect()").not());
+}
+
+#[test]
+fn test_free_call_predicate_skips_method_calls() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=grep")
+        .arg("free_call:connect")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("client.rs:19:5:    connect()"))
+        .stdout(predicate::str::contains("db::connect()"))
+        .stdout(predicate::str::contains("self.connect()").not());
+}
+
+#[test]
+fn test_call_predicate_still_matches_both() {
+    let dir = setup_semantic_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=grep")
+        .arg("call:connect")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("self.connect()"))
+        .stdout(predicate::str::contains("client.rs:19:5:    connect()"));
+}
+
+#[test]
+fn test_route_predicate_rust() {
+    let dir = setup_route_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("route:/users & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("async fn list_users() -> String"))
+        // Plain string literals are not routes.
+        .stdout(predicate::str::contains("fn not_a_route").not());
+}
+
+#[test]
+fn test_route_predicate_axum_router() {
+    let dir = setup_route_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("route:/orders & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Router::new().route(\"/orders\", get(list_orders))",
+        ))
+        .stdout(predicate::str::contains("list_users").not());
+}
+
+#[test]
+fn test_env_predicate_rust() {
+    let dir = setup_env_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("env:DATABASE_URL & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("std::env::var(\"DATABASE_URL\")"))
+        .stdout(predicate::str::contains("LOG_LEVEL").not());
+}
+
+#[test]
+fn test_env_predicate_rust_macro() {
+    let dir = setup_env_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("env:PORT & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("env!(\"PORT\")"))
+        .stdout(predicate::str::contains("DATABASE_URL").not());
+}
+
+#[test]
+fn test_complexity_predicate_rust() {
+    let dir = setup_complexity_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("complexity:>4 & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fn gnarly(x: i32, y: i32)"))
+        .stdout(predicate::str::contains("simple").not());
+}
+
+#[test]
+fn test_complexity_predicate_lower_bound() {
+    let dir = setup_complexity_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("complexity:<=1 & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fn simple()"))
+        .stdout(predicate::str::contains("fn gnarly").not());
+}
+
+#[test]
+fn test_complexity_predicate_invalid_value() {
+    let dir = setup_complexity_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("complexity:>lots")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid number"));
+}
+
+#[test]
+fn test_funclines_predicate_finds_long_functions() {
+    let dir = setup_funclines_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("funclines:>5 & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fn process()"))
+        .stdout(predicate::str::contains("fn other_long()"))
+        .stdout(predicate::str::contains("wrapper").not());
+}
+
+#[test]
+fn test_funclines_predicate_finds_trivial_wrappers() {
+    let dir = setup_funclines_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("funclines:<3 & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fn wrapper() -> i32 { 1 }"))
+        .stdout(predicate::str::contains("process").not());
+}
+
+#[test]
+fn test_funclines_predicate_composes_with_func_per_file() {
+    let dir = setup_funclines_project();
+    let root = dir.path();
+    let long_body = "    let x = 1;\n".repeat(10);
+    fs::write(
+        root.join("short_process.rs"),
+        format!("fn process() {{}}\n\nfn helper() {{\n{long_body}}}\n"),
+    )
+    .unwrap();
+    fs::write(root.join("only_short.rs"), "fn process() {}\n").unwrap();
+
+    // `&` combines the predicates per file, so a short `process` next to a long
+    // function still matches. Files without a long function, or without a
+    // `process`, are excluded.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(root)
+        .arg("search")
+        .arg("--format=paths")
+        .arg("func:process & funclines:>5")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs"))
+        .stdout(predicate::str::contains("short_process.rs"))
+        .stdout(predicate::str::contains("only_short.rs").not())
+        .stdout(predicate::str::contains("app.py").not());
+}
+
+#[test]
+fn test_unsafe_predicate_wildcard_finds_all_regions() {
+    let dir = setup_unsafe_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("unsafe:*")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pub unsafe fn reinterpret"))
+        .stdout(predicate::str::contains("unsafe impl Send for Handle {}"))
+        .stdout(predicate::str::contains("unsafe { ptr.read() }"))
+        .stdout(predicate::str::contains("fn safe_code").not());
+}
+
+#[test]
+fn test_unsafe_predicate_filters_by_identifier() {
+    let dir = setup_unsafe_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("unsafe:transmute")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("std::mem::transmute(x)"))
+        .stdout(predicate::str::contains("ptr.read()").not())
+        .stdout(predicate::str::contains("unsafe impl").not());
+}
+
+#[test]
+fn test_unsafe_predicate_ignores_other_languages() {
+    let dir = setup_unsafe_project();
+    fs::write(dir.path().join("app.py"), "unsafe = True\n").unwrap();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("unsafe:*")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs"))
+        .stdout(predicate::str::contains("app.py").not());
+}
+
+#[test]
+fn test_tag_predicate_matches_leading_marker_only() {
+    let dir = setup_tag_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("tag:FIXME")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("// FIXME: handle overflow"))
+        // The following line of code is included for context.
+        .stdout(predicate::str::contains("fn add(a: u8, b: u8)"))
+        .stdout(predicate::str::contains("merely mentions").not())
+        .stdout(predicate::str::contains("app.py").not());
+}
+
+#[test]
+fn test_tag_predicate_wildcard_uses_default_markers() {
+    let dir = setup_tag_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("tag:. & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("// FIXME: handle overflow"))
+        .stdout(predicate::str::contains("PERF").not());
+}
+
+#[test]
+fn test_tag_predicate_wildcard_uses_configured_markers() {
+    let dir = setup_tag_project();
+    fs::write(dir.path().join(".rdump.toml"), "tags = [\"PERF\"]\n").unwrap();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("tag:. & ext:rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("// PERF: allocates on every call"))
+        .stdout(predicate::str::contains("// FIXME: handle overflow"));
+}
+
+#[test]
+fn test_signature_predicate_rust() {
+    let dir = setup_signature_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("signature:'fn new(config: &Config)'")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "pub fn new(config: &Config) -> Self {",
+        ))
+        // The whole function is returned, body included.
+        .stdout(predicate::str::contains("Server { port: config.port }"))
+        // Text in the body doesn't count as part of the signature.
+        .stdout(predicate::str::contains("new_default").not());
+}
+
+#[test]
+fn test_complexity_predicate_counts_match_arms_after_the_first() {
+    let dir = setup_project_with([(
+        "arms.rs",
+        r#"
+fn three_arms(x: u8) -> u8 {
+    match x {
+        0 => 1,
+        // The comment doesn't hide the arm after it.
+        1 => 2,
+        _ => 3,
+    }
+}
+"#,
+    )]);
+    // Three arms are two branch points, so the complexity is 3.
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("complexity:3")
+        .assert()
+        .success()
+        .stdout("./arms.rs\n");
+}
@@ -4,7 +4,7 @@ use predicates::prelude::*;
 use std::process::Command;
 
 mod common;
-use common::setup_test_project;
+use common::{setup_impl_project, setup_test_project};
 
 #[test]
 fn test_trait_predicate() {
@@ -33,3 +33,56 @@ fn test_impl_predicate() {
         .stdout(predicate::str::contains("src/traits.rs"))
         .stdout(predicate::str::contains("impl Summary for NewsArticle"));
 }
+
+#[test]
+fn test_impl_predicate_matches_all_impl_blocks_for_type() {
+    let dir = setup_impl_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("impl:User")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("impl User {"))
+        // The whole block is reported, not just the type name.
+        .stdout(predicate::str::contains("Self { name }"))
+        .stdout(predicate::str::contains(
+            "impl std::fmt::Display for User {",
+        ))
+        .stdout(predicate::str::contains("write!(f, \"{}\", self.name)"))
+        .stdout(predicate::str::contains(
+            "impl Default for crate::models::User {",
+        ))
+        .stdout(predicate::str::contains("impl Order").not())
+        .stdout(predicate::str::contains("pub fn total").not());
+}
+
+#[test]
+fn test_impl_predicate_matches_generic_types() {
+    let dir = setup_impl_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("impl:Wrapper")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("impl<T> Wrapper<T> {"))
+        .stdout(predicate::str::contains("pub fn inner(&self) -> &T"))
+        .stdout(predicate::str::contains("impl User").not());
+}
+
+#[test]
+fn test_impl_predicate_does_not_match_trait_name() {
+    let dir = setup_impl_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg("search")
+        .arg("--format=paths")
+        .arg("impl:Display")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
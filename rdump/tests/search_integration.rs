@@ -1,5 +1,5 @@
 use anyhow::Result;
-use rdump::{commands::search::run_search, ColorChoice, Format, SearchArgs};
+use rdump::{commands::search::run_search, ColorChoice, Format, SearchArgs, SortKey};
 use std::fs;
 use std::path::Path;
 use tempfile::tempdir;
@@ -9,18 +9,75 @@ use tempfile::tempdir;
 fn create_test_args(root: &Path, query: &str) -> SearchArgs {
     SearchArgs {
         query: Some(query.to_string()), // The query is a single string
-        root: root.to_path_buf(),
+        root: vec![root.to_path_buf()],
+        workspace: false,
+        absolute_paths: false,
+        files_from: None,
+        files_from0: None,
         preset: vec![],
+        include: vec![],
+        exclude: vec![],
+        invert_match: false,
+        whole_file: false,
+        file_type: vec![],
         output: None,
         line_numbers: false,
         no_headers: false,
+        quiet: false,
+        verbose: false,
         format: Format::Paths,
         no_ignore: true, // Crucial for hermetic tests
+        no_default_ignore: false,
+        no_rdumpignore: false,
+        ignore_file: vec![],
         hidden: true,    // Crucial for hermetic tests
+        follow: false,
+        dry_run: false,
+        dry_run_count: false,
+        dry_run_report: false,
         color: ColorChoice::Never,
         max_depth: None,
+        max_hunks_per_file: None,
+        explicit_flags: Default::default(),
+        hunk_granularity: None,
+        rich_max_width: None,
+        min_depth: None,
         context: None,
+        before_context: None,
+        after_context: None,
+        output_dir: None,
+        archive: None,
+        copy: false,
+        interactive: false,
+        template: None,
+        json_full_content: false,
+        toc: false,
+        link_base: None,
+        unsorted: false,
+        sort: SortKey::Path,
+        reverse: false,
+        limit: None,
+        offset: 0,
+        sample: None,
+        seed: None,
+        max_tokens: None,
+        max_filesize: None,
+        strict_encoding: false,
+        jobs: None,
+        theme: None,
+        merge_adjacent_hunks: 0,
         find: false,
+        print0: false,
+        count: false,
+        stats: false,
+        exit_code: false,
+        silent: false,
+        progress: false,
+        no_progress: false,
+        pager: None,
+        no_pager: false,
+        editor: None,
+        dry_run_editor: false,
     }
 }
 
@@ -0,0 +1,53 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::process::Command;
+
+mod common;
+use common::setup_test_project;
+
+#[test]
+fn test_stats_json_counts_definitions_per_language() {
+    let dir = setup_test_project();
+    let output = Command::cargo_bin("rdump")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["stats", "--format=json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+
+    let stats: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let language = |name: &str| {
+        stats
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|row| row["language"] == name)
+            .unwrap_or_else(|| panic!("no {name} row in {stats}"))
+            .clone()
+    };
+    let rust = language("Rust");
+    assert!(rust["files"].as_u64().unwrap() >= 2);
+    // `struct Cli` and `struct User`, at least.
+    assert!(rust["definitions"].as_u64().unwrap() >= 2);
+    assert!(rust["functions"].as_u64().unwrap() >= 2);
+    assert!(rust["imports"].as_u64().unwrap() >= 1);
+    // `class Helper`.
+    assert!(language("Python")["definitions"].as_u64().unwrap() >= 1);
+}
+
+#[test]
+fn test_stats_table() {
+    let dir = setup_test_project();
+    Command::cargo_bin("rdump")
+        .unwrap()
+        .args(["stats", "--root"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("LANGUAGE"))
+        .stdout(predicate::str::contains("Rust"))
+        .stdout(predicate::str::contains("Python"))
+        .stdout(predicate::str::contains("Markdown").not());
+}